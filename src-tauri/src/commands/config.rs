@@ -1,7 +1,12 @@
-use crate::models::{DnsConfig, RoutingConfig};
+use crate::models::{
+    CoreLogLine, CoreSettings, DnsConfig, FailoverSettings, InboundSettings, LatencySettings,
+    NodeGroup, RoutingConfig, ValidationResult,
+};
+use crate::services::{failover, groups, inbounds, latency, selection, xray};
 use crate::utils::paths;
 use std::fs;
 use std::io::{Read, Seek, SeekFrom};
+use tauri::ipc::Channel;
 
 #[tauri::command]
 pub fn get_dns_config() -> Result<DnsConfig, String> {
@@ -37,12 +42,77 @@ pub fn get_routing_config() -> Result<RoutingConfig, String> {
 
 #[tauri::command]
 pub fn save_routing_config(config: RoutingConfig) -> Result<(), String> {
-    let config_path = paths::get_confdir().join("03_routing.json");
+    groups::save_routing(serde_json::json!({ "routing": config })).map_err(|e| e.to_string())
+}
 
-    let wrapper = serde_json::json!({ "routing": config });
-    let content = serde_json::to_string_pretty(&wrapper).map_err(|e| e.to_string())?;
+#[tauri::command]
+pub fn get_inbound_settings() -> InboundSettings {
+    inbounds::load()
+}
 
-    fs::write(&config_path, content).map_err(|e| e.to_string())
+/// 重写 01_inbounds.json，核心运行中时需重启后生效
+#[tauri::command]
+pub fn save_inbound_settings(settings: InboundSettings) -> Result<(), String> {
+    inbounds::save(&settings).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub fn list_node_groups() -> Vec<NodeGroup> {
+    groups::list()
+}
+
+#[tauri::command]
+pub fn save_node_group(group: NodeGroup) -> Result<(), String> {
+    groups::save(group).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub fn remove_node_group(tag: String) -> Result<(), String> {
+    groups::remove(&tag).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub fn get_latency_settings() -> LatencySettings {
+    latency::settings::load()
+}
+
+#[tauri::command]
+pub fn save_latency_settings(settings: LatencySettings) -> Result<(), String> {
+    latency::settings::save(&settings).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub fn get_failover_settings() -> FailoverSettings {
+    failover::settings::load()
+}
+
+#[tauri::command]
+pub fn save_failover_settings(settings: FailoverSettings) -> Result<(), String> {
+    failover::settings::save(&settings).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub fn get_core_settings() -> CoreSettings {
+    xray::settings::load()
+}
+
+#[tauri::command]
+pub fn save_core_settings(settings: CoreSettings) -> Result<(), String> {
+    xray::settings::save(&settings).map_err(|e| e.to_string())
+}
+
+/// 用 xray -test 校验当前配置；不传 node_file 时使用正在运行或选中的节点
+#[tauri::command]
+pub async fn validate_config(node_file: Option<String>) -> Result<ValidationResult, String> {
+    let node_file = node_file
+        .or_else(xray::current_node)
+        .or_else(selection::get);
+    // xray -test 需要等待子进程结束
+    tauri::async_runtime::spawn_blocking(move || {
+        xray::validate::validate(node_file.as_deref()).map_err(|e| e.to_string())
+    })
+    .await
+    .map_err(|e| e.to_string())?
 }
 
 #[tauri::command]
@@ -53,7 +123,7 @@ pub fn get_xray_log(log_type: String) -> Result<String, String> {
         "error.log"
     };
 
-    let log_path = paths::get_data_dir().join("logs").join(filename);
+    let log_path = paths::get_logs_dir().join(filename);
     if !log_path.exists() {
         return Ok(String::new());
     }
@@ -75,3 +145,20 @@ pub fn get_xray_log(log_type: String) -> Result<String, String> {
 
     Ok(content)
 }
+
+/// 订阅 xray 实时输出：返回已缓冲的内容，之后的每一行通过 channel 推送
+#[tauri::command]
+pub fn subscribe_core_log(channel: Channel<CoreLogLine>) -> Vec<CoreLogLine> {
+    xray::output::subscribe(channel)
+}
+
+#[tauri::command]
+pub fn unsubscribe_core_log(channel_id: u32) {
+    xray::output::unsubscribe(channel_id)
+}
+
+/// 增量获取 xray 输出，since 为上次收到的最大序号加一
+#[tauri::command]
+pub fn get_core_log(since: Option<u64>, limit: Option<usize>) -> Vec<CoreLogLine> {
+    xray::output::lines_since(since.unwrap_or(0), limit.unwrap_or(500))
+}
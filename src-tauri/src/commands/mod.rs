@@ -2,3 +2,4 @@ pub mod config;
 pub mod monitor;
 pub mod nodes;
 pub mod proxy;
+pub mod subscription;
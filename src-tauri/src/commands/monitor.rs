@@ -1,51 +1,105 @@
-use crate::services::monitor::MONITOR;
+use crate::models::{
+    Connection, DailyUsage, InterfaceSettings, IpCheckSettings, IpInfo, NetworkInterface,
+    TrafficBucket, TrafficCapSettings, TrafficCapStatus, TrafficGranularity, TrafficSample,
+    TrafficStats, UsagePeriod, UsageReport,
+};
+use crate::services::{accounting, connections, ipinfo, monitor};
 
-#[derive(serde::Serialize)]
-pub struct TrafficStats {
-    pub upload_speed: u64,
-    pub download_speed: u64,
-    pub upload_total: u64,
-    pub download_total: u64,
+/// 最近一次采样，实时数据通过 traffic 事件推送
+#[tauri::command]
+pub fn get_traffic_stats() -> TrafficStats {
+    monitor::latest()
 }
 
-#[derive(serde::Serialize)]
-pub struct IpInfo {
-    pub external_ip: Option<String>,
-    pub internal_ip: Option<String>,
+/// 最近 10 分钟的流量时间序列，仪表盘打开时用于补全图表
+#[tauri::command]
+pub fn get_traffic_history() -> Vec<TrafficSample> {
+    monitor::history()
 }
 
+/// 系统网卡列表，包含类型与是否计入系统流量
 #[tauri::command]
-pub fn get_traffic_stats() -> TrafficStats {
-    let mut monitor = MONITOR.lock().unwrap();
-    let (up, down) = monitor.get_speed();
-    let (total_up, total_down) = monitor.get_totals();
-
-    TrafficStats {
-        upload_speed: up,
-        download_speed: down,
-        upload_total: total_up,
-        download_total: total_down,
-    }
-}
-
-#[tauri::command]
-pub async fn get_ip_info() -> IpInfo {
-    // 公网 IP
-    let client = reqwest::Client::builder()
-        .timeout(std::time::Duration::from_secs(3))
-        .build()
-        .unwrap_or_default();
-
-    let external_ip = match client.get("https://api.ipify.org").send().await {
-        Ok(resp) => resp.text().await.ok(),
-        Err(_) => None,
-    };
-
-    // 内网 IP
-    let internal_ip = local_ip_address::local_ip().ok().map(|ip| ip.to_string());
-
-    IpInfo {
-        external_ip,
-        internal_ip,
-    }
+pub fn list_network_interfaces() -> Vec<NetworkInterface> {
+    monitor::MONITOR.lock().unwrap().interfaces()
+}
+
+#[tauri::command]
+pub fn get_interface_settings() -> InterfaceSettings {
+    monitor::interfaces::load()
+}
+
+/// 保存网卡的包含/排除列表，立即用于之后的采样
+#[tauri::command]
+pub fn save_interface_settings(settings: InterfaceSettings) -> Result<(), String> {
+    monitor::interfaces::save(&settings).map_err(|e| e.to_string())?;
+    monitor::MONITOR.lock().unwrap().set_settings(settings);
+    Ok(())
+}
+
+/// 最近 count 个小时/天/月的流量，没有流量的时段补零
+#[tauri::command]
+pub fn get_traffic_rollups(granularity: TrafficGranularity, count: u32) -> Vec<TrafficBucket> {
+    accounting::store::query(granularity, count)
+}
+
+#[tauri::command]
+pub fn get_traffic_cap_settings() -> TrafficCapSettings {
+    monitor::settings::load()
+}
+
+#[tauri::command]
+pub fn save_traffic_cap_settings(settings: TrafficCapSettings) -> Result<(), String> {
+    monitor::settings::save(&settings).map_err(|e| e.to_string())
+}
+
+/// 当前计费周期的用量
+#[tauri::command]
+pub fn get_traffic_cap_status() -> TrafficCapStatus {
+    monitor::cap::status(&monitor::settings::load())
+}
+
+/// 按今天/最近 7 天/本月汇总的代理、直连与各节点、各订阅流量
+#[tauri::command]
+pub fn get_traffic_usage(period: UsagePeriod) -> UsageReport {
+    accounting::store::report(period)
+}
+
+/// 最近 days 天的每日流量记录
+#[tauri::command]
+pub fn get_daily_traffic_usage(days: u32) -> Vec<DailyUsage> {
+    accounting::store::daily(days)
+}
+
+#[tauri::command]
+pub fn clear_traffic_usage() -> Result<(), String> {
+    accounting::store::clear().map_err(|e| e.to_string())
+}
+
+/// 最近经过核心的连接，新连接另通过 connection 事件推送
+#[tauri::command]
+pub fn get_connections() -> Vec<Connection> {
+    connections::list()
+}
+
+#[tauri::command]
+pub fn clear_connections() {
+    connections::clear();
+}
+
+/// 直连与经代理的出口 IP 及地理位置，节点不变时返回缓存，refresh 为 true 时重新查询
+#[tauri::command]
+pub async fn get_ip_info(refresh: Option<bool>) -> IpInfo {
+    ipinfo::get(refresh.unwrap_or(false)).await
+}
+
+#[tauri::command]
+pub fn get_ip_check_settings() -> IpCheckSettings {
+    ipinfo::settings::load()
+}
+
+#[tauri::command]
+pub fn save_ip_check_settings(settings: IpCheckSettings) -> Result<(), String> {
+    ipinfo::settings::save(&settings).map_err(|e| e.to_string())?;
+    ipinfo::invalidate();
+    Ok(())
 }
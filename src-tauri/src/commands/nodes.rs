@@ -1,8 +1,11 @@
-use crate::models::NodeInfo;
-use crate::services::proxylink;
-use crate::utils::paths;
+use crate::models::{
+    LatencyBatchSummary, LatencyMethod, LatencyResult, LatencySample, LatencyStats, NodeInfo,
+};
+use crate::services::{groups, latency, proxylink, xray};
+use crate::utils::{paths, time};
 use std::fs;
 use std::path::Path;
+use tauri::AppHandle;
 
 #[tauri::command]
 pub async fn import_link(link: String) -> Result<NodeInfo, String> {
@@ -11,7 +14,9 @@ pub async fn import_link(link: String) -> Result<NodeInfo, String> {
 
 #[tauri::command]
 pub async fn import_subscription(url: String, sub_name: String) -> Result<Vec<NodeInfo>, String> {
-    proxylink::import_subscription(&url, &sub_name).map_err(|e| e.to_string())
+    proxylink::import_subscription(&url, &sub_name)
+        .await
+        .map_err(|e| e.to_string())
 }
 
 #[tauri::command]
@@ -25,6 +30,7 @@ pub fn list_nodes() -> Vec<NodeInfo> {
     nodes
 }
 
+#[allow(clippy::collapsible_if, clippy::unnecessary_map_or)]
 fn collect_nodes(base_dir: &Path, current_dir: &Path, nodes: &mut Vec<NodeInfo>) {
     if let Ok(entries) = fs::read_dir(current_dir) {
         for entry in entries.flatten() {
@@ -60,6 +66,7 @@ fn collect_nodes(base_dir: &Path, current_dir: &Path, nodes: &mut Vec<NodeInfo>)
     }
 }
 
+#[allow(clippy::collapsible_if)]
 fn parse_node_details(path: &Path) -> (String, String, u16) {
     if let Ok(content) = fs::read_to_string(path) {
         if let Ok(mut json) = serde_json::from_str::<serde_json::Value>(&content) {
@@ -113,6 +120,24 @@ fn parse_node_details(path: &Path) -> (String, String, u16) {
                         .to_string();
                     port = servers.get("port").and_then(|v| v.as_u64()).unwrap_or(0) as u16;
                 }
+                // peers (wireguard): endpoint 为 host:port
+                else if let Some(endpoint) = s
+                    .get("peers")
+                    .and_then(|v| v.as_array())
+                    .and_then(|a| a.first())
+                    .and_then(|p| p.get("endpoint"))
+                    .and_then(|v| v.as_str())
+                {
+                    if let Some((host, p)) = endpoint.rsplit_once(':') {
+                        address = host.trim_matches(|c| c == '[' || c == ']').to_string();
+                        port = p.parse().unwrap_or(0);
+                    }
+                }
+                // 扁平结构 (hysteria)
+                else if let Some(addr) = s.get("address").and_then(|v| v.as_str()) {
+                    address = addr.to_string();
+                    port = s.get("port").and_then(|v| v.as_u64()).unwrap_or(0) as u16;
+                }
             }
 
             return (protocol, address, port);
@@ -126,11 +151,37 @@ pub fn delete_node(file_path: String) -> Result<(), String> {
     let outbounds_dir = paths::get_outbounds_dir();
     let full_path = outbounds_dir.join(&file_path);
 
-    fs::remove_file(&full_path).map_err(|e| e.to_string())
+    fs::remove_file(&full_path).map_err(|e| e.to_string())?;
+    let removed = [file_path];
+    latency::history::forget(&removed).map_err(|e| e.to_string())?;
+    groups::remove_nodes(&removed).map_err(|e| e.to_string())
 }
 
+/// TCP 连接测试；传入 node_file 时记录到延迟历史
 #[tauri::command]
-pub async fn ping_node(address: String, port: u16) -> Result<i64, String> {
+pub async fn ping_node(
+    address: String,
+    port: u16,
+    node_file: Option<String>,
+) -> Result<i64, String> {
+    let result = tcp_ping(address, port).await;
+
+    if let Some(node_file) = node_file {
+        let sample = LatencySample {
+            timestamp: time::now_secs(),
+            method: LatencyMethod::Tcp,
+            delay_ms: result.as_ref().ok().map(|ms| *ms as u64),
+            error: result.as_ref().err().cloned(),
+        };
+        if let Err(e) = latency::history::record(vec![(node_file, sample)]) {
+            xray::output::log(format!("保存延迟记录失败: {}", e));
+        }
+    }
+
+    result
+}
+
+async fn tcp_ping(address: String, port: u16) -> Result<i64, String> {
     use std::net::TcpStream;
     use std::net::ToSocketAddrs;
     use std::time::{Duration, Instant};
@@ -162,3 +213,68 @@ pub async fn ping_node(address: String, port: u16) -> Result<i64, String> {
     .await
     .map_err(|e| e.to_string())?
 }
+
+/// 真实延迟测试：经临时 xray 实例请求测试地址
+#[tauri::command]
+pub async fn test_node_latency(node_file: String) -> LatencyResult {
+    let result = latency::test_node(&node_file, &latency::settings::load()).await;
+    if let Err(e) = latency::history::record(vec![latency::history::sample_of(&result)]) {
+        xray::output::log(format!("保存延迟记录失败: {}", e));
+    }
+    result
+}
+
+/// 批量真实延迟测试：指定节点文件列表或整个订阅，结果通过 latency-progress 事件逐个推送
+#[tauri::command]
+pub async fn test_nodes_latency(
+    app: AppHandle,
+    node_files: Option<Vec<String>>,
+    subscription: Option<String>,
+    concurrency: Option<usize>,
+    timeout_ms: Option<u64>,
+) -> Result<LatencyBatchSummary, String> {
+    let mut node_files = node_files.unwrap_or_default();
+    if let Some(sub_name) = subscription {
+        node_files.extend(latency::batch::subscription_node_files(&sub_name));
+    }
+    if node_files.is_empty() {
+        return Err("没有需要测试的节点".to_string());
+    }
+
+    let mut settings = latency::settings::load();
+    if let Some(concurrency) = concurrency {
+        settings.concurrency = concurrency;
+    }
+    if let Some(timeout_ms) = timeout_ms {
+        settings.timeout_ms = timeout_ms;
+    }
+
+    Ok(latency::batch::run(app, node_files, settings).await)
+}
+
+#[tauri::command]
+pub fn cancel_latency_test() -> bool {
+    latency::batch::cancel()
+}
+
+/// 节点延迟统计，不传 node_files 时返回全部有记录的节点；不传 method 时统计真实延迟
+#[tauri::command]
+pub fn get_latency_stats(
+    node_files: Option<Vec<String>>,
+    method: Option<LatencyMethod>,
+) -> Vec<LatencyStats> {
+    latency::history::stats(
+        &node_files.unwrap_or_default(),
+        method.unwrap_or(LatencyMethod::Real),
+    )
+}
+
+#[tauri::command]
+pub fn get_latency_history(node_file: String) -> Vec<LatencySample> {
+    latency::history::samples(&node_file)
+}
+
+#[tauri::command]
+pub fn clear_latency_history() -> Result<(), String> {
+    latency::history::clear().map_err(|e| e.to_string())
+}
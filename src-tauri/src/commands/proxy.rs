@@ -1,30 +1,27 @@
-use crate::models::ProxyStatus;
-use crate::services::{system_proxy, xray};
-use crate::utils::paths;
-use once_cell::sync::Lazy;
-use std::fs;
-use std::sync::Mutex;
-
-// 全局存储选中的节点
-static SELECTED_NODE: Lazy<Mutex<Option<String>>> = Lazy::new(|| Mutex::new(None));
+use crate::models::{CandidatePool, FailoverEvent, PortCheck, ProxyStatus, SwitchReason};
+use crate::services::{failover, ports, selection, system_proxy, xray};
+use tauri::AppHandle;
 
 #[tauri::command]
 pub async fn start_proxy(node_file: String) -> Result<(), String> {
-    xray::start(&node_file).map_err(|e| e.to_string())
+    // 启动与停止都会等待子进程，放到阻塞线程池中执行
+    tauri::async_runtime::spawn_blocking(move || xray::start(&node_file).map_err(|e| e.to_string()))
+        .await
+        .map_err(|e| e.to_string())?
 }
 
 #[tauri::command]
 pub async fn stop_proxy() -> Result<(), String> {
-    xray::stop().map_err(|e| e.to_string())
+    tauri::async_runtime::spawn_blocking(|| xray::stop().map_err(|e| e.to_string()))
+        .await
+        .map_err(|e| e.to_string())?
 }
 
 #[tauri::command]
 pub fn get_proxy_status() -> ProxyStatus {
     let mut status = xray::get_status();
     // 获取当前选中的节点
-    if let Ok(node) = SELECTED_NODE.lock() {
-        status.current_node = node.clone();
-    }
+    status.current_node = selection::get();
     status
 }
 
@@ -35,37 +32,26 @@ pub async fn set_system_proxy(enable: bool, port: u16) -> Result<(), String> {
 
 #[tauri::command]
 pub fn select_node(node_file: String) -> Result<(), String> {
-    let mut selected = SELECTED_NODE.lock().map_err(|e| e.to_string())?;
-    *selected = Some(node_file.clone());
-
-    // 保存到配置文件
-    let config_path = paths::get_data_dir().join("selected_node.txt");
-    fs::write(&config_path, &node_file).map_err(|e| e.to_string())?;
-
-    Ok(())
+    selection::set(&node_file).map_err(|e| e.to_string())
 }
 
 #[tauri::command]
 pub fn get_selected_node() -> Option<String> {
-    // 先尝试从内存读取
-    if let Ok(node) = SELECTED_NODE.lock() {
-        if node.is_some() {
-            return node.clone();
-        }
-    }
+    selection::get()
+}
 
-    // 从文件读取
-    let config_path = paths::get_data_dir().join("selected_node.txt");
-    if let Ok(content) = fs::read_to_string(&config_path) {
-        let node = content.trim().to_string();
-        if !node.is_empty() {
-            // 更新内存
-            if let Ok(mut selected) = SELECTED_NODE.lock() {
-                *selected = Some(node.clone());
-            }
-            return Some(node);
-        }
-    }
+/// 测试候选池并选中最快的节点，核心运行中时立即切换；不传 pool 时使用故障切换设置中的候选池
+#[tauri::command]
+pub async fn select_fastest_node(
+    app: AppHandle,
+    pool: Option<CandidatePool>,
+) -> Result<FailoverEvent, String> {
+    let pool = pool.unwrap_or_else(|| failover::settings::load().pool);
+    failover::switch_to_best(&app, &pool, SwitchReason::Fastest).await
+}
 
-    None
+/// 检查入站与 API 端口是否被其他程序占用
+#[tauri::command]
+pub fn check_ports() -> Vec<PortCheck> {
+    ports::check(xray::current_pid())
 }
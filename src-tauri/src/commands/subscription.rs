@@ -0,0 +1,43 @@
+use crate::models::{Subscription, SubscriptionDiff, SubscriptionUsage};
+use crate::services::subscription;
+use tauri::AppHandle;
+
+#[tauri::command]
+pub fn list_subscriptions() -> Vec<Subscription> {
+    subscription::store::list()
+}
+
+#[tauri::command]
+pub fn update_subscription(subscription: Subscription) -> Result<Subscription, String> {
+    // 只允许修改用户设置，更新时间等运行信息保持不变
+    subscription::store::update(&subscription.name, |s| {
+        s.url = subscription.url.clone();
+        s.user_agent = subscription.user_agent.clone().filter(|ua| !ua.is_empty());
+        s.update_interval = subscription.update_interval;
+        s.low_quota_percent = subscription.low_quota_percent.min(100);
+        s.expire_warn_days = subscription.expire_warn_days;
+    })
+    .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn remove_subscription(name: String) -> Result<(), String> {
+    subscription::remove(&name).await.map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn refresh_subscription(
+    app: AppHandle,
+    name: String,
+) -> Result<SubscriptionDiff, String> {
+    let result = subscription::refresh(&name)
+        .await
+        .map_err(|e| e.to_string());
+    subscription::scheduler::emit_usage_warning(&app, &name);
+    result
+}
+
+#[tauri::command]
+pub fn get_subscription_usage() -> Vec<SubscriptionUsage> {
+    subscription::usage::list()
+}
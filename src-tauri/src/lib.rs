@@ -3,12 +3,35 @@ mod models;
 mod services;
 mod utils;
 
-use commands::{config, monitor, nodes, proxy};
+use commands::{config, monitor, nodes, proxy, subscription};
+use services::xray;
+use tauri::Manager;
+use utils::paths;
 
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
     tauri::Builder::default()
         .plugin(tauri_plugin_opener::init())
+        .setup(|app| {
+            // 初始化数据目录，首次运行时从打包资源复制默认配置
+            paths::init(app.path().resource_dir().ok());
+            if let Err(e) = paths::seed_from_resources() {
+                xray::output::log(format!("初始化数据目录失败: {}", e));
+            }
+
+            // 核心进程监控需要通过 AppHandle 发送事件
+            xray::init(app.handle().clone());
+
+            // 订阅自动更新
+            services::subscription::scheduler::start(app.handle().clone());
+            // 节点故障自动切换
+            services::failover::start(app.handle().clone());
+            // 流量采样、traffic 事件，以及按节点与订阅记录每日流量
+            services::monitor::start(app.handle().clone());
+            // 活动连接（路由统计与访问日志）
+            services::connections::start(app.handle().clone());
+            Ok(())
+        })
         .invoke_handler(tauri::generate_handler![
             // 代理控制
             proxy::start_proxy,
@@ -17,22 +40,73 @@ pub fn run() {
             proxy::set_system_proxy,
             proxy::select_node,
             proxy::get_selected_node,
+            proxy::select_fastest_node,
+            proxy::check_ports,
             // 节点管理
             nodes::import_link,
             nodes::import_subscription,
             nodes::list_nodes,
             nodes::delete_node,
             nodes::ping_node,
+            nodes::test_node_latency,
+            nodes::test_nodes_latency,
+            nodes::cancel_latency_test,
+            nodes::get_latency_stats,
+            nodes::get_latency_history,
+            nodes::clear_latency_history,
+            // 订阅管理
+            subscription::list_subscriptions,
+            subscription::update_subscription,
+            subscription::remove_subscription,
+            subscription::refresh_subscription,
+            subscription::get_subscription_usage,
             // 配置管理
             config::get_dns_config,
             config::save_dns_config,
             config::get_routing_config,
             config::save_routing_config,
+            config::get_inbound_settings,
+            config::save_inbound_settings,
+            config::list_node_groups,
+            config::save_node_group,
+            config::remove_node_group,
+            config::get_latency_settings,
+            config::save_latency_settings,
+            config::get_failover_settings,
+            config::save_failover_settings,
+            config::get_core_settings,
+            config::save_core_settings,
+            config::validate_config,
             config::get_xray_log,
+            config::get_core_log,
+            config::subscribe_core_log,
+            config::unsubscribe_core_log,
             // 监控
             monitor::get_traffic_stats,
+            monitor::get_traffic_history,
+            monitor::get_traffic_rollups,
+            monitor::list_network_interfaces,
+            monitor::get_interface_settings,
+            monitor::save_interface_settings,
+            monitor::get_traffic_cap_settings,
+            monitor::save_traffic_cap_settings,
+            monitor::get_traffic_cap_status,
+            monitor::get_traffic_usage,
+            monitor::get_daily_traffic_usage,
+            monitor::clear_traffic_usage,
+            monitor::get_connections,
+            monitor::clear_connections,
             monitor::get_ip_info,
+            monitor::get_ip_check_settings,
+            monitor::save_ip_check_settings,
         ])
-        .run(tauri::generate_context!())
-        .expect("error while running tauri application");
+        .build(tauri::generate_context!())
+        .expect("error while building tauri application")
+        .run(|_app, event| {
+            // 退出时回收 xray 进程并还原系统代理，保存流量记账
+            if let tauri::RunEvent::Exit = event {
+                let _ = xray::stop();
+                let _ = services::accounting::store::flush();
+            }
+        });
 }
@@ -43,7 +43,11 @@ pub struct RoutingRule {
     pub rule_type: String,
     #[serde(default)]
     pub inbound_tag: Vec<String>,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
     pub outbound_tag: String,
+    /// 指向节点组的负载均衡器，与 outbound_tag 二选一
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub balancer_tag: Option<String>,
     #[serde(default)]
     pub domain: Vec<String>,
     #[serde(default)]
@@ -0,0 +1,48 @@
+use serde::Serialize;
+
+/// 连接信息的来源
+#[derive(Debug, Clone, Copy, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum ConnectionOrigin {
+    /// RoutingService 路由统计订阅
+    Routing,
+    /// 访问日志 logs/access.log
+    AccessLog,
+}
+
+/// 推断命中的路由规则
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MatchedRule {
+    /// 规则序号与首个条件，如 "#3 geosite:google"
+    pub label: String,
+    /// 条件均已在本地核对；含 geosite/geoip 等无法判断的条件时为 false
+    pub certain: bool,
+}
+
+/// 经过核心的一条连接
+///
+/// xray 不报告单条连接的关闭与字节数，条目在空闲超时后移出列表。
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Connection {
+    pub id: u64,
+    /// tcp / udp
+    pub network: String,
+    /// 来源地址，如 "127.0.0.1:52044"
+    pub source: String,
+    pub domain: Option<String>,
+    pub destination_ip: Option<String>,
+    pub destination_port: Option<u16>,
+    pub inbound_tag: String,
+    pub outbound_tag: String,
+    /// 嗅探到的协议，如 http / tls
+    pub protocol: Option<String>,
+    /// 推断命中的路由规则
+    pub rule: Option<MatchedRule>,
+    /// 访问日志中被拒绝的连接
+    pub rejected: bool,
+    /// 开始时间（毫秒时间戳）
+    pub start: u64,
+    pub origin: ConnectionOrigin,
+}
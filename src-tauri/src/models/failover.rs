@@ -0,0 +1,71 @@
+use serde::{Deserialize, Serialize};
+
+/// 自动故障切换设置
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FailoverSettings {
+    #[serde(default)]
+    pub enabled: bool,
+    /// 健康检查间隔（秒）
+    #[serde(default = "default_interval_secs")]
+    pub interval_secs: u64,
+    /// 连续失败多少次后切换
+    #[serde(default = "default_failure_threshold")]
+    pub failure_threshold: u32,
+    /// 候选节点池
+    #[serde(default)]
+    pub pool: CandidatePool,
+}
+
+impl Default for FailoverSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            interval_secs: default_interval_secs(),
+            failure_threshold: default_failure_threshold(),
+            pool: CandidatePool::default(),
+        }
+    }
+}
+
+fn default_interval_secs() -> u64 {
+    60
+}
+
+fn default_failure_threshold() -> u32 {
+    3
+}
+
+/// 候选节点池
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "camelCase")]
+pub enum CandidatePool {
+    /// 与当前节点位于同一目录（同一订阅或手动导入的节点）
+    #[default]
+    SameGroup,
+    /// 指定订阅的全部节点
+    Subscription { name: String },
+    /// 名称中包含该标签的节点（不区分大小写），如 "HK"
+    Tag { tag: String },
+}
+
+/// 切换原因
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "kind", rename_all = "camelCase")]
+pub enum SwitchReason {
+    /// 当前节点连续健康检查失败
+    #[serde(rename_all = "camelCase")]
+    ActiveNodeFailed { failures: u32, last_error: String },
+    /// 手动触发的最快节点选择
+    Fastest,
+}
+
+/// 节点切换事件；to 为 None 表示没有可用的候选节点
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FailoverEvent {
+    pub from: Option<String>,
+    pub to: Option<String>,
+    pub delay_ms: Option<u64>,
+    pub reason: SwitchReason,
+}
@@ -0,0 +1,40 @@
+use serde::{Deserialize, Serialize};
+
+/// 节点组：多个节点由 Xray 负载均衡器统一调度，路由规则通过 balancerTag 指向 tag
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NodeGroup {
+    /// 负载均衡器标签
+    pub tag: String,
+    /// 成员节点文件，相对于 outbounds 目录
+    pub node_files: Vec<String>,
+    #[serde(default)]
+    pub strategy: BalancerStrategy,
+    /// 所有成员都不可用时使用的出站
+    #[serde(default)]
+    pub fallback_tag: Option<String>,
+}
+
+/// 负载均衡策略
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum BalancerStrategy {
+    #[default]
+    Random,
+    RoundRobin,
+    /// 依赖 observatory 的探测结果
+    LeastPing,
+    /// 依赖 burstObservatory 的探测结果
+    LeastLoad,
+}
+
+impl BalancerStrategy {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            BalancerStrategy::Random => "random",
+            BalancerStrategy::RoundRobin => "roundRobin",
+            BalancerStrategy::LeastPing => "leastPing",
+            BalancerStrategy::LeastLoad => "leastLoad",
+        }
+    }
+}
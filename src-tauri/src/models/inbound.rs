@@ -0,0 +1,65 @@
+use serde::{Deserialize, Serialize};
+
+/// 本地入站设置，对应 confdir/01_inbounds.json
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InboundSettings {
+    /// 监听地址，局域网共享时设为 0.0.0.0
+    #[serde(default = "default_listen")]
+    pub listen: String,
+    /// SOCKS 端口，None 表示不启用
+    #[serde(default)]
+    pub socks_port: Option<u16>,
+    /// HTTP 端口，None 表示不启用
+    #[serde(default)]
+    pub http_port: Option<u16>,
+    /// 混合端口（同一端口接受 SOCKS 与 HTTP），None 表示不启用
+    #[serde(default)]
+    pub mixed_port: Option<u16>,
+    /// SOCKS 入站是否转发 UDP
+    #[serde(default = "default_true")]
+    pub udp: bool,
+    /// 流量探测（http/tls），用于按域名分流
+    #[serde(default = "default_true")]
+    pub sniffing: bool,
+    /// 入站认证，None 表示无需认证
+    #[serde(default)]
+    pub auth: Option<InboundAuth>,
+}
+
+impl Default for InboundSettings {
+    fn default() -> Self {
+        Self {
+            listen: default_listen(),
+            socks_port: Some(10808),
+            http_port: Some(10809),
+            mixed_port: None,
+            udp: true,
+            sniffing: true,
+            auth: None,
+        }
+    }
+}
+
+impl InboundSettings {
+    /// 系统代理使用的端口：系统代理的 http 与 socks 共用同一端口，
+    /// 因此优先混合端口，其次同样接受 HTTP 请求的 SOCKS 端口，最后 HTTP
+    pub fn system_proxy_port(&self) -> Option<u16> {
+        self.mixed_port.or(self.socks_port).or(self.http_port)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InboundAuth {
+    pub user: String,
+    pub pass: String,
+}
+
+fn default_listen() -> String {
+    "127.0.0.1".to_string()
+}
+
+fn default_true() -> bool {
+    true
+}
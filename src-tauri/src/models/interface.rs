@@ -0,0 +1,41 @@
+use serde::{Deserialize, Serialize};
+
+/// 网卡类型
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum InterfaceKind {
+    Loopback,
+    Ethernet,
+    Wireless,
+    /// TUN/TAP 及 WireGuard 等隧道网卡
+    Tun,
+    /// 网桥、veth、虚拟机与容器网卡
+    Virtual,
+    Other,
+}
+
+/// 系统网卡及其累计计数
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NetworkInterface {
+    pub name: String,
+    pub kind: InterfaceKind,
+    pub mac_address: String,
+    /// 是否计入系统流量
+    pub included: bool,
+    /// 开机以来的发送/接收字节数
+    pub transmitted: u64,
+    pub received: u64,
+}
+
+/// 系统流量统计的网卡选择
+///
+/// 包含列表非空时只统计其中的网卡；否则统计除排除列表、回环与 TUN 网卡外的全部网卡。
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InterfaceSettings {
+    #[serde(default)]
+    pub included: Vec<String>,
+    #[serde(default)]
+    pub excluded: Vec<String>,
+}
@@ -0,0 +1,95 @@
+use serde::{Deserialize, Serialize};
+
+/// IP 查询服务，各自有不同的响应格式
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum IpProvider {
+    /// http://ip-api.com/json
+    IpApi,
+    /// https://ipinfo.io/json
+    IpInfo,
+    /// https://ipapi.co/json
+    IpApiCo,
+    /// https://api.ip.sb/geoip
+    IpSb,
+    /// https://api.ipify.org，仅返回地址
+    Ipify,
+}
+
+impl IpProvider {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            IpProvider::IpApi => "ip-api",
+            IpProvider::IpInfo => "ip-info",
+            IpProvider::IpApiCo => "ip-api-co",
+            IpProvider::IpSb => "ip-sb",
+            IpProvider::Ipify => "ipify",
+        }
+    }
+}
+
+/// IP 查询设置
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct IpCheckSettings {
+    /// 按顺序尝试的查询服务，失败时使用下一个
+    #[serde(default = "default_providers")]
+    pub providers: Vec<IpProvider>,
+    /// 单个服务的超时时间（毫秒）
+    #[serde(default = "default_timeout_ms")]
+    pub timeout_ms: u64,
+}
+
+impl Default for IpCheckSettings {
+    fn default() -> Self {
+        Self {
+            providers: default_providers(),
+            timeout_ms: default_timeout_ms(),
+        }
+    }
+}
+
+fn default_providers() -> Vec<IpProvider> {
+    vec![
+        IpProvider::IpApi,
+        IpProvider::IpSb,
+        IpProvider::IpInfo,
+        IpProvider::IpApiCo,
+        IpProvider::Ipify,
+    ]
+}
+
+fn default_timeout_ms() -> u64 {
+    5000
+}
+
+/// 出口 IP 及其地理位置
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct IpLookup {
+    pub ip: String,
+    pub country: Option<String>,
+    /// ISO 3166 两位国家代码
+    pub country_code: Option<String>,
+    pub region: Option<String>,
+    pub city: Option<String>,
+    /// 如 "AS15169"
+    pub asn: Option<String>,
+    pub isp: Option<String>,
+    /// 实际给出结果的查询服务
+    pub provider: Option<IpProvider>,
+}
+
+/// 仪表盘 IP 信息：直连与经本地入站代理的出口
+#[derive(Debug, Clone, Serialize)]
+pub struct IpInfo {
+    /// 代理可用时为代理出口，否则为直连出口
+    pub external_ip: Option<String>,
+    pub internal_ip: Option<String>,
+    pub direct: Option<IpLookup>,
+    pub proxy: Option<IpLookup>,
+    pub direct_error: Option<String>,
+    pub proxy_error: Option<String>,
+    /// 查询时正在使用的节点文件，节点变化后缓存失效
+    pub node: Option<String>,
+}
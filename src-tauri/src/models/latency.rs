@@ -0,0 +1,152 @@
+use serde::{Deserialize, Serialize};
+
+/// 真实延迟测试设置
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LatencySettings {
+    /// 测试地址，应返回 204 或 200
+    #[serde(default = "default_test_url")]
+    pub test_url: String,
+    /// 单个节点的超时时间（毫秒）
+    #[serde(default = "default_timeout_ms")]
+    pub timeout_ms: u64,
+    /// 批量测试时同时测试的节点数
+    #[serde(default = "default_concurrency")]
+    pub concurrency: usize,
+}
+
+impl Default for LatencySettings {
+    fn default() -> Self {
+        Self {
+            test_url: default_test_url(),
+            timeout_ms: default_timeout_ms(),
+            concurrency: default_concurrency(),
+        }
+    }
+}
+
+fn default_test_url() -> String {
+    "https://www.gstatic.com/generate_204".to_string()
+}
+
+fn default_timeout_ms() -> u64 {
+    5000
+}
+
+fn default_concurrency() -> usize {
+    16
+}
+
+/// 单个节点的延迟测试结果
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LatencyResult {
+    pub node_file: String,
+    pub success: bool,
+    /// 首次请求到收到响应头的总耗时，包含建立代理连接
+    pub delay_ms: Option<u64>,
+    /// 估算的建立代理连接耗时：首次请求减去复用连接的请求，
+    /// 两次请求的服务端处理时间不同，只能作为参考
+    pub estimated_handshake_ms: Option<u64>,
+    /// 复用已建立的连接再次请求到收到响应头的耗时
+    pub warm_ms: Option<u64>,
+    pub error: Option<LatencyError>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LatencyError {
+    pub kind: LatencyErrorKind,
+    pub message: String,
+}
+
+/// 失败原因分类
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum LatencyErrorKind {
+    /// 节点文件缺失或格式错误
+    InvalidNode,
+    /// 临时 xray 实例未能启动
+    CoreFailed,
+    /// 超时
+    Timeout,
+    /// 代理握手失败（协议、TLS 或凭据错误，连接被服务端关闭）
+    ProxyFailed,
+    /// 测试地址返回了非预期的状态码
+    BadStatus,
+    /// 其他网络错误
+    Network,
+}
+
+/// 批量测试中单个节点完成时的事件
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LatencyProgress {
+    pub batch_id: u64,
+    pub completed: usize,
+    pub total: usize,
+    pub result: LatencyResult,
+}
+
+/// 批量测试结束时的汇总
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LatencyBatchSummary {
+    pub batch_id: u64,
+    pub total: usize,
+    pub completed: usize,
+    pub cancelled: bool,
+    pub results: Vec<LatencyResult>,
+}
+
+/// 测试方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum LatencyMethod {
+    /// TCP 连接服务器端口
+    Tcp,
+    /// 经 xray 请求测试地址
+    Real,
+}
+
+/// 单次测试记录
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LatencySample {
+    /// 测试时间（Unix 秒）
+    pub timestamp: u64,
+    pub method: LatencyMethod,
+    pub delay_ms: Option<u64>,
+    #[serde(default)]
+    pub error: Option<String>,
+}
+
+/// 延迟变化趋势
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum LatencyTrend {
+    Improving,
+    Stable,
+    Degrading,
+    /// 样本不足
+    Unknown,
+}
+
+/// 节点在滑动窗口内的延迟统计
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LatencyStats {
+    pub node_file: String,
+    /// 参与统计的测试方式
+    pub method: LatencyMethod,
+    /// 窗口内的样本数
+    pub samples: usize,
+    pub last_delay_ms: Option<u64>,
+    pub last_tested: Option<u64>,
+    pub median_ms: Option<u64>,
+    /// 相邻成功样本差值的平均值
+    pub jitter_ms: Option<u64>,
+    /// 失败样本占比（0-100）
+    pub loss_percent: f64,
+    pub trend: LatencyTrend,
+}
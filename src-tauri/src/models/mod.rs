@@ -1,7 +1,39 @@
 pub mod config;
+pub mod connection;
+pub mod failover;
+pub mod group;
+pub mod inbound;
+pub mod interface;
+pub mod ip;
+pub mod latency;
 pub mod node;
+pub mod port;
 pub mod proxy;
+pub mod subscription;
+pub mod traffic;
 
-pub use config::{DnsConfig, RoutingConfig};
+pub use config::{DnsConfig, RoutingConfig, RoutingRule};
+pub use connection::{Connection, ConnectionOrigin, MatchedRule};
+pub use failover::{CandidatePool, FailoverEvent, FailoverSettings, SwitchReason};
+pub use group::{BalancerStrategy, NodeGroup};
+pub use inbound::{InboundAuth, InboundSettings};
+pub use interface::{InterfaceKind, InterfaceSettings, NetworkInterface};
+pub use ip::{IpCheckSettings, IpInfo, IpLookup, IpProvider};
+pub use latency::{
+    LatencyBatchSummary, LatencyError, LatencyErrorKind, LatencyMethod, LatencyProgress,
+    LatencyResult, LatencySample, LatencySettings, LatencyStats, LatencyTrend,
+};
 pub use node::NodeInfo;
-pub use proxy::ProxyStatus;
+pub use port::{PortCheck, PortOwner, PortReassignment};
+pub use proxy::{
+    CoreExit, CoreLogLine, CoreSettings, ProxyStatus, ValidationIssue, ValidationResult,
+};
+pub use subscription::{
+    NodeRename, Subscription, SubscriptionDiff, SubscriptionUpdateEvent, SubscriptionUsage,
+    SubscriptionUserinfo, UsageWarning,
+};
+pub use traffic::{
+    ByteCount, CapSource, DailyUsage, TrafficBucket, TrafficCapSettings, TrafficCapStatus,
+    TrafficCounter, TrafficGranularity, TrafficSample, TrafficSource, TrafficStats, UsageEntry,
+    UsagePeriod, UsageReport,
+};
@@ -0,0 +1,31 @@
+use serde::{Deserialize, Serialize};
+
+/// 单个端口的占用检查结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PortCheck {
+    /// 入站标签，API 端口为 "api"
+    pub tag: String,
+    pub listen: String,
+    pub port: u16,
+    pub available: bool,
+    /// 占用端口的进程，无法识别时为 None
+    pub owner: Option<PortOwner>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PortOwner {
+    pub pid: u32,
+    pub name: String,
+    pub exe: Option<String>,
+}
+
+/// 自动改用的端口
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PortReassignment {
+    pub tag: String,
+    pub from: u16,
+    pub to: u16,
+}
@@ -1,3 +1,4 @@
+use super::InboundSettings;
 use serde::{Deserialize, Serialize};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -7,6 +8,12 @@ pub struct ProxyStatus {
     pub system_proxy: bool,
     pub current_node: Option<String>,
     pub port: u16,
+    /// 核心最近一次异常退出的信息
+    #[serde(default)]
+    pub last_exit: Option<CoreExit>,
+    /// 核心未运行时的错误说明
+    #[serde(default)]
+    pub error: Option<String>,
 }
 
 impl Default for ProxyStatus {
@@ -15,7 +22,94 @@ impl Default for ProxyStatus {
             running: false,
             system_proxy: false,
             current_node: None,
-            port: 10808,
+            port: InboundSettings::default()
+                .system_proxy_port()
+                .unwrap_or_default(),
+            last_exit: None,
+            error: None,
         }
     }
 }
+
+/// xray 进程异常退出记录
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CoreExit {
+    pub node_file: String,
+    /// 退出码，被信号结束时为 None
+    pub code: Option<i32>,
+    pub message: String,
+    /// 退出时间（Unix 秒）
+    pub at: u64,
+    /// 退出前的最后几行输出
+    pub output_tail: Vec<String>,
+    /// 将在多少毫秒后自动重启，不重启时为 None
+    pub restart_in_ms: Option<u64>,
+}
+
+/// 核心进程设置
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CoreSettings {
+    /// 异常退出后自动重启
+    #[serde(default = "default_auto_restart")]
+    pub auto_restart: bool,
+    /// 连续自动重启的最大次数
+    #[serde(default = "default_max_restarts")]
+    pub max_restarts: u32,
+    /// 启动前发现端口被占用时自动改用空闲端口，改用的端口写入生成的 08_ports.json
+    #[serde(default)]
+    pub auto_select_ports: bool,
+}
+
+impl Default for CoreSettings {
+    fn default() -> Self {
+        Self {
+            auto_restart: default_auto_restart(),
+            max_restarts: default_max_restarts(),
+            auto_select_ports: false,
+        }
+    }
+}
+
+fn default_auto_restart() -> bool {
+    true
+}
+
+fn default_max_restarts() -> u32 {
+    5
+}
+
+/// xray -test 配置校验结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ValidationResult {
+    pub valid: bool,
+    pub errors: Vec<ValidationIssue>,
+    /// xray 的原始输出
+    pub output: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ValidationIssue {
+    /// 最内层的错误原因
+    pub message: String,
+    /// 由外到内的错误链
+    pub chain: Vec<String>,
+    /// 出错的配置文件（能从输出中识别时）
+    pub file: Option<String>,
+}
+
+/// xray 输出或应用消息的一行
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CoreLogLine {
+    /// 递增序号，用于增量获取
+    pub seq: u64,
+    /// 时间（Unix 秒）
+    pub timestamp: u64,
+    /// stdout、stderr，或 app（应用自身的消息）
+    pub stream: String,
+    pub line: String,
+}
@@ -0,0 +1,239 @@
+use super::NodeInfo;
+use serde::{Deserialize, Serialize};
+
+/// 默认自动更新间隔（分钟）
+pub const DEFAULT_UPDATE_INTERVAL: u64 = 24 * 60;
+/// 默认剩余流量告警阈值（占总量的百分比）
+pub const DEFAULT_LOW_QUOTA_PERCENT: u8 = 10;
+/// 默认到期告警提前天数
+pub const DEFAULT_EXPIRE_WARN_DAYS: u64 = 3;
+/// 更新失败后的首次重试间隔（分钟），之后每次失败翻倍，不超过更新间隔
+pub const RETRY_BASE_INTERVAL: u64 = 5;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Subscription {
+    pub name: String,
+    pub url: String,
+    #[serde(default)]
+    pub user_agent: Option<String>,
+    /// 自动更新间隔（分钟），0 表示不自动更新
+    #[serde(default = "default_update_interval")]
+    pub update_interval: u64,
+    /// 上次成功更新时间（Unix 秒）
+    #[serde(default)]
+    pub last_update: Option<u64>,
+    /// 上次尝试更新时间（Unix 秒），无论成功与否
+    #[serde(default)]
+    pub last_attempt: Option<u64>,
+    #[serde(default)]
+    pub last_error: Option<String>,
+    /// 连续失败次数，成功后清零
+    #[serde(default)]
+    pub failures: u32,
+    #[serde(default)]
+    pub node_count: usize,
+    /// 最近一次拉取时服务端返回的流量与到期信息
+    #[serde(default)]
+    pub userinfo: Option<SubscriptionUserinfo>,
+    /// 剩余流量低于总量的该百分比时告警，0 表示不告警
+    #[serde(default = "default_low_quota_percent")]
+    pub low_quota_percent: u8,
+    /// 距离到期不足该天数时告警，0 表示不告警
+    #[serde(default = "default_expire_warn_days")]
+    pub expire_warn_days: u64,
+}
+
+impl Subscription {
+    pub fn new(name: &str, url: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            url: url.to_string(),
+            user_agent: None,
+            update_interval: DEFAULT_UPDATE_INTERVAL,
+            last_update: None,
+            last_attempt: None,
+            last_error: None,
+            failures: 0,
+            node_count: 0,
+            userinfo: None,
+            low_quota_percent: DEFAULT_LOW_QUOTA_PERCENT,
+            expire_warn_days: DEFAULT_EXPIRE_WARN_DAYS,
+        }
+    }
+
+    /// 是否到了自动更新时间；上次更新失败时按较短的退避间隔重试
+    pub fn is_due(&self, now: u64) -> bool {
+        if self.update_interval == 0 {
+            return false;
+        }
+        if self.failures > 0
+            && let Some(attempt) = self.last_attempt
+        {
+            return now.saturating_sub(attempt) >= self.retry_delay() * 60;
+        }
+        match self.last_update {
+            Some(last) => now.saturating_sub(last) >= self.update_interval * 60,
+            None => true,
+        }
+    }
+
+    /// 失败重试间隔（分钟）：5、10、20……，不超过更新间隔
+    pub fn retry_delay(&self) -> u64 {
+        let exponent = self.failures.saturating_sub(1).min(16);
+        (RETRY_BASE_INTERVAL << exponent).min(self.update_interval)
+    }
+}
+
+fn default_update_interval() -> u64 {
+    DEFAULT_UPDATE_INTERVAL
+}
+
+fn default_low_quota_percent() -> u8 {
+    DEFAULT_LOW_QUOTA_PERCENT
+}
+
+fn default_expire_warn_days() -> u64 {
+    DEFAULT_EXPIRE_WARN_DAYS
+}
+
+/// subscription-userinfo 响应头：upload=..; download=..; total=..; expire=..
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SubscriptionUserinfo {
+    pub upload: u64,
+    pub download: u64,
+    /// 总流量（字节），0 表示不限
+    pub total: u64,
+    /// 到期时间（Unix 秒）
+    pub expire: Option<u64>,
+}
+
+impl SubscriptionUserinfo {
+    pub fn used(&self) -> u64 {
+        self.upload.saturating_add(self.download)
+    }
+
+    /// 剩余流量，不限流量时为 None
+    pub fn remaining(&self) -> Option<u64> {
+        (self.total > 0).then(|| self.total.saturating_sub(self.used()))
+    }
+}
+
+/// 订阅用量概况
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SubscriptionUsage {
+    pub name: String,
+    pub upload: u64,
+    pub download: u64,
+    pub total: u64,
+    pub used: u64,
+    pub remaining: Option<u64>,
+    pub expire: Option<u64>,
+    pub warnings: Vec<UsageWarning>,
+}
+
+/// 用量告警
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "kind", rename_all = "camelCase")]
+pub enum UsageWarning {
+    /// 剩余流量低于阈值
+    #[serde(rename_all = "camelCase")]
+    LowQuota { remaining: u64, total: u64 },
+    /// 流量已用完
+    Exhausted,
+    /// 即将到期
+    #[serde(rename_all = "camelCase")]
+    ExpiringSoon { expire: u64, days_left: u64 },
+    /// 已过期
+    #[serde(rename_all = "camelCase")]
+    Expired { expire: u64 },
+}
+
+/// 订阅更新结果事件
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SubscriptionUpdateEvent {
+    pub name: String,
+    pub success: bool,
+    pub node_count: usize,
+    pub added: usize,
+    pub removed: usize,
+    pub changed: usize,
+    pub error: Option<String>,
+}
+
+/// 订阅刷新前后的节点差异，节点以相对 outbounds 的文件名标识
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SubscriptionDiff {
+    /// 刷新后的全部节点
+    pub nodes: Vec<NodeInfo>,
+    pub added: Vec<String>,
+    pub removed: Vec<String>,
+    /// 配置发生变化的节点（文件名为刷新后的名称）
+    pub changed: Vec<String>,
+    /// 服务端改名导致的文件重命名
+    pub renamed: Vec<NodeRename>,
+    /// 刷新后选中的节点，原节点被删除时为 None
+    pub selected_node: Option<String>,
+    /// 正在使用的节点配置变化，已重启核心
+    pub restarted: bool,
+    /// 成员全部被删除而移除的节点组
+    pub removed_groups: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NodeRename {
+    pub from: String,
+    pub to: String,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const HOUR: u64 = 3600;
+
+    #[test]
+    fn schedules_updates_and_retries() {
+        let now = 100 * HOUR;
+        // (last_update, last_attempt, failures, update_interval, 是否到期)
+        let cases = [
+            (None, None, 0, 60, true),
+            (Some(now - HOUR), Some(now - HOUR), 0, 60, true),
+            (Some(now - HOUR + 1), Some(now - HOUR + 1), 0, 60, false),
+            (Some(now - 10 * HOUR), Some(now), 0, 0, false),
+            // 失败后不因 last_update 过期而每分钟重试
+            (Some(now - 10 * HOUR), Some(now - 4 * 60), 1, 60, false),
+            (Some(now - 10 * HOUR), Some(now - 5 * 60), 1, 60, true),
+            (None, Some(now - 5 * 60), 1, 60, true),
+            (None, Some(now - 15 * 60), 3, 60, false),
+            (None, Some(now - 20 * 60), 3, 60, true),
+            // 退避不超过更新间隔
+            (None, Some(now - 30 * 60), 10, 30, true),
+            (None, Some(now - 29 * 60), 10, 30, false),
+        ];
+
+        for (last_update, last_attempt, failures, update_interval, due) in cases {
+            let subscription = Subscription {
+                last_update,
+                last_attempt,
+                failures,
+                update_interval,
+                ..Subscription::new("sub", "http://example.com")
+            };
+            assert_eq!(
+                subscription.is_due(now),
+                due,
+                "{:?} {:?} {} {}",
+                last_update,
+                last_attempt,
+                failures,
+                update_interval
+            );
+        }
+    }
+}
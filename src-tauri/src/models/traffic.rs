@@ -0,0 +1,213 @@
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// 流量数据来源
+#[derive(Debug, Clone, Copy, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum TrafficSource {
+    /// xray StatsService，仅统计经过代理核心的流量
+    Xray,
+    /// 系统网卡计数，核心未运行或 API 不可用时使用
+    System,
+}
+
+/// 单个入站或出站的流量计数（字节）与速率（字节/秒）
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TrafficCounter {
+    pub tag: String,
+    pub uplink: u64,
+    pub downlink: u64,
+    pub upload_speed: u64,
+    pub download_speed: u64,
+}
+
+/// 仪表盘流量统计
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TrafficStats {
+    pub upload_speed: u64,
+    pub download_speed: u64,
+    pub upload_total: u64,
+    pub download_total: u64,
+    pub source: TrafficSource,
+    pub inbounds: Vec<TrafficCounter>,
+    pub outbounds: Vec<TrafficCounter>,
+    /// 计入系统流量的各网卡读数，tag 为网卡名称
+    pub interfaces: Vec<TrafficCounter>,
+}
+
+/// 流量时间序列中的一个采样点
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TrafficSample {
+    /// 毫秒时间戳
+    pub timestamp: u64,
+    pub upload_speed: u64,
+    pub download_speed: u64,
+    pub upload_total: u64,
+    pub download_total: u64,
+    pub source: TrafficSource,
+}
+
+/// 上行/下行字节数
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize)]
+pub struct ByteCount {
+    pub uplink: u64,
+    pub downlink: u64,
+}
+
+impl ByteCount {
+    pub fn add(&mut self, other: ByteCount) {
+        self.uplink += other.uplink;
+        self.downlink += other.downlink;
+    }
+
+    pub fn total(&self) -> u64 {
+        self.uplink + self.downlink
+    }
+}
+
+/// 某一天的流量记账，按出站类别、节点文件与订阅汇总
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DailyUsage {
+    /// 本地日期，如 "2026-10-18"
+    pub date: String,
+    /// 所选网卡的系统流量
+    #[serde(default)]
+    pub system: ByteCount,
+    #[serde(default)]
+    pub proxy: ByteCount,
+    #[serde(default)]
+    pub direct: ByteCount,
+    #[serde(default)]
+    pub block: ByteCount,
+    /// 其他出站（如 dns-out）
+    #[serde(default)]
+    pub other: ByteCount,
+    #[serde(default)]
+    pub nodes: BTreeMap<String, ByteCount>,
+    #[serde(default)]
+    pub subscriptions: BTreeMap<String, ByteCount>,
+}
+
+/// 流量统计周期
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum UsagePeriod {
+    /// 今天
+    Day,
+    /// 最近 7 天
+    Week,
+    /// 本月
+    Month,
+}
+
+/// 单个节点或订阅的流量
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UsageEntry {
+    pub name: String,
+    pub uplink: u64,
+    pub downlink: u64,
+}
+
+/// 周期内的流量汇总，节点与订阅按总流量降序
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UsageReport {
+    pub period: UsagePeriod,
+    pub from: String,
+    pub to: String,
+    pub proxy: ByteCount,
+    pub direct: ByteCount,
+    pub block: ByteCount,
+    pub other: ByteCount,
+    pub nodes: Vec<UsageEntry>,
+    pub subscriptions: Vec<UsageEntry>,
+}
+
+/// 流量历史的汇总粒度
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum TrafficGranularity {
+    Hour,
+    Day,
+    Month,
+}
+
+/// 一个小时/天/月内的流量
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TrafficBucket {
+    /// 本地时间，如 "2026-10-18 13"、"2026-10-18"、"2026-10"
+    pub start: String,
+    /// 系统网卡流量
+    #[serde(default)]
+    pub system: ByteCount,
+    /// 经代理出站（节点）的流量，与每日记账的 proxy 一致
+    #[serde(default)]
+    pub proxy: ByteCount,
+}
+
+/// 流量上限按哪种流量计算
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum CapSource {
+    #[default]
+    System,
+    Proxy,
+}
+
+/// 每月流量上限设置
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TrafficCapSettings {
+    #[serde(default)]
+    pub enabled: bool,
+    /// 每个计费周期的上限（字节）
+    #[serde(default)]
+    pub cap_bytes: u64,
+    /// 用量达到该百分比时提醒
+    #[serde(default = "default_warn_percent")]
+    pub warn_percent: u8,
+    /// 每月的结算日（1-28）
+    #[serde(default = "default_reset_day")]
+    pub reset_day: u32,
+    #[serde(default)]
+    pub source: CapSource,
+}
+
+impl Default for TrafficCapSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            cap_bytes: 0,
+            warn_percent: default_warn_percent(),
+            reset_day: default_reset_day(),
+            source: CapSource::default(),
+        }
+    }
+}
+
+fn default_warn_percent() -> u8 {
+    80
+}
+
+fn default_reset_day() -> u32 {
+    1
+}
+
+/// 当前计费周期的用量
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TrafficCapStatus {
+    /// 周期开始日期，如 "2026-10-01"
+    pub period_start: String,
+    pub used: u64,
+    pub cap_bytes: u64,
+    pub percent: f64,
+    pub source: CapSource,
+    pub exceeded: bool,
+}
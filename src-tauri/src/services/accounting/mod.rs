@@ -0,0 +1,83 @@
+// 流量记账：把流量监控采样得到的出站增量记到代理/直连/拦截类别，
+// 以及当时正在使用的节点文件和它所属的订阅
+pub mod store;
+
+use crate::models::{ByteCount, DailyUsage, NodeGroup};
+use crate::services::{groups, proxylink, subscription, xray};
+use crate::utils::paths;
+use std::collections::HashMap;
+use std::path::Path;
+
+/// 增量的归属
+enum Target {
+    /// 代理流量，附带实际使用的节点文件
+    Proxy(Option<String>),
+    Direct,
+    Block,
+    Other,
+}
+
+/// 将一段时间的系统流量与各出站增量记入今天的统计
+pub fn record(system: ByteCount, outbounds: HashMap<String, ByteCount>) {
+    if system.total() == 0 && outbounds.is_empty() {
+        return;
+    }
+
+    let active = xray::current_node();
+    let groups = if outbounds.is_empty() {
+        Vec::new()
+    } else {
+        groups::list()
+    };
+    store::record(system, |day: &mut DailyUsage| {
+        for (tag, delta) in outbounds {
+            match target(&tag, active.as_deref(), &groups) {
+                Target::Proxy(node_file) => {
+                    day.proxy.add(delta);
+                    if let Some(node_file) = node_file {
+                        if let Some(name) = subscription_of(&node_file) {
+                            day.subscriptions.entry(name).or_default().add(delta);
+                        }
+                        day.nodes.entry(node_file).or_default().add(delta);
+                    }
+                }
+                Target::Direct => day.direct.add(delta),
+                Target::Block => day.block.add(delta),
+                Target::Other => day.other.add(delta),
+            }
+        }
+    });
+}
+
+/// 出站 tag 的归属：proxy 为当前节点，"组@序号" 为节点组中的成员
+fn target(tag: &str, active: Option<&str>, groups: &[NodeGroup]) -> Target {
+    match tag {
+        "proxy" => Target::Proxy(active.map(str::to_string)),
+        "direct" => Target::Direct,
+        "block" => Target::Block,
+        _ => match tag.rsplit_once('@') {
+            Some((group_tag, index)) => {
+                let member = groups
+                    .iter()
+                    .find(|g| g.tag == group_tag)
+                    .zip(index.parse::<usize>().ok())
+                    .and_then(|(g, i)| g.node_files.get(i.checked_sub(1)?).cloned());
+                Target::Proxy(member)
+            }
+            None => Target::Other,
+        },
+    }
+}
+
+/// 节点文件所属的订阅名称
+fn subscription_of(node_file: &str) -> Option<String> {
+    let outbounds_dir = paths::get_outbounds_dir();
+    subscription::store::list()
+        .into_iter()
+        .find(|s| {
+            proxylink::subscription_dir(&s.name)
+                .strip_prefix(&outbounds_dir)
+                .is_ok_and(|dir| Path::new(node_file).starts_with(dir))
+        })
+        .map(|s| s.name)
+}
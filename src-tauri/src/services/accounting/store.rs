@@ -0,0 +1,359 @@
+// 流量存储（状态目录 traffic_usage.json）：按天记录的系统流量与出站记账，
+// 以及系统/代理流量的小时、月汇总，并按周期生成报告
+use crate::models::{
+    ByteCount, DailyUsage, NodeRename, TrafficBucket, TrafficGranularity, UsageEntry, UsagePeriod,
+    UsageReport,
+};
+use crate::services::subscription::sync;
+use crate::utils::json::{load_json, save_json};
+use crate::utils::paths;
+use chrono::{DateTime, Datelike, Duration, Local, NaiveDate};
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+/// 各粒度保留的记录数
+const HOURLY_RETENTION: usize = 24 * 7;
+const DAILY_RETENTION_DAYS: i64 = 400;
+const MONTHLY_RETENTION: usize = 120;
+
+const HOUR_FORMAT: &str = "%Y-%m-%d %H";
+pub const DATE_FORMAT: &str = "%Y-%m-%d";
+
+/// 各粒度的记录按时间升序
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct Usage {
+    #[serde(default)]
+    hourly: Vec<TrafficBucket>,
+    #[serde(default)]
+    daily: Vec<DailyUsage>,
+    #[serde(default)]
+    monthly: Vec<TrafficBucket>,
+}
+
+// 首次访问时从文件加载，之后以内存为准；记账只更新内存，由 flush 定期写回
+static USAGE: Lazy<Mutex<Usage>> = Lazy::new(|| Mutex::new(read()));
+// 内存中有尚未写回的记账
+static DIRTY: AtomicBool = AtomicBool::new(false);
+
+fn usage_path() -> PathBuf {
+    paths::get_state_dir().join("traffic_usage.json")
+}
+
+fn read() -> Usage {
+    load_json(&usage_path())
+}
+
+fn write(usage: &Usage) -> Result<(), Box<dyn std::error::Error>> {
+    DIRTY.store(false, Ordering::Relaxed);
+    save_json(&usage_path(), usage).inspect_err(|_| DIRTY.store(true, Ordering::Relaxed))
+}
+
+/// 将尚未保存的记账写入文件
+pub fn flush() -> Result<(), Box<dyn std::error::Error>> {
+    if !DIRTY.load(Ordering::Relaxed) {
+        return Ok(());
+    }
+    let usage = USAGE.lock().unwrap();
+    write(&usage)
+}
+
+fn today() -> NaiveDate {
+    Local::now().date_naive()
+}
+
+fn format_date(date: NaiveDate) -> String {
+    date.format(DATE_FORMAT).to_string()
+}
+
+fn month_key(year: i32, month: u32) -> String {
+    format!("{:04}-{:02}", year, month)
+}
+
+/// 累加系统流量并由 f 修改今天的出站记账，同时更新小时与月汇总，由 flush 写回文件
+pub fn record<F>(system: ByteCount, f: F)
+where
+    F: FnOnce(&mut DailyUsage),
+{
+    let now = Local::now();
+    let today = now.date_naive();
+    let date = format_date(today);
+    let mut usage = USAGE.lock().unwrap();
+    let usage = &mut *usage;
+
+    if usage.daily.last().is_none_or(|d| d.date != date) {
+        usage.daily.push(DailyUsage {
+            date,
+            ..Default::default()
+        });
+        let oldest = format_date(today - Duration::days(DAILY_RETENTION_DAYS));
+        usage.daily.retain(|d| d.date >= oldest);
+    }
+    let Some(day) = usage.daily.last_mut() else {
+        return;
+    };
+    let before = day.proxy;
+    day.system.add(system);
+    f(day);
+    let proxy = ByteCount {
+        uplink: day.proxy.uplink - before.uplink,
+        downlink: day.proxy.downlink - before.downlink,
+    };
+
+    for (buckets, start, retention) in [
+        (
+            &mut usage.hourly,
+            now.format(HOUR_FORMAT).to_string(),
+            HOURLY_RETENTION,
+        ),
+        (
+            &mut usage.monthly,
+            month_key(now.year(), now.month()),
+            MONTHLY_RETENTION,
+        ),
+    ] {
+        if buckets.last().is_none_or(|b| b.start != start) {
+            buckets.push(TrafficBucket {
+                start,
+                ..Default::default()
+            });
+            if buckets.len() > retention {
+                let excess = buckets.len() - retention;
+                buckets.drain(..excess);
+            }
+        }
+        if let Some(bucket) = buckets.last_mut() {
+            bucket.system.add(system);
+            bucket.proxy.add(proxy);
+        }
+    }
+    DIRTY.store(true, Ordering::Relaxed);
+}
+
+/// 节点文件改名后迁移按节点记录的流量
+pub fn rename(renames: &[NodeRename]) -> Result<(), Box<dyn std::error::Error>> {
+    let mut usage = USAGE.lock().unwrap();
+    if apply_renames(&mut usage.daily, renames) {
+        write(&usage)?;
+    }
+    Ok(())
+}
+
+/// 逐天迁移，改名到已有记录的文件名时累加。返回是否有记录被迁移
+fn apply_renames(usage: &mut [DailyUsage], renames: &[NodeRename]) -> bool {
+    let mut changed = false;
+    for day in usage.iter_mut() {
+        changed |= sync::migrate_keys(&mut day.nodes, renames, ByteCount::add);
+    }
+    changed
+}
+
+/// 最近 days 天（含今天）的每日记录
+pub fn daily(days: u32) -> Vec<DailyUsage> {
+    daily_since(&format_date(
+        today() - Duration::days(i64::from(days.max(1)) - 1),
+    ))
+}
+
+/// 从 from（含，"YYYY-MM-DD"）起的每日记录
+pub fn daily_since(from: &str) -> Vec<DailyUsage> {
+    USAGE
+        .lock()
+        .unwrap()
+        .daily
+        .iter()
+        .filter(|d| d.date.as_str() >= from)
+        .cloned()
+        .collect()
+}
+
+/// 最近 count 个小时/天/月的系统与代理流量（含当前），没有流量的时段补零，最旧的在前
+pub fn query(granularity: TrafficGranularity, count: u32) -> Vec<TrafficBucket> {
+    let keys = bucket_keys(granularity, count, Local::now());
+
+    let usage = USAGE.lock().unwrap();
+    keys.into_iter()
+        .map(|start| {
+            let found = match granularity {
+                TrafficGranularity::Hour => usage.hourly.iter().find(|b| b.start == start).cloned(),
+                TrafficGranularity::Day => {
+                    usage
+                        .daily
+                        .iter()
+                        .find(|d| d.date == start)
+                        .map(|d| TrafficBucket {
+                            start: d.date.clone(),
+                            system: d.system,
+                            proxy: d.proxy,
+                        })
+                }
+                TrafficGranularity::Month => {
+                    usage.monthly.iter().find(|b| b.start == start).cloned()
+                }
+            };
+            found.unwrap_or(TrafficBucket {
+                start,
+                ..Default::default()
+            })
+        })
+        .collect()
+}
+
+/// 截至 now 的最近 count 个时段的键，最旧的在前
+fn bucket_keys(granularity: TrafficGranularity, count: u32, now: DateTime<Local>) -> Vec<String> {
+    (0..i64::from(count.max(1)))
+        .rev()
+        .map(|i| match granularity {
+            TrafficGranularity::Hour => (now - Duration::hours(i)).format(HOUR_FORMAT).to_string(),
+            TrafficGranularity::Day => (now - Duration::days(i)).format(DATE_FORMAT).to_string(),
+            TrafficGranularity::Month => {
+                let months = i64::from(now.year()) * 12 + i64::from(now.month0()) - i;
+                month_key((months / 12) as i32, (months % 12) as u32 + 1)
+            }
+        })
+        .collect()
+}
+
+/// 汇总周期内的流量
+pub fn report(period: UsagePeriod) -> UsageReport {
+    let today = today();
+    let from = match period {
+        UsagePeriod::Day => today,
+        UsagePeriod::Week => today - Duration::days(6),
+        UsagePeriod::Month => today.with_day(1).unwrap_or(today),
+    };
+    let (from, to) = (format_date(from), format_date(today));
+
+    let mut report = UsageReport {
+        period,
+        from: from.clone(),
+        to: to.clone(),
+        proxy: ByteCount::default(),
+        direct: ByteCount::default(),
+        block: ByteCount::default(),
+        other: ByteCount::default(),
+        nodes: Vec::new(),
+        subscriptions: Vec::new(),
+    };
+    let mut nodes: BTreeMap<String, ByteCount> = BTreeMap::new();
+    let mut subscriptions: BTreeMap<String, ByteCount> = BTreeMap::new();
+
+    let usage = USAGE.lock().unwrap();
+    for day in usage
+        .daily
+        .iter()
+        .filter(|d| d.date >= from && d.date <= to)
+    {
+        report.proxy.add(day.proxy);
+        report.direct.add(day.direct);
+        report.block.add(day.block);
+        report.other.add(day.other);
+        for (name, count) in &day.nodes {
+            nodes.entry(name.clone()).or_default().add(*count);
+        }
+        for (name, count) in &day.subscriptions {
+            subscriptions.entry(name.clone()).or_default().add(*count);
+        }
+    }
+
+    report.nodes = entries(nodes);
+    report.subscriptions = entries(subscriptions);
+    report
+}
+
+fn entries(counts: BTreeMap<String, ByteCount>) -> Vec<UsageEntry> {
+    let mut entries: Vec<(String, ByteCount)> = counts.into_iter().collect();
+    entries.sort_by_key(|(_, count)| std::cmp::Reverse(count.total()));
+    entries
+        .into_iter()
+        .map(|(name, count)| UsageEntry {
+            name,
+            uplink: count.uplink,
+            downlink: count.downlink,
+        })
+        .collect()
+}
+
+/// 清空全部流量记录
+pub fn clear() -> Result<(), Box<dyn std::error::Error>> {
+    let mut usage = USAGE.lock().unwrap();
+    *usage = Usage::default();
+    write(&usage)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn day(nodes: &[(&str, u64)]) -> DailyUsage {
+        DailyUsage {
+            date: "2026-10-18".to_string(),
+            nodes: nodes
+                .iter()
+                .map(|(file, bytes)| {
+                    let count = ByteCount {
+                        uplink: *bytes,
+                        downlink: 0,
+                    };
+                    (file.to_string(), count)
+                })
+                .collect(),
+            ..Default::default()
+        }
+    }
+
+    fn rename(from: &str, to: &str) -> NodeRename {
+        NodeRename {
+            from: from.to_string(),
+            to: to.to_string(),
+        }
+    }
+
+    fn uplinks(day: &DailyUsage) -> Vec<(&str, u64)> {
+        day.nodes
+            .iter()
+            .map(|(file, count)| (file.as_str(), count.uplink))
+            .collect()
+    }
+
+    #[test]
+    fn merges_into_existing_entries() {
+        let mut usage = vec![day(&[("a", 1), ("b", 2)]), day(&[("b", 5)])];
+        assert!(apply_renames(&mut usage, &[rename("a", "b")]));
+        assert_eq!(uplinks(&usage[0]), vec![("b", 3)]);
+        assert_eq!(uplinks(&usage[1]), vec![("b", 5)]);
+
+        assert!(!apply_renames(&mut usage, &[rename("x", "y")]));
+    }
+
+    #[test]
+    fn month_keys_cross_year_boundary() {
+        let now = Local.with_ymd_and_hms(2026, 2, 15, 12, 0, 0).unwrap();
+        assert_eq!(
+            bucket_keys(TrafficGranularity::Month, 4, now),
+            vec!["2025-11", "2025-12", "2026-01", "2026-02"]
+        );
+        assert_eq!(
+            bucket_keys(TrafficGranularity::Month, 15, now)[..2],
+            ["2024-12", "2025-01"]
+        );
+        // 至少返回当前时段
+        assert_eq!(
+            bucket_keys(TrafficGranularity::Month, 0, now),
+            vec!["2026-02"]
+        );
+    }
+
+    #[test]
+    fn day_keys_end_today() {
+        let now = Local.with_ymd_and_hms(2026, 3, 1, 12, 0, 0).unwrap();
+        assert_eq!(
+            bucket_keys(TrafficGranularity::Day, 3, now),
+            vec!["2026-02-27", "2026-02-28", "2026-03-01"]
+        );
+    }
+}
@@ -0,0 +1,81 @@
+// Xray gRPC API 客户端：连接 05_api.json 中配置的 API 地址
+pub mod routing;
+pub mod stats;
+
+use crate::services::ports;
+use once_cell::sync::Lazy;
+use std::error::Error;
+use std::sync::Mutex;
+use std::time::Duration;
+use tonic::Streaming;
+use tonic::codec::ProstCodec;
+use tonic::codegen::http::uri::PathAndQuery;
+use tonic::transport::{Channel, Endpoint};
+
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(2);
+
+// 按地址缓存的连接，API 地址改变后重建
+static CHANNEL: Lazy<Mutex<Option<(String, Channel)>>> = Lazy::new(|| Mutex::new(None));
+
+fn channel() -> Result<Channel, Box<dyn Error>> {
+    let (host, port) = ports::api_listen().ok_or("05_api.json 未配置 API 监听地址")?;
+    let host = if host.contains(':') {
+        format!("[{}]", host)
+    } else {
+        host
+    };
+    let uri = format!("http://{}:{}", host, port);
+
+    let mut cached = CHANNEL.lock().unwrap();
+    if let Some((cached_uri, channel)) = cached.as_ref()
+        && *cached_uri == uri
+    {
+        return Ok(channel.clone());
+    }
+
+    let channel = Endpoint::from_shared(uri.clone())?
+        .connect_timeout(REQUEST_TIMEOUT)
+        .timeout(REQUEST_TIMEOUT)
+        .connect_lazy();
+    *cached = Some((uri, channel.clone()));
+    Ok(channel)
+}
+
+/// 发起一次 unary 调用，path 如 "/xray.app.stats.command.StatsService/QueryStats"
+async fn unary<Req, Resp>(path: &'static str, request: Req) -> Result<Resp, Box<dyn Error>>
+where
+    Req: prost::Message + Send + Sync + 'static,
+    Resp: prost::Message + Default + Send + Sync + 'static,
+{
+    let mut client = tonic::client::Grpc::new(channel()?);
+    client.ready().await?;
+    let response = client
+        .unary(
+            tonic::Request::new(request),
+            PathAndQuery::from_static(path),
+            ProstCodec::<Req, Resp>::default(),
+        )
+        .await?;
+    Ok(response.into_inner())
+}
+
+/// 发起一次服务端流式调用
+async fn server_streaming<Req, Resp>(
+    path: &'static str,
+    request: Req,
+) -> Result<Streaming<Resp>, Box<dyn Error>>
+where
+    Req: prost::Message + Send + Sync + 'static,
+    Resp: prost::Message + Default + Send + Sync + 'static,
+{
+    let mut client = tonic::client::Grpc::new(channel()?);
+    client.ready().await?;
+    let response = client
+        .server_streaming(
+            tonic::Request::new(request),
+            PathAndQuery::from_static(path),
+            ProstCodec::<Req, Resp>::default(),
+        )
+        .await?;
+    Ok(response.into_inner())
+}
@@ -0,0 +1,72 @@
+// RoutingService：订阅核心的路由统计，每条新连接推送一次路由结果
+use std::error::Error;
+use std::net::IpAddr;
+use tonic::Streaming;
+
+const SUBSCRIBE_ROUTING_STATS: &str =
+    "/xray.app.router.command.RoutingService/SubscribeRoutingStats";
+
+#[derive(Clone, PartialEq, prost::Message)]
+struct SubscribeRoutingStatsRequest {
+    #[prost(string, repeated, tag = "1")]
+    field_selectors: Vec<String>,
+}
+
+/// 一次路由决策
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct RoutingContext {
+    #[prost(string, tag = "1")]
+    pub inbound_tag: String,
+    /// xray.common.net.Network：2 = TCP，3 = UDP，4 = UNIX
+    #[prost(int32, tag = "2")]
+    pub network: i32,
+    #[prost(bytes = "vec", repeated, tag = "3")]
+    pub source_ips: Vec<Vec<u8>>,
+    #[prost(bytes = "vec", repeated, tag = "4")]
+    pub target_ips: Vec<Vec<u8>>,
+    #[prost(uint32, tag = "5")]
+    pub source_port: u32,
+    #[prost(uint32, tag = "6")]
+    pub target_port: u32,
+    #[prost(string, tag = "7")]
+    pub target_domain: String,
+    #[prost(string, tag = "8")]
+    pub protocol: String,
+    #[prost(string, tag = "12")]
+    pub outbound_tag: String,
+}
+
+impl RoutingContext {
+    pub fn network_name(&self) -> &'static str {
+        match self.network {
+            3 => "udp",
+            4 => "unix",
+            _ => "tcp",
+        }
+    }
+
+    pub fn source_ip(&self) -> Option<IpAddr> {
+        self.source_ips.first().and_then(|ip| ip_from_bytes(ip))
+    }
+
+    pub fn target_ip(&self) -> Option<IpAddr> {
+        self.target_ips.first().and_then(|ip| ip_from_bytes(ip))
+    }
+}
+
+fn ip_from_bytes(bytes: &[u8]) -> Option<IpAddr> {
+    match bytes.len() {
+        4 => Some(IpAddr::from(<[u8; 4]>::try_from(bytes).ok()?)),
+        16 => Some(IpAddr::from(<[u8; 16]>::try_from(bytes).ok()?)),
+        _ => None,
+    }
+}
+
+/// 订阅路由统计，核心退出时流结束
+pub async fn subscribe() -> Result<Streaming<RoutingContext>, Box<dyn Error>> {
+    super::server_streaming(
+        SUBSCRIBE_ROUTING_STATS,
+        SubscribeRoutingStatsRequest::default(),
+    )
+    .await
+}
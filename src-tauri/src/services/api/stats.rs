@@ -0,0 +1,309 @@
+// StatsService：按入站/出站查询流量计数并计算速率
+use crate::models::{TrafficCounter, TrafficSource, TrafficStats};
+use crate::utils::paths;
+use once_cell::sync::Lazy;
+use serde_json::{Value, json};
+use std::collections::BTreeMap;
+use std::error::Error;
+use std::fs;
+use std::sync::Mutex;
+use std::time::Instant;
+
+const QUERY_STATS: &str = "/xray.app.stats.command.StatsService/QueryStats";
+const POLICY_CONFIG: &str = "04_policy.json";
+const POLICY_FLAGS: [&str; 4] = [
+    "statsInboundUplink",
+    "statsInboundDownlink",
+    "statsOutboundUplink",
+    "statsOutboundDownlink",
+];
+// API 自身的入站不计入代理流量
+const API_TAG: &str = "api";
+
+#[derive(Clone, PartialEq, prost::Message)]
+struct QueryStatsRequest {
+    #[prost(string, tag = "1")]
+    pattern: String,
+    #[prost(bool, tag = "2")]
+    reset: bool,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+struct Stat {
+    #[prost(string, tag = "1")]
+    name: String,
+    #[prost(int64, tag = "2")]
+    value: i64,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+struct QueryStatsResponse {
+    #[prost(message, repeated, tag = "1")]
+    stat: Vec<Stat>,
+}
+
+/// 计数键：("inbound" | "outbound", tag)
+pub type CounterKey = (String, String);
+
+struct Snapshot {
+    at: Instant,
+    totals: BTreeMap<CounterKey, (u64, u64)>,
+    speeds: BTreeMap<CounterKey, (u64, u64)>,
+}
+
+static LAST: Lazy<Mutex<Option<Snapshot>>> = Lazy::new(|| Mutex::new(None));
+
+/// 查询名称匹配 pattern 的计数，空字符串返回全部
+pub async fn query(pattern: &str) -> Result<Vec<(String, i64)>, Box<dyn Error>> {
+    let response: QueryStatsResponse = super::unary(
+        QUERY_STATS,
+        QueryStatsRequest {
+            pattern: pattern.to_string(),
+            reset: false,
+        },
+    )
+    .await?;
+    Ok(response
+        .stat
+        .into_iter()
+        .map(|stat| (stat.name, stat.value))
+        .collect())
+}
+
+/// 全部入站/出站的累计流量（上行，下行）
+pub async fn counters() -> Result<BTreeMap<CounterKey, (u64, u64)>, Box<dyn Error>> {
+    Ok(parse_counters(query("").await?))
+}
+
+/// 读取入站/出站流量，总量与速率按入站汇总
+pub async fn traffic() -> Result<TrafficStats, Box<dyn Error>> {
+    let totals = counters().await?;
+    let speeds = update_speeds(totals.clone());
+
+    let counters = |kind: &str| -> Vec<TrafficCounter> {
+        totals
+            .iter()
+            .filter(|((k, _), _)| k == kind)
+            .map(|(key, &(uplink, downlink))| {
+                let (upload_speed, download_speed) = speeds.get(key).copied().unwrap_or_default();
+                TrafficCounter {
+                    tag: key.1.clone(),
+                    uplink,
+                    downlink,
+                    upload_speed,
+                    download_speed,
+                }
+            })
+            .collect()
+    };
+    let inbounds: Vec<TrafficCounter> = counters("inbound")
+        .into_iter()
+        .filter(|c| c.tag != API_TAG)
+        .collect();
+    let outbounds = counters("outbound");
+
+    Ok(TrafficStats {
+        upload_speed: inbounds.iter().map(|c| c.upload_speed).sum(),
+        download_speed: inbounds.iter().map(|c| c.download_speed).sum(),
+        upload_total: inbounds.iter().map(|c| c.uplink).sum(),
+        download_total: inbounds.iter().map(|c| c.downlink).sum(),
+        source: TrafficSource::Xray,
+        inbounds,
+        outbounds,
+        interfaces: Vec::new(),
+    })
+}
+
+/// 解析 "inbound>>>socks-in>>>traffic>>>uplink" 形式的计数名称
+fn parse_counters(stats: Vec<(String, i64)>) -> BTreeMap<CounterKey, (u64, u64)> {
+    let mut counters: BTreeMap<CounterKey, (u64, u64)> = BTreeMap::new();
+    for (name, value) in stats {
+        let parts: Vec<&str> = name.split(">>>").collect();
+        let [kind @ ("inbound" | "outbound"), tag, "traffic", direction] = parts[..] else {
+            continue;
+        };
+        let entry = counters
+            .entry((kind.to_string(), tag.to_string()))
+            .or_default();
+        let value = value.max(0) as u64;
+        match direction {
+            "uplink" => entry.0 = value,
+            "downlink" => entry.1 = value,
+            _ => {}
+        }
+    }
+    counters
+}
+
+/// 与上次的计数比较得到速率；间隔过短时沿用上次的速率
+fn update_speeds(totals: BTreeMap<CounterKey, (u64, u64)>) -> BTreeMap<CounterKey, (u64, u64)> {
+    advance(&mut LAST.lock().unwrap(), totals, Instant::now())
+}
+
+fn advance(
+    last: &mut Option<Snapshot>,
+    totals: BTreeMap<CounterKey, (u64, u64)>,
+    now: Instant,
+) -> BTreeMap<CounterKey, (u64, u64)> {
+    let previous = match last.as_ref() {
+        Some(snapshot) => snapshot,
+        None => {
+            *last = Some(Snapshot {
+                at: now,
+                totals,
+                speeds: BTreeMap::new(),
+            });
+            return BTreeMap::new();
+        }
+    };
+
+    let elapsed = now.duration_since(previous.at).as_secs_f64();
+    if elapsed < 0.5 {
+        return previous.speeds.clone();
+    }
+
+    let speeds = totals
+        .iter()
+        .map(|(key, &(up, down))| {
+            let (last_up, last_down) = previous.totals.get(key).copied().unwrap_or_default();
+            // 核心重启后计数归零，此时本次计数即为增量
+            let delta = |current: u64, last: u64| {
+                if current >= last {
+                    current - last
+                } else {
+                    current
+                }
+            };
+            let speed = |delta: u64| (delta as f64 / elapsed) as u64;
+            (
+                key.clone(),
+                (speed(delta(up, last_up)), speed(delta(down, last_down))),
+            )
+        })
+        .collect::<BTreeMap<_, _>>();
+
+    *last = Some(Snapshot {
+        at: now,
+        totals,
+        speeds: speeds.clone(),
+    });
+    speeds
+}
+
+/// 打开 04_policy.json 中的入站/出站流量统计，并确保存在顶层 stats 对象
+pub fn enable_policy_stats() -> Result<(), Box<dyn Error>> {
+    let path = paths::get_confdir().join(POLICY_CONFIG);
+    let mut config: Value = match fs::read_to_string(&path) {
+        Ok(content) => serde_json::from_str(&content)?,
+        Err(_) => json!({}),
+    };
+    let original = config.clone();
+
+    if !config["policy"].is_object() {
+        config["policy"] = json!({});
+    }
+    if !config["policy"]["system"].is_object() {
+        config["policy"]["system"] = json!({});
+    }
+    for flag in POLICY_FLAGS {
+        config["policy"]["system"][flag] = Value::Bool(true);
+    }
+    if !config["stats"].is_object() {
+        config["stats"] = json!({});
+    }
+
+    if config != original {
+        fs::write(&path, serde_json::to_string_pretty(&config)?)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    fn key(kind: &str, tag: &str) -> CounterKey {
+        (kind.to_string(), tag.to_string())
+    }
+
+    fn stats(entries: &[(&str, i64)]) -> Vec<(String, i64)> {
+        entries
+            .iter()
+            .map(|(name, value)| (name.to_string(), *value))
+            .collect()
+    }
+
+    #[test]
+    fn parses_counter_names() {
+        let counters = parse_counters(stats(&[
+            ("inbound>>>socks-in>>>traffic>>>uplink", 100),
+            ("inbound>>>socks-in>>>traffic>>>downlink", 2000),
+            ("outbound>>>proxy>>>traffic>>>downlink", 1500),
+            // 负值按 0 处理
+            ("outbound>>>direct>>>traffic>>>uplink", -1),
+            // 其他计数忽略
+            ("user>>>a@example.com>>>traffic>>>uplink", 10),
+            ("inbound>>>socks-in>>>traffic", 10),
+            ("inbound>>>socks-in>>>online>>>uplink", 10),
+        ]));
+        assert_eq!(
+            counters.into_iter().collect::<Vec<_>>(),
+            vec![
+                (key("inbound", "socks-in"), (100, 2000)),
+                (key("outbound", "direct"), (0, 0)),
+                (key("outbound", "proxy"), (0, 1500)),
+            ]
+        );
+    }
+
+    #[test]
+    fn computes_speeds_between_snapshots() {
+        let start = Instant::now();
+        let mut last = None;
+        let totals =
+            |up: u64, down: u64| BTreeMap::from([(key("inbound", "socks-in"), (up, down))]);
+
+        // 首次只记录快照
+        assert!(advance(&mut last, totals(1000, 4000), start).is_empty());
+
+        let speeds = advance(
+            &mut last,
+            totals(3000, 8000),
+            start + Duration::from_secs(2),
+        );
+        assert_eq!(speeds[&key("inbound", "socks-in")], (1000, 2000));
+
+        // 间隔过短沿用上次速率，且不更新快照
+        let speeds = advance(
+            &mut last,
+            totals(9000, 9000),
+            start + Duration::from_millis(2200),
+        );
+        assert_eq!(speeds[&key("inbound", "socks-in")], (1000, 2000));
+
+        // 新出现的计数从 0 起算
+        let mut next = totals(5000, 10000);
+        next.insert(key("outbound", "proxy"), (400, 800));
+        let speeds = advance(&mut last, next, start + Duration::from_secs(4));
+        assert_eq!(speeds[&key("inbound", "socks-in")], (1000, 1000));
+        assert_eq!(speeds[&key("outbound", "proxy")], (200, 400));
+    }
+
+    #[test]
+    fn treats_counter_reset_as_delta() {
+        let start = Instant::now();
+        let mut last = None;
+        let totals =
+            |up: u64, down: u64| BTreeMap::from([(key("inbound", "socks-in"), (up, down))]);
+
+        advance(&mut last, totals(50_000, 80_000), start);
+        // 核心重启后计数小于上次，本次计数即为增量
+        let speeds = advance(
+            &mut last,
+            totals(300, 80_100),
+            start + Duration::from_secs(1),
+        );
+        assert_eq!(speeds[&key("inbound", "socks-in")], (300, 100));
+    }
+}
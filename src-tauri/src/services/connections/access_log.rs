@@ -0,0 +1,313 @@
+// 访问日志：跟踪 00_log.json 中配置的 access 日志文件，解析新增的连接记录
+use crate::models::{Connection, ConnectionOrigin};
+use crate::services::xray;
+use crate::utils::{paths, time};
+use chrono::{Local, NaiveDateTime};
+use serde_json::Value;
+use std::fs::{self, File};
+use std::io::{Read, Seek, SeekFrom};
+use std::net::IpAddr;
+use std::path::PathBuf;
+use std::thread;
+use std::time::Duration;
+
+const POLL_INTERVAL: Duration = Duration::from_secs(1);
+const LOG_CONFIG: &str = "00_log.json";
+
+/// 访问日志路径，相对路径基于核心的工作目录（状态目录）；未启用时返回 None
+fn log_path() -> Option<PathBuf> {
+    let content = fs::read_to_string(paths::get_confdir().join(LOG_CONFIG)).ok()?;
+    let config: Value = serde_json::from_str(&content).ok()?;
+    let access = config.pointer("/log/access")?.as_str()?.trim();
+    if access.is_empty() || access == "none" {
+        return None;
+    }
+    Some(paths::get_state_dir().join(access))
+}
+
+/// 在后台线程中跟踪访问日志，只处理启动之后、核心运行期间写入的行
+pub fn follow<F>(on_entry: F)
+where
+    F: Fn(Connection) + Send + 'static,
+{
+    thread::spawn(move || {
+        // None 表示尚未读取过，从文件末尾开始
+        let mut position: Option<u64> = None;
+        let mut pending = Vec::new();
+
+        loop {
+            thread::sleep(POLL_INTERVAL);
+
+            let Some(mut file) = log_path().and_then(|path| File::open(path).ok()) else {
+                // 文件之后才创建时，其中的内容都是新的
+                position = Some(0);
+                continue;
+            };
+            let len = file.metadata().map(|m| m.len()).unwrap_or(0);
+            // 核心未运行时只跟随文件末尾，不把残留内容当作新连接
+            if xray::current_pid().is_none() {
+                position = Some(len);
+                pending.clear();
+                continue;
+            }
+            let start = match position {
+                None => len,
+                // 文件被截断或轮转
+                Some(p) if p > len => {
+                    pending.clear();
+                    0
+                }
+                Some(p) => p,
+            };
+
+            let mut chunk = Vec::new();
+            if file.seek(SeekFrom::Start(start)).is_err() || file.read_to_end(&mut chunk).is_err() {
+                continue;
+            }
+            position = Some(start + chunk.len() as u64);
+            pending.extend_from_slice(&chunk);
+
+            // 最后一行可能尚未写完，留到下次
+            let Some(end) = pending.iter().rposition(|b| *b == b'\n') else {
+                continue;
+            };
+            let complete: Vec<u8> = pending.drain(..=end).collect();
+            for line in String::from_utf8_lossy(&complete).lines() {
+                if let Some(connection) = parse(line) {
+                    on_entry(connection);
+                }
+            }
+        }
+    });
+}
+
+/// 解析访问日志中的一行，如
+/// "2024/05/01 12:00:00.123456 from 127.0.0.1:52044 accepted tcp:www.google.com:443 [socks-in -> proxy]"
+pub fn parse(line: &str) -> Option<Connection> {
+    let (date, rest) = line.trim().split_once(' ')?;
+    let (clock, rest) = rest.split_once(' ')?;
+    let start =
+        NaiveDateTime::parse_from_str(&format!("{} {}", date, clock), "%Y/%m/%d %H:%M:%S%.f")
+            .ok()
+            .and_then(|t| t.and_local_timezone(Local).single())
+            .map(|t| t.timestamp_millis() as u64)
+            .unwrap_or_else(time::now_millis);
+
+    let rest = rest.strip_prefix("from ")?;
+    let (source, rest) = rest.split_once(' ')?;
+    // 新版本的来源地址带有 "tcp:" 前缀
+    let source = source
+        .strip_prefix("tcp:")
+        .or_else(|| source.strip_prefix("udp:"))
+        .unwrap_or(source);
+
+    let (status, rest) = rest.trim_start().split_once(' ')?;
+    let rejected = match status {
+        "accepted" => false,
+        "rejected" => true,
+        _ => return None,
+    };
+
+    let rest = rest.trim_start();
+    let (destination, rest) = rest.split_once(' ').unwrap_or((rest, ""));
+    let (network, address) = destination.split_once(':')?;
+    if network != "tcp" && network != "udp" {
+        return None;
+    }
+    let (host, port) = match address.rsplit_once(':') {
+        Some((host, port)) => (host, port.parse().ok()),
+        None => (address, None),
+    };
+    let host = host.trim_start_matches('[').trim_end_matches(']');
+    let (domain, destination_ip) = match host.parse::<IpAddr>() {
+        Ok(ip) => (None, Some(ip.to_string())),
+        Err(_) => (Some(host.to_string()).filter(|h| !h.is_empty()), None),
+    };
+
+    // "[入站 -> 出站]"，分隔符随路由方式不同可能为 "->"、">>" 或 "==>"
+    let (inbound_tag, outbound_tag) = rest
+        .split_once('[')
+        .and_then(|(_, detour)| detour.split_once(']'))
+        .map(|(detour, _)| {
+            [" -> ", " >> ", " ==> "]
+                .iter()
+                .find_map(|sep| detour.split_once(sep))
+                .map(|(i, o)| (i.trim().to_string(), o.trim().to_string()))
+                .unwrap_or_else(|| (detour.trim().to_string(), String::new()))
+        })
+        .unwrap_or_default();
+
+    Some(Connection {
+        id: 0,
+        network: network.to_string(),
+        source: source.to_string(),
+        domain,
+        destination_ip,
+        destination_port: port,
+        inbound_tag,
+        outbound_tag,
+        protocol: None,
+        rule: None,
+        rejected,
+        start,
+        origin: ConnectionOrigin::AccessLog,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// (网络, 来源, 域名, IP, 端口, 入站, 出站, 是否拒绝)
+    type Parsed = (
+        String,
+        String,
+        Option<String>,
+        Option<String>,
+        Option<u16>,
+        String,
+        String,
+        bool,
+    );
+
+    fn parsed(line: &str) -> Option<Parsed> {
+        parse(line).map(|c| {
+            (
+                c.network,
+                c.source,
+                c.domain,
+                c.destination_ip,
+                c.destination_port,
+                c.inbound_tag,
+                c.outbound_tag,
+                c.rejected,
+            )
+        })
+    }
+
+    fn expected(
+        network: &str,
+        source: &str,
+        domain: Option<&str>,
+        ip: Option<&str>,
+        port: Option<u16>,
+        tags: (&str, &str),
+        rejected: bool,
+    ) -> Option<Parsed> {
+        Some((
+            network.to_string(),
+            source.to_string(),
+            domain.map(str::to_string),
+            ip.map(str::to_string),
+            port,
+            tags.0.to_string(),
+            tags.1.to_string(),
+            rejected,
+        ))
+    }
+
+    #[test]
+    fn parses_old_and_new_formats() {
+        // 旧版本：来源无网络前缀
+        assert_eq!(
+            parsed(
+                "2024/05/01 12:00:00 from 127.0.0.1:52044 accepted tcp:www.google.com:443 [socks-in -> proxy]"
+            ),
+            expected(
+                "tcp",
+                "127.0.0.1:52044",
+                Some("www.google.com"),
+                None,
+                Some(443),
+                ("socks-in", "proxy"),
+                false
+            )
+        );
+        // 新版本：来源带网络前缀，行尾附带 email
+        assert_eq!(
+            parsed(
+                "2024/05/01 12:00:00.123456 from tcp:127.0.0.1:52044 accepted udp:1.1.1.1:53 [socks-in >> direct] email: user@example.com"
+            ),
+            expected(
+                "udp",
+                "127.0.0.1:52044",
+                None,
+                Some("1.1.1.1"),
+                Some(53),
+                ("socks-in", "direct"),
+                false
+            )
+        );
+    }
+
+    #[test]
+    fn parses_start_time() {
+        let start = parse(
+            "2024/05/01 12:00:00.250000 from 127.0.0.1:52044 accepted tcp:a.com:443 [socks-in -> proxy]",
+        )
+        .unwrap()
+        .start;
+        let local =
+            NaiveDateTime::parse_from_str("2024-05-01 12:00:00.250", "%Y-%m-%d %H:%M:%S%.f")
+                .unwrap()
+                .and_local_timezone(Local)
+                .single()
+                .unwrap();
+        assert_eq!(start, local.timestamp_millis() as u64);
+    }
+
+    #[test]
+    fn parses_rejected_and_ipv6() {
+        assert_eq!(
+            parsed(
+                "2024/05/01 12:00:00 from [::1]:52044 rejected tcp:[2001:db8::1]:443 [http-in ==> block]"
+            ),
+            expected(
+                "tcp",
+                "[::1]:52044",
+                None,
+                Some("2001:db8::1"),
+                Some(443),
+                ("http-in", "block"),
+                true
+            )
+        );
+    }
+
+    #[test]
+    fn splits_each_detour_separator() {
+        for sep in ["->", ">>", "==>"] {
+            let line = format!(
+                "2024/05/01 12:00:00 from 127.0.0.1:1 accepted tcp:a.com:80 [mixed-in {} auto@2]",
+                sep
+            );
+            let connection = parse(&line).unwrap();
+            assert_eq!(connection.inbound_tag, "mixed-in", "{}", sep);
+            assert_eq!(connection.outbound_tag, "auto@2", "{}", sep);
+        }
+
+        // 没有出站时整段作为入站
+        let connection =
+            parse("2024/05/01 12:00:00 from 127.0.0.1:1 accepted tcp:a.com:80 [api]").unwrap();
+        assert_eq!(
+            (
+                connection.inbound_tag.as_str(),
+                connection.outbound_tag.as_str()
+            ),
+            ("api", "")
+        );
+    }
+
+    #[test]
+    fn ignores_other_lines() {
+        let lines = [
+            "",
+            "2024/05/01 12:00:00 [Info] app/dispatcher: taking detour [proxy]",
+            "2024/05/01 12:00:00 from 127.0.0.1:1 closed tcp:a.com:80 [socks-in -> proxy]",
+            "2024/05/01 12:00:00 from 127.0.0.1:1 accepted unix:/tmp/sock [socks-in -> proxy]",
+        ];
+        for line in lines {
+            assert!(parse(line).is_none(), "{}", line);
+        }
+    }
+}
@@ -0,0 +1,149 @@
+// 活动连接：合并 RoutingService 路由统计与访问日志，按来源地址去重，
+// 推断命中的路由规则并通过事件推送给前端
+mod access_log;
+mod rules;
+
+use crate::models::{Connection, ConnectionOrigin};
+use crate::services::api::routing::{self, RoutingContext};
+use crate::services::xray;
+use crate::utils::time;
+use std::collections::VecDeque;
+use std::net::SocketAddr;
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+use tauri::{AppHandle, Emitter};
+
+/// 新增或更新连接的事件
+pub const CONNECTION_EVENT: &str = "connection";
+
+const MAX_CONNECTIONS: usize = 1000;
+/// xray 不报告连接关闭，超过该时间的条目视为已结束（与 04_policy.json 的 connIdle 一致）
+const IDLE_MILLIS: u64 = 300_000;
+/// 路由统计与访问日志记录同一连接的最大时间差
+const MERGE_WINDOW_MILLIS: u64 = 5_000;
+const RETRY_INTERVAL: Duration = Duration::from_secs(3);
+
+static CONNECTIONS: Mutex<VecDeque<Connection>> = Mutex::new(VecDeque::new());
+static NEXT_ID: AtomicU64 = AtomicU64::new(1);
+
+pub fn start(app: AppHandle) {
+    let routing_app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        loop {
+            // 核心重启后流会结束，重新订阅
+            if xray::current_pid().is_some() {
+                // 错误类型不是 Send，不能跨 await 持有
+                let stream = routing::subscribe().await.ok();
+                if let Some(mut stream) = stream {
+                    while let Ok(Some(context)) = stream.message().await {
+                        record(&routing_app, from_routing(context));
+                    }
+                }
+            }
+            tokio::time::sleep(RETRY_INTERVAL).await;
+        }
+    });
+
+    access_log::follow(move |connection| record(&app, connection));
+}
+
+fn from_routing(context: RoutingContext) -> Connection {
+    let source = match context.source_ip() {
+        Some(ip) => SocketAddr::new(ip, context.source_port as u16).to_string(),
+        None => String::new(),
+    };
+
+    Connection {
+        id: 0,
+        network: context.network_name().to_string(),
+        source,
+        domain: Some(context.target_domain.clone()).filter(|d| !d.is_empty()),
+        destination_ip: context.target_ip().map(|ip| ip.to_string()),
+        destination_port: u16::try_from(context.target_port).ok().filter(|p| *p != 0),
+        inbound_tag: context.inbound_tag,
+        outbound_tag: context.outbound_tag,
+        protocol: Some(context.protocol).filter(|p| !p.is_empty()),
+        rule: None,
+        rejected: false,
+        start: time::now_millis(),
+        origin: ConnectionOrigin::Routing,
+    }
+}
+
+/// 两个来源的记录是否为同一连接
+fn same_connection(a: &Connection, b: &Connection) -> bool {
+    a.source == b.source
+        && a.network == b.network
+        && a.destination_port == b.destination_port
+        && a.start.abs_diff(b.start) <= MERGE_WINDOW_MILLIS
+}
+
+/// 用另一来源的记录补全缺失的字段
+fn merge(existing: &mut Connection, other: Connection) {
+    if existing.domain.is_none() {
+        existing.domain = other.domain;
+    }
+    if existing.destination_ip.is_none() {
+        existing.destination_ip = other.destination_ip;
+    }
+    if existing.protocol.is_none() {
+        existing.protocol = other.protocol;
+    }
+    if existing.inbound_tag.is_empty() {
+        existing.inbound_tag = other.inbound_tag;
+    }
+    if existing.outbound_tag.is_empty() {
+        existing.outbound_tag = other.outbound_tag;
+    }
+    existing.rejected |= other.rejected;
+}
+
+fn record(app: &AppHandle, mut incoming: Connection) {
+    let now = time::now_millis();
+    let connection = {
+        let mut connections = CONNECTIONS.lock().unwrap();
+        connections.retain(|c| now.saturating_sub(c.start) < IDLE_MILLIS);
+
+        if incoming.source.is_empty() {
+            None
+        } else if let Some(existing) = connections
+            .iter_mut()
+            .rev()
+            .find(|c| same_connection(c, &incoming))
+        {
+            merge(existing, incoming);
+            existing.rule = rules::matched(existing);
+            Some(existing.clone())
+        } else {
+            incoming.id = NEXT_ID.fetch_add(1, Ordering::Relaxed);
+            incoming.rule = rules::matched(&incoming);
+            if connections.len() >= MAX_CONNECTIONS {
+                connections.pop_front();
+            }
+            connections.push_back(incoming.clone());
+            Some(incoming)
+        }
+    };
+
+    if let Some(connection) = connection {
+        let _ = app.emit(CONNECTION_EVENT, connection);
+    }
+}
+
+/// 最近的连接，最新的在前
+pub fn list() -> Vec<Connection> {
+    let now = time::now_millis();
+    CONNECTIONS
+        .lock()
+        .unwrap()
+        .iter()
+        .rev()
+        .filter(|c| now.saturating_sub(c.start) < IDLE_MILLIS)
+        .cloned()
+        .collect()
+}
+
+pub fn clear() {
+    CONNECTIONS.lock().unwrap().clear();
+}
@@ -0,0 +1,337 @@
+// 命中规则推断：xray 不在路由统计中报告规则，按 03_routing.json 的顺序
+// 找出第一条目标与实际出站一致、且条件可能满足的规则
+use crate::models::{Connection, MatchedRule, RoutingConfig, RoutingRule};
+use crate::utils::paths;
+use once_cell::sync::Lazy;
+use serde_json::Value;
+use std::fs;
+use std::net::IpAddr;
+use std::sync::Mutex;
+use std::time::SystemTime;
+
+const ROUTING_CONFIG: &str = "03_routing.json";
+
+// 按修改时间缓存的路由规则
+type CachedRules = Option<(SystemTime, Vec<RoutingRule>)>;
+static RULES: Lazy<Mutex<CachedRules>> = Lazy::new(|| Mutex::new(None));
+
+/// 条件的核对结果，按 No < Maybe < Yes 排序：多个条件同时满足取最小，列表中任一满足取最大
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum Check {
+    No,
+    /// geosite/geoip/regexp 等无法在本地判断的条件
+    Maybe,
+    Yes,
+}
+
+impl From<bool> for Check {
+    fn from(matches: bool) -> Self {
+        if matches { Check::Yes } else { Check::No }
+    }
+}
+
+fn load_rules() -> Vec<RoutingRule> {
+    let path = paths::get_confdir().join(ROUTING_CONFIG);
+    let Ok(modified) = fs::metadata(&path).and_then(|m| m.modified()) else {
+        return Vec::new();
+    };
+
+    let mut cached = RULES.lock().unwrap();
+    if let Some((at, rules)) = cached.as_ref()
+        && *at == modified
+    {
+        return rules.clone();
+    }
+
+    let rules = fs::read_to_string(&path)
+        .ok()
+        .and_then(|content| serde_json::from_str::<Value>(&content).ok())
+        .and_then(|wrapper| wrapper.get("routing").cloned())
+        .and_then(|routing| serde_json::from_value::<RoutingConfig>(routing).ok())
+        .map(|config| config.rules)
+        .unwrap_or_default();
+    *cached = Some((modified, rules.clone()));
+    rules
+}
+
+/// 推断连接命中的规则，如 "#3 geosite:google"；走默认出站时返回 None
+///
+/// 条件中含无法在本地判断的 geosite/geoip 等时，结果标记为不确定。
+pub fn matched(connection: &Connection) -> Option<MatchedRule> {
+    if connection.outbound_tag.is_empty() {
+        return None;
+    }
+    find(&load_rules(), connection)
+}
+
+fn find(rules: &[RoutingRule], connection: &Connection) -> Option<MatchedRule> {
+    rules.iter().enumerate().find_map(|(index, rule)| {
+        if !targets(rule, &connection.outbound_tag) {
+            return None;
+        }
+        let (check, condition) = conditions_match(rule, connection);
+        if check == Check::No {
+            return None;
+        }
+
+        let label = match condition {
+            Some(condition) => format!("#{} {}", index + 1, condition),
+            None => format!("#{}", index + 1),
+        };
+        Some(MatchedRule {
+            label,
+            certain: check == Check::Yes,
+        })
+    })
+}
+
+/// 规则的目标是否为该出站；节点组成员 "组@序号" 对应组的负载均衡器
+fn targets(rule: &RoutingRule, outbound_tag: &str) -> bool {
+    if rule.outbound_tag == outbound_tag {
+        return true;
+    }
+    match (rule.balancer_tag.as_deref(), outbound_tag.rsplit_once('@')) {
+        (Some(balancer), Some((group, _))) => balancer == group,
+        _ => false,
+    }
+}
+
+/// 各条件同时满足；无法在本地判断的 geosite/geoip/regexp 为 Maybe。
+/// 同时返回用于标注的条件：域名或 IP 列表中实际满足的一项优先，其次为端口与入站
+fn conditions_match<'a>(
+    rule: &'a RoutingRule,
+    connection: &Connection,
+) -> (Check, Option<&'a str>) {
+    let mut check = Check::Yes;
+    let mut label = None;
+    if !rule.domain.is_empty() {
+        let (matched, pattern) = match connection.domain.as_deref() {
+            Some(domain) => {
+                let domain = domain.to_lowercase();
+                best(&rule.domain, |p| domain_matches(p, &domain))
+            }
+            None => (Check::No, None),
+        };
+        check = check.min(matched);
+        label = label.or(pattern);
+    }
+    if !rule.ip.is_empty() {
+        let ip = connection
+            .destination_ip
+            .as_deref()
+            .and_then(|ip| ip.parse::<IpAddr>().ok());
+        let (matched, pattern) = match ip {
+            Some(ip) => best(&rule.ip, |p| ip_matches(p, ip)),
+            None => (Check::No, None),
+        };
+        check = check.min(matched);
+        label = label.or(pattern);
+    }
+    if let Some(spec) = rule.port.as_deref().filter(|p| !p.is_empty()) {
+        let port = connection.destination_port;
+        check = check.min(port.is_some_and(|port| port_matches(spec, port)).into());
+        label = label.or(Some(spec));
+    }
+    if !rule.inbound_tag.is_empty() {
+        let tag = rule
+            .inbound_tag
+            .iter()
+            .find(|tag| **tag == connection.inbound_tag);
+        check = check.min(tag.is_some().into());
+        label = label.or(tag.map(String::as_str));
+    }
+    (check, label)
+}
+
+/// 列表中任一条件满足即可，返回结果最好的第一项
+fn best(patterns: &[String], check: impl Fn(&str) -> Check) -> (Check, Option<&str>) {
+    patterns
+        .iter()
+        .map(|pattern| (check(pattern), pattern.as_str()))
+        .fold((Check::No, None), |best, (check, pattern)| {
+            if check > best.0 {
+                (check, Some(pattern))
+            } else {
+                best
+            }
+        })
+}
+
+/// 端口列表，如 "53,443,1000-2000"
+fn port_matches(spec: &str, port: u16) -> bool {
+    spec.split(',')
+        .map(str::trim)
+        .any(|part| match part.split_once('-') {
+            Some((from, to)) => match (from.trim().parse::<u16>(), to.trim().parse::<u16>()) {
+                (Ok(from), Ok(to)) => (from..=to).contains(&port),
+                _ => false,
+            },
+            None => part.parse() == Ok(port),
+        })
+}
+
+/// domain 已转为小写
+fn domain_matches(pattern: &str, domain: &str) -> Check {
+    if let Some(full) = pattern.strip_prefix("full:") {
+        (domain == full).into()
+    } else if let Some(suffix) = pattern.strip_prefix("domain:") {
+        (domain == suffix || domain.ends_with(&format!(".{}", suffix))).into()
+    } else if let Some(keyword) = pattern.strip_prefix("keyword:") {
+        domain.contains(keyword).into()
+    } else if ["geosite:", "ext:", "regexp:"]
+        .iter()
+        .any(|prefix| pattern.starts_with(prefix))
+    {
+        Check::Maybe
+    } else {
+        domain.contains(pattern).into()
+    }
+}
+
+fn ip_matches(pattern: &str, ip: IpAddr) -> Check {
+    if pattern == "geoip:private" {
+        return is_private(ip).into();
+    }
+    if pattern.starts_with("geoip:") || pattern.starts_with("ext:") {
+        return Check::Maybe;
+    }
+
+    let (network, bits) = match pattern.split_once('/') {
+        Some((network, bits)) => (network, bits.parse::<u32>().ok()),
+        None => (pattern, None),
+    };
+    let matches = match (network.parse::<IpAddr>(), ip) {
+        (Ok(IpAddr::V4(network)), IpAddr::V4(ip)) => {
+            prefix_matches(&network.octets(), &ip.octets(), bits.unwrap_or(32))
+        }
+        (Ok(IpAddr::V6(network)), IpAddr::V6(ip)) => {
+            prefix_matches(&network.octets(), &ip.octets(), bits.unwrap_or(128))
+        }
+        _ => false,
+    };
+    matches.into()
+}
+
+fn prefix_matches(network: &[u8], ip: &[u8], bits: u32) -> bool {
+    let bits = bits.min(network.len() as u32 * 8) as usize;
+    let (bytes, rest) = (bits / 8, bits % 8);
+    if network[..bytes] != ip[..bytes] {
+        return false;
+    }
+    if rest == 0 {
+        return true;
+    }
+    let mask = 0xffu8 << (8 - rest);
+    network[bytes] & mask == ip[bytes] & mask
+}
+
+fn is_private(ip: IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(ip) => ip.is_private() || ip.is_loopback() || ip.is_link_local(),
+        // fc00::/7 与 fe80::/10
+        IpAddr::V6(ip) => {
+            ip.is_loopback()
+                || (ip.segments()[0] & 0xfe00) == 0xfc00
+                || (ip.segments()[0] & 0xffc0) == 0xfe80
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::ConnectionOrigin;
+    use serde_json::json;
+
+    fn connection(domain: &str, ip: &str, outbound_tag: &str) -> Connection {
+        Connection {
+            id: 1,
+            network: "tcp".to_string(),
+            source: "127.0.0.1:52044".to_string(),
+            domain: Some(domain.to_string()),
+            destination_ip: Some(ip.to_string()),
+            destination_port: Some(443),
+            inbound_tag: "socks-in".to_string(),
+            outbound_tag: outbound_tag.to_string(),
+            protocol: None,
+            rule: None,
+            rejected: false,
+            start: 0,
+            origin: ConnectionOrigin::Routing,
+        }
+    }
+
+    fn matched_label(rules: &[RoutingRule], connection: &Connection) -> Option<(String, bool)> {
+        find(rules, connection).map(|m| (m.label, m.certain))
+    }
+
+    #[test]
+    fn marks_geo_rules_as_uncertain() {
+        let rules: Vec<RoutingRule> = serde_json::from_value(json!([
+            { "type": "field", "ip": ["geoip:private"], "outboundTag": "direct" },
+            { "type": "field", "domain": ["geosite:cn"], "outboundTag": "direct" },
+            { "type": "field", "domain": ["domain:example.com"], "outboundTag": "proxy" },
+            { "type": "field", "ip": ["geoip:us", "10.0.0.0/8"], "outboundTag": "proxy" },
+            { "type": "field", "port": "443", "outboundTag": "proxy" },
+        ]))
+        .unwrap();
+
+        assert_eq!(
+            matched_label(&rules, &connection("nas.lan", "192.168.1.2", "direct")),
+            Some(("#1 geoip:private".to_string(), true))
+        );
+        assert_eq!(
+            matched_label(&rules, &connection("baidu.com", "1.1.1.1", "direct")),
+            Some(("#2 geosite:cn".to_string(), false))
+        );
+        assert_eq!(
+            matched_label(&rules, &connection("www.Example.com", "8.8.8.8", "proxy")),
+            Some(("#3 domain:example.com".to_string(), true))
+        );
+        // 10.0.0.0/8 不满足，但 geoip:us 无法判断
+        assert_eq!(
+            matched_label(&rules, &connection("other.org", "8.8.8.8", "proxy")),
+            Some(("#4 geoip:us".to_string(), false))
+        );
+        // 以实际满足的 10.0.0.0/8 标注
+        assert_eq!(
+            matched_label(&rules, &connection("other.org", "10.1.2.3", "proxy")),
+            Some(("#4 10.0.0.0/8".to_string(), true))
+        );
+        assert_eq!(
+            matched_label(&rules, &connection("other.org", "8.8.8.8", "block")),
+            None
+        );
+    }
+
+    #[test]
+    fn labels_with_the_matching_condition() {
+        let rules: Vec<RoutingRule> = serde_json::from_value(json!([
+            {
+                "type": "field",
+                "domain": ["geosite:netflix", "domain:nflxvideo.net"],
+                "outboundTag": "media"
+            },
+            { "type": "field", "port": "53,443", "inboundTag": ["http-in", "socks-in"], "outboundTag": "proxy" },
+            { "type": "field", "inboundTag": ["http-in", "socks-in"], "outboundTag": "direct" },
+        ]))
+        .unwrap();
+
+        assert_eq!(
+            matched_label(&rules, &connection("a.nflxvideo.net", "8.8.8.8", "media")),
+            Some(("#1 domain:nflxvideo.net".to_string(), true))
+        );
+        assert_eq!(
+            matched_label(&rules, &connection("netflix.com", "8.8.8.8", "media")),
+            Some(("#1 geosite:netflix".to_string(), false))
+        );
+        assert_eq!(
+            matched_label(&rules, &connection("other.org", "8.8.8.8", "proxy")),
+            Some(("#2 53,443".to_string(), true))
+        );
+        assert_eq!(
+            matched_label(&rules, &connection("other.org", "8.8.8.8", "direct")),
+            Some(("#3 socks-in".to_string(), true))
+        );
+    }
+}
@@ -0,0 +1,261 @@
+// 自动故障切换：定期检查正在使用的节点，并以较低频率检查候选池；连续失败达到阈值时
+// 在候选池中选择延迟最低的可用节点，重启核心并通过事件通知前端
+pub mod settings;
+
+use crate::models::{CandidatePool, FailoverEvent, SwitchReason};
+use crate::services::latency::{self, batch, history};
+use crate::services::{proxylink, selection, xray};
+use crate::utils::paths;
+use std::path::Path;
+use std::sync::Arc;
+use std::sync::atomic::AtomicBool;
+use std::time::Duration;
+use tauri::{AppHandle, Emitter};
+
+/// 节点切换事件
+pub const SWITCH_EVENT: &str = "failover-switch";
+
+// 未启用或核心未运行时，重新检查设置的间隔
+const IDLE_INTERVAL: Duration = Duration::from_secs(10);
+const MIN_INTERVAL: Duration = Duration::from_secs(5);
+// 每检查当前节点若干次后测试一次候选池，使延迟记录反映候选节点的近况
+const POOL_CHECK_EVERY: u32 = 10;
+
+pub fn start(app: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        let mut failures = 0;
+        let mut checks: u32 = 0;
+        let mut checked_node: Option<String> = None;
+
+        loop {
+            let settings = settings::load();
+            let active = xray::current_node().filter(|_| settings.enabled);
+            let Some(active) = active else {
+                failures = 0;
+                tokio::time::sleep(IDLE_INTERVAL).await;
+                continue;
+            };
+
+            // 用户手动切换节点后重新计数
+            if checked_node.as_deref() != Some(active.as_str()) {
+                failures = 0;
+                checked_node = Some(active.clone());
+            }
+
+            let result = latency::test_node(&active, &latency::settings::load()).await;
+            if let Err(e) = history::record(vec![history::sample_of(&result)]) {
+                xray::output::log(format!("保存延迟记录失败: {}", e));
+            }
+
+            if result.success {
+                failures = 0;
+            } else {
+                failures += 1;
+                if failures >= settings.failure_threshold.max(1) {
+                    let reason = SwitchReason::ActiveNodeFailed {
+                        failures,
+                        last_error: result.error.map(|e| e.message).unwrap_or_default(),
+                    };
+                    if let Err(e) = switch_to_best(&app, &settings.pool, reason).await {
+                        xray::output::log(format!("自动切换节点失败: {}", e));
+                    }
+                    failures = 0;
+                }
+            }
+
+            checks = checks.wrapping_add(1);
+            if checks.is_multiple_of(POOL_CHECK_EVERY) {
+                check_pool(&settings.pool, &active).await;
+            }
+
+            let interval = Duration::from_secs(settings.interval_secs).max(MIN_INTERVAL);
+            tokio::time::sleep(interval).await;
+        }
+    });
+}
+
+/// 测试候选池中除当前节点外的节点并记录延迟，不切换节点
+async fn check_pool(pool: &CandidatePool, active: &str) {
+    let candidates: Vec<String> = candidates(pool, Some(active))
+        .into_iter()
+        .filter(|file| file != active)
+        .collect();
+    if candidates.is_empty() {
+        return;
+    }
+
+    let results = batch::test_all(
+        candidates,
+        latency::settings::load(),
+        Arc::new(AtomicBool::new(false)),
+        |_| {},
+    )
+    .await;
+    if let Err(e) = history::record(results.iter().map(history::sample_of).collect()) {
+        xray::output::log(format!("保存延迟记录失败: {}", e));
+    }
+}
+
+/// 测试候选池并切换到延迟最低的可用节点
+///
+/// 因故障切换时排除当前节点；核心未运行时只更新选中节点，不启动核心。
+pub async fn switch_to_best(
+    app: &AppHandle,
+    pool: &CandidatePool,
+    reason: SwitchReason,
+) -> Result<FailoverEvent, String> {
+    let active = xray::current_node().or_else(selection::get);
+    let exclude_active = matches!(reason, SwitchReason::ActiveNodeFailed { .. });
+
+    let candidates: Vec<String> = candidates(pool, active.as_deref())
+        .into_iter()
+        .filter(|file| !(exclude_active && active.as_ref() == Some(file)))
+        .collect();
+    if candidates.is_empty() {
+        return Err("候选池中没有节点".to_string());
+    }
+
+    let results = batch::test_all(
+        candidates,
+        latency::settings::load(),
+        Arc::new(AtomicBool::new(false)),
+        |_| {},
+    )
+    .await;
+    if let Err(e) = history::record(results.iter().map(history::sample_of).collect()) {
+        xray::output::log(format!("保存延迟记录失败: {}", e));
+    }
+
+    let best = results
+        .iter()
+        .filter(|r| r.success)
+        .min_by_key(|r| r.delay_ms.unwrap_or(u64::MAX));
+
+    let event = FailoverEvent {
+        from: active.clone(),
+        to: best.map(|r| r.node_file.clone()),
+        delay_ms: best.and_then(|r| r.delay_ms),
+        reason,
+    };
+
+    if let Some(best) = best
+        && active.as_ref() != Some(&best.node_file)
+    {
+        // 测试期间用户手动切换了节点时不覆盖用户的选择
+        if xray::current_node().or_else(selection::get) != active {
+            return Err("测试期间节点已被手动切换，取消自动切换".to_string());
+        }
+        if xray::current_node().is_some() {
+            // 启动核心会校验配置并等待旧进程退出，放到阻塞线程池中执行
+            let node_file = best.node_file.clone();
+            tauri::async_runtime::spawn_blocking(move || {
+                xray::start(&node_file).map_err(|e| e.to_string())
+            })
+            .await
+            .map_err(|e| e.to_string())??;
+        }
+        selection::set(&best.node_file).map_err(|e| e.to_string())?;
+    }
+
+    let _ = app.emit(SWITCH_EVENT, event.clone());
+    Ok(event)
+}
+
+/// 候选池中的节点文件
+fn candidates(pool: &CandidatePool, active: Option<&str>) -> Vec<String> {
+    select(
+        pool,
+        active,
+        proxylink::node_files(&paths::get_outbounds_dir()),
+    )
+}
+
+/// 从全部节点文件（相对 outbounds 目录）中选出候选池
+fn select(pool: &CandidatePool, active: Option<&str>, files: Vec<String>) -> Vec<String> {
+    match pool {
+        CandidatePool::SameGroup => {
+            let Some(active) = active else {
+                return Vec::new();
+            };
+            let group = Path::new(active).parent();
+            files
+                .into_iter()
+                .filter(|file| Path::new(file).parent() == group)
+                .collect()
+        }
+        CandidatePool::Subscription { name } => {
+            let dir = proxylink::subscription_dir(name);
+            let dir = dir.file_name().map(Path::new);
+            files
+                .into_iter()
+                .filter(|file| Path::new(file).parent() == dir)
+                .collect()
+        }
+        CandidatePool::Tag { tag } => {
+            let tag = tag.to_lowercase();
+            files
+                .into_iter()
+                .filter(|file| {
+                    Path::new(file)
+                        .file_stem()
+                        .is_some_and(|stem| stem.to_string_lossy().to_lowercase().contains(&tag))
+                })
+                .collect()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn files() -> Vec<String> {
+        [
+            "manual.json",
+            "HK 01.json",
+            "sub_a/HK 1.json",
+            "sub_a/JP 2.json",
+            "sub_b/hk-3.json",
+        ]
+        .map(String::from)
+        .to_vec()
+    }
+
+    #[test]
+    fn selects_same_group() {
+        let pool = CandidatePool::SameGroup;
+        assert_eq!(
+            select(&pool, Some("sub_a/JP 2.json"), files()),
+            ["sub_a/HK 1.json", "sub_a/JP 2.json"]
+        );
+        assert_eq!(
+            select(&pool, Some("manual.json"), files()),
+            ["manual.json", "HK 01.json"]
+        );
+        assert!(select(&pool, None, files()).is_empty());
+    }
+
+    #[test]
+    fn selects_subscription() {
+        let pool = |name: &str| CandidatePool::Subscription {
+            name: name.to_string(),
+        };
+        assert_eq!(
+            select(&pool("a"), None, files()),
+            ["sub_a/HK 1.json", "sub_a/JP 2.json"]
+        );
+        assert_eq!(select(&pool("b"), None, files()), ["sub_b/hk-3.json"]);
+        assert!(select(&pool("c"), None, files()).is_empty());
+    }
+
+    #[test]
+    fn selects_tag_case_insensitively() {
+        let pool = CandidatePool::Tag {
+            tag: "hk".to_string(),
+        };
+        assert_eq!(
+            select(&pool, None, files()),
+            ["HK 01.json", "sub_a/HK 1.json", "sub_b/hk-3.json"]
+        );
+    }
+}
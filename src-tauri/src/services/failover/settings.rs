@@ -0,0 +1,13 @@
+// 自动故障切换设置：保存在配置目录的 failover.json
+use crate::models::FailoverSettings;
+use crate::utils::settings;
+
+const FILE_NAME: &str = "failover.json";
+
+pub fn load() -> FailoverSettings {
+    settings::load(FILE_NAME)
+}
+
+pub fn save(value: &FailoverSettings) -> Result<(), Box<dyn std::error::Error>> {
+    settings::save(FILE_NAME, value)
+}
@@ -0,0 +1,379 @@
+// 节点组：将多个节点的出站以唯一 tag 合并到 confdir/07_groups.json，
+// 并在路由中生成对应的负载均衡器，leastPing/leastLoad 所需的 observatory 一并生成
+use crate::models::{BalancerStrategy, NodeGroup, NodeRename, SubscriptionDiff};
+use crate::services::{latency, proxylink};
+use crate::utils::{json, paths};
+use once_cell::sync::Lazy;
+use serde_json::{Value, json};
+use std::error::Error;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::Mutex;
+
+/// 生成的配置文件，排在 06_outbounds.json 之后
+const GROUPS_CONFIG: &str = "07_groups.json";
+const ROUTING_CONFIG: &str = "03_routing.json";
+
+// 内置出站标签，节点组不能占用
+const RESERVED_TAGS: &[&str] = &["proxy", "direct", "block", "dns-out", "api"];
+
+// 串行化读写
+static STORE_LOCK: Lazy<Mutex<()>> = Lazy::new(|| Mutex::new(()));
+
+fn store_path() -> PathBuf {
+    paths::get_config_dir().join("groups.json")
+}
+
+// 文件损坏时返回错误，修改操作因此不会用空列表覆盖原有内容
+fn read() -> Result<Vec<NodeGroup>, Box<dyn Error>> {
+    json::read_json(&store_path())
+}
+
+fn write(groups: &[NodeGroup]) -> Result<(), Box<dyn Error>> {
+    json::save_json(&store_path(), groups)
+}
+
+pub fn list() -> Vec<NodeGroup> {
+    let _guard = STORE_LOCK.lock().unwrap();
+    read().unwrap_or_default()
+}
+
+/// 新增或修改节点组（按 tag 匹配），并重新生成配置
+pub fn save(group: NodeGroup) -> Result<(), Box<dyn Error>> {
+    let tag = group.tag.trim();
+    if tag.is_empty() || tag.contains('@') {
+        return Err("节点组标签不能为空或包含 @".into());
+    }
+    if RESERVED_TAGS.contains(&tag) {
+        return Err(format!("标签 {} 为内置出站保留", tag).into());
+    }
+    if group.node_files.is_empty() {
+        return Err("节点组至少需要一个节点".into());
+    }
+    for file in &group.node_files {
+        proxylink::read_outbound(file).map_err(|e| format!("{}: {}", file, e))?;
+    }
+
+    let _guard = STORE_LOCK.lock().unwrap();
+    let mut groups = read()?;
+    let group = NodeGroup {
+        tag: tag.to_string(),
+        ..group
+    };
+    match groups.iter_mut().find(|g| g.tag == group.tag) {
+        Some(existing) => *existing = group,
+        None => groups.push(group),
+    }
+    write(&groups)?;
+    generate(&groups)
+}
+
+/// 删除节点组；仍被路由规则引用时拒绝删除，避免 xray 无法启动
+pub fn remove(tag: &str) -> Result<(), Box<dyn Error>> {
+    // 先加锁再检查引用，避免检查后路由被并发生成覆盖
+    let _guard = STORE_LOCK.lock().unwrap();
+    if is_referenced(&read_routing()?, tag) {
+        return Err(format!("节点组 {} 仍被路由规则引用", tag).into());
+    }
+
+    let mut groups = read()?;
+    groups.retain(|g| g.tag != tag);
+    write(&groups)?;
+    generate(&groups)
+}
+
+/// 订阅刷新后更新成员：改名的节点指向新文件，已删除的节点移出节点组；
+/// 成员全部被删除的节点组一并移除，引用它的路由规则改为直接使用回退出站
+pub fn sync_nodes(diff: &mut SubscriptionDiff) -> Result<(), Box<dyn Error>> {
+    let removed_groups = update_members(&diff.removed, &diff.renamed)?;
+    diff.removed_groups.extend(removed_groups);
+    Ok(())
+}
+
+/// 节点文件被删除后移出节点组，规则同 sync_nodes
+pub fn remove_nodes(files: &[String]) -> Result<(), Box<dyn Error>> {
+    update_members(files, &[]).map(|_| ())
+}
+
+/// 更新成员并重新生成配置，返回因成员清空而移除的节点组
+fn update_members(
+    removed: &[String],
+    renamed: &[NodeRename],
+) -> Result<Vec<String>, Box<dyn Error>> {
+    let _guard = STORE_LOCK.lock().unwrap();
+    let groups = read()?;
+    if groups.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let (groups, emptied) = rewrite_members(groups, removed, renamed);
+    if !emptied.is_empty() {
+        let mut routing = read_routing()?;
+        for group in &emptied {
+            redirect_rules(&mut routing, group);
+        }
+        write_routing(&routing)?;
+    }
+    write(&groups)?;
+
+    // 成员配置可能已变化，始终重新生成
+    generate(&groups)?;
+    Ok(emptied.into_iter().map(|g| g.tag).collect())
+}
+
+/// 移除和改名成员，返回 (仍有成员的节点组, 成员已清空的节点组)；
+/// 没有成员的负载均衡器无法选出出站
+fn rewrite_members(
+    mut groups: Vec<NodeGroup>,
+    removed: &[String],
+    renamed: &[NodeRename],
+) -> (Vec<NodeGroup>, Vec<NodeGroup>) {
+    for group in &mut groups {
+        group.node_files.retain(|file| !removed.contains(file));
+        for file in &mut group.node_files {
+            if let Some(rename) = renamed.iter().find(|r| &r.from == file) {
+                *file = rename.to.clone();
+            }
+        }
+    }
+    groups.into_iter().partition(|g| !g.node_files.is_empty())
+}
+
+/// 路由规则是否引用了该节点组的负载均衡器
+fn is_referenced(routing: &Value, tag: &str) -> bool {
+    routing
+        .pointer("/routing/rules")
+        .and_then(Value::as_array)
+        .is_some_and(|rules| {
+            rules
+                .iter()
+                .any(|r| r.get("balancerTag").and_then(Value::as_str) == Some(tag))
+        })
+}
+
+/// 将引用该节点组的路由规则改为回退出站，未设置回退时使用 proxy
+fn redirect_rules(routing: &mut Value, group: &NodeGroup) {
+    let target = group
+        .fallback_tag
+        .as_deref()
+        .filter(|t| !t.is_empty())
+        .unwrap_or("proxy");
+    let Some(rules) = routing
+        .pointer_mut("/routing/rules")
+        .and_then(Value::as_array_mut)
+    else {
+        return;
+    };
+
+    for rule in rules.iter_mut().filter_map(Value::as_object_mut) {
+        if rule.get("balancerTag").and_then(Value::as_str) == Some(group.tag.as_str()) {
+            rule.remove("balancerTag");
+            rule.insert("outboundTag".into(), json!(target));
+        }
+    }
+}
+
+/// 保存用户编辑的路由配置（{ "routing": {...} }），并保留节点组生成的负载均衡器；
+/// 与节点组修改共用锁，避免并发生成的负载均衡器被覆盖
+pub fn save_routing(mut routing: Value) -> Result<(), Box<dyn Error>> {
+    let _guard = STORE_LOCK.lock().unwrap();
+    set_balancers(&mut routing, &read()?);
+    write_routing(&routing)
+}
+
+fn set_balancers(routing: &mut Value, groups: &[NodeGroup]) {
+    let balancers: Vec<Value> = groups.iter().map(balancer).collect();
+    let Some(routing) = routing.get_mut("routing").and_then(Value::as_object_mut) else {
+        return;
+    };
+
+    if balancers.is_empty() {
+        routing.remove("balancers");
+    } else {
+        routing.insert("balancers".into(), Value::Array(balancers));
+    }
+}
+
+/// 成员出站的 tag 前缀，负载均衡器与 observatory 按前缀选择出站
+fn member_prefix(group: &NodeGroup) -> String {
+    format!("{}@", group.tag)
+}
+
+fn balancer(group: &NodeGroup) -> Value {
+    let mut balancer = json!({
+        "tag": group.tag,
+        "selector": [member_prefix(group)],
+        "strategy": { "type": group.strategy.as_str() }
+    });
+    if let Some(fallback) = group.fallback_tag.as_deref().filter(|t| !t.is_empty()) {
+        balancer["fallbackTag"] = json!(fallback);
+    }
+    balancer
+}
+
+/// 生成 07_groups.json 并更新路由中的负载均衡器
+fn generate(groups: &[NodeGroup]) -> Result<(), Box<dyn Error>> {
+    let config_path = paths::get_confdir().join(GROUPS_CONFIG);
+
+    let mut outbounds = Vec::new();
+    let mut ping_selectors = Vec::new();
+    let mut load_selectors = Vec::new();
+    for group in groups {
+        let prefix = member_prefix(group);
+        for (index, file) in group.node_files.iter().enumerate() {
+            // 读取失败的成员直接跳过，不影响其他节点
+            if let Ok(mut outbound) = proxylink::read_outbound(file) {
+                outbound["tag"] = json!(format!("{}{}", prefix, index + 1));
+                outbounds.push(outbound);
+            }
+        }
+        match group.strategy {
+            BalancerStrategy::LeastPing => ping_selectors.push(prefix),
+            BalancerStrategy::LeastLoad => load_selectors.push(prefix),
+            _ => {}
+        }
+    }
+
+    if groups.is_empty() {
+        if config_path.exists() {
+            fs::remove_file(&config_path)?;
+        }
+    } else {
+        let test_url = latency::settings::load().test_url;
+        let mut config = json!({ "outbounds": outbounds });
+        if !ping_selectors.is_empty() {
+            config["observatory"] = json!({
+                "subjectSelector": ping_selectors,
+                "probeUrl": test_url,
+                "probeInterval": "1m",
+                "enableConcurrency": true
+            });
+        }
+        if !load_selectors.is_empty() {
+            config["burstObservatory"] = json!({
+                "subjectSelector": load_selectors,
+                "pingConfig": {
+                    "destination": test_url,
+                    "interval": "1m",
+                    "sampling": 3,
+                    "timeout": "5s"
+                }
+            });
+        }
+        fs::write(&config_path, serde_json::to_string_pretty(&config)?)?;
+    }
+
+    // 负载均衡器必须位于 routing 中，写入 03_routing.json
+    let mut routing = read_routing()?;
+    set_balancers(&mut routing, groups);
+    write_routing(&routing)
+}
+
+fn read_routing() -> Result<Value, Box<dyn Error>> {
+    let content = fs::read_to_string(paths::get_confdir().join(ROUTING_CONFIG))?;
+    Ok(serde_json::from_str(&content)?)
+}
+
+fn write_routing(routing: &Value) -> Result<(), Box<dyn Error>> {
+    json::save_json(&paths::get_confdir().join(ROUTING_CONFIG), routing)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn group(tag: &str, fallback_tag: Option<&str>) -> NodeGroup {
+        NodeGroup {
+            tag: tag.to_string(),
+            node_files: Vec::new(),
+            strategy: BalancerStrategy::default(),
+            fallback_tag: fallback_tag.map(str::to_string),
+        }
+    }
+
+    #[test]
+    fn redirects_rules_of_removed_groups() {
+        let mut routing = json!({
+            "routing": {
+                "rules": [
+                    { "domain": ["geosite:netflix"], "balancerTag": "media" },
+                    { "domain": ["geosite:google"], "balancerTag": "auto" },
+                    { "ip": ["geoip:private"], "outboundTag": "direct" }
+                ]
+            }
+        });
+
+        assert!(is_referenced(&routing, "media"));
+        assert!(is_referenced(&routing, "auto"));
+        assert!(!is_referenced(&routing, "direct"));
+
+        redirect_rules(&mut routing, &group("media", Some("direct")));
+        redirect_rules(&mut routing, &group("auto", Some("")));
+
+        assert!(!is_referenced(&routing, "media"));
+        assert!(!is_referenced(&routing, "auto"));
+        assert_eq!(
+            routing["routing"]["rules"],
+            json!([
+                { "domain": ["geosite:netflix"], "outboundTag": "direct" },
+                { "domain": ["geosite:google"], "outboundTag": "proxy" },
+                { "ip": ["geoip:private"], "outboundTag": "direct" }
+            ])
+        );
+    }
+
+    #[test]
+    fn rewrites_members_and_splits_emptied_groups() {
+        let member = |tag: &str, files: &[&str]| NodeGroup {
+            node_files: files.iter().map(|f| f.to_string()).collect(),
+            ..group(tag, None)
+        };
+        let groups = vec![
+            member("auto", &["sub_a/A.json", "sub_a/B.json", "C.json"]),
+            member("media", &["sub_a/B.json"]),
+        ];
+        let renamed = [NodeRename {
+            from: "sub_a/A.json".into(),
+            to: "sub_a/A2.json".into(),
+        }];
+
+        let (kept, emptied) = rewrite_members(groups, &["sub_a/B.json".into()], &renamed);
+        assert_eq!(kept.len(), 1);
+        assert_eq!(kept[0].node_files, ["sub_a/A2.json", "C.json"]);
+        assert_eq!(emptied.len(), 1);
+        assert_eq!(emptied[0].tag, "media");
+    }
+
+    #[test]
+    fn sets_balancers_for_groups() {
+        let mut routing = json!({ "routing": { "rules": [], "balancers": [{ "tag": "old" }] } });
+        let groups = [
+            NodeGroup {
+                strategy: BalancerStrategy::LeastPing,
+                ..group("auto", Some("direct"))
+            },
+            group("media", None),
+        ];
+
+        set_balancers(&mut routing, &groups);
+        assert_eq!(
+            routing["routing"]["balancers"],
+            json!([
+                {
+                    "tag": "auto",
+                    "selector": ["auto@"],
+                    "strategy": { "type": BalancerStrategy::LeastPing.as_str() },
+                    "fallbackTag": "direct"
+                },
+                {
+                    "tag": "media",
+                    "selector": ["media@"],
+                    "strategy": { "type": BalancerStrategy::default().as_str() }
+                }
+            ])
+        );
+
+        set_balancers(&mut routing, &[]);
+        assert_eq!(routing, json!({ "routing": { "rules": [] } }));
+    }
+}
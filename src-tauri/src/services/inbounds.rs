@@ -0,0 +1,305 @@
+// 本地入站：以 confdir/01_inbounds.json 为唯一来源，读取与更新 socks/http/mixed 入站
+use crate::models::{InboundAuth, InboundSettings};
+use crate::services::ports;
+use crate::utils::{json, paths};
+use serde_json::{Value, json};
+use std::fs;
+use std::path::PathBuf;
+
+const INBOUNDS_CONFIG: &str = "01_inbounds.json";
+
+pub const SOCKS_TAG: &str = "socks-in";
+pub const HTTP_TAG: &str = "http-in";
+pub const MIXED_TAG: &str = "mixed-in";
+
+fn config_path() -> PathBuf {
+    paths::get_confdir().join(INBOUNDS_CONFIG)
+}
+
+/// 从 01_inbounds.json 读取入站设置，文件缺失或损坏时使用默认值
+pub fn load() -> InboundSettings {
+    configured()
+        .map(|inbounds| parse(&inbounds))
+        .unwrap_or_default()
+}
+
+/// 核心实际使用的入站设置：端口冲突后自动改用的端口覆盖 01_inbounds.json 中的同名入站
+pub fn load_effective() -> InboundSettings {
+    let Some(mut inbounds) = configured() else {
+        return InboundSettings::default();
+    };
+    for inbound in ports::override_inbounds() {
+        if let Some(existing) = inbounds
+            .iter_mut()
+            .find(|i| i.get("tag") == inbound.get("tag"))
+        {
+            *existing = inbound;
+        }
+    }
+    parse(&inbounds)
+}
+
+/// 01_inbounds.json 中的全部入站
+pub fn configured() -> Option<Vec<Value>> {
+    fs::read_to_string(config_path())
+        .ok()
+        .and_then(|content| serde_json::from_str::<Value>(&content).ok())
+        .and_then(|json| json.get("inbounds").and_then(Value::as_array).cloned())
+}
+
+fn parse(inbounds: &[Value]) -> InboundSettings {
+    let find = |tag: &str| {
+        inbounds
+            .iter()
+            .find(|i| i.get("tag").and_then(Value::as_str) == Some(tag))
+    };
+    let port = |inbound: Option<&Value>| {
+        inbound
+            .and_then(|i| i.get("port"))
+            .and_then(Value::as_u64)
+            .and_then(|p| u16::try_from(p).ok())
+    };
+
+    let socks = find(SOCKS_TAG);
+    let http = find(HTTP_TAG);
+    let mixed = find(MIXED_TAG);
+    let primary = mixed.or(socks).or(http);
+
+    // 认证与监听地址以第一个存在的入站为准
+    let auth = primary
+        .and_then(|i| i.pointer("/settings/accounts/0"))
+        .map(|account| InboundAuth {
+            user: account
+                .get("user")
+                .and_then(Value::as_str)
+                .unwrap_or_default()
+                .to_string(),
+            pass: account
+                .get("pass")
+                .and_then(Value::as_str)
+                .unwrap_or_default()
+                .to_string(),
+        });
+
+    InboundSettings {
+        // 未设置 listen 时按默认的本机地址显示，保存时写入明确的地址
+        listen: primary
+            .and_then(|i| i.get("listen"))
+            .and_then(Value::as_str)
+            .map(str::to_string)
+            .unwrap_or_else(|| InboundSettings::default().listen),
+        socks_port: port(socks),
+        http_port: port(http),
+        mixed_port: port(mixed),
+        udp: mixed
+            .or(socks)
+            .and_then(|i| i.pointer("/settings/udp"))
+            .and_then(Value::as_bool)
+            .unwrap_or(false),
+        sniffing: primary
+            .and_then(|i| i.pointer("/sniffing/enabled"))
+            .and_then(Value::as_bool)
+            .unwrap_or(false),
+        auth,
+    }
+}
+
+/// 校验并更新 01_inbounds.json 中由本模块管理的入站，其他入站与未识别的字段保持不变
+pub fn save(settings: &InboundSettings) -> Result<(), Box<dyn std::error::Error>> {
+    let ports: Vec<u16> = [settings.socks_port, settings.http_port, settings.mixed_port]
+        .into_iter()
+        .flatten()
+        .collect();
+    if ports.is_empty() {
+        return Err("至少需要启用一个入站端口".into());
+    }
+    if ports.contains(&0) {
+        return Err("端口不能为 0".into());
+    }
+    for (i, port) in ports.iter().enumerate() {
+        if ports[i + 1..].contains(port) {
+            return Err(format!("端口 {} 被重复使用", port).into());
+        }
+    }
+
+    let path = config_path();
+    let mut config: Value = json::read_json(&path)?;
+    if !config.is_object() {
+        config = json!({});
+    }
+    if !config["inbounds"].is_array() {
+        config["inbounds"] = json!([]);
+    }
+    if let Some(inbounds) = config["inbounds"].as_array_mut() {
+        merge(inbounds, settings);
+    }
+    json::save_json(&path, &config)
+}
+
+/// 按 tag 将生成的入站合并到已有列表：已有入站只覆盖生成的字段，
+/// 已停用端口对应的入站被移除，新启用的追加到末尾
+fn merge(inbounds: &mut Vec<Value>, settings: &InboundSettings) {
+    let generated = build(settings);
+    let tag_of = |inbound: &Value| {
+        inbound
+            .get("tag")
+            .and_then(Value::as_str)
+            .unwrap_or_default()
+            .to_string()
+    };
+
+    inbounds.retain(|inbound| {
+        let tag = tag_of(inbound);
+        ![SOCKS_TAG, HTTP_TAG, MIXED_TAG].contains(&tag.as_str())
+            || generated.iter().any(|g| tag_of(g) == tag)
+    });
+    for inbound in generated {
+        let tag = tag_of(&inbound);
+        match inbounds.iter_mut().find(|i| tag_of(i) == tag) {
+            Some(existing) => {
+                patch(existing, inbound);
+                // 关闭认证后移除旧账号，否则 http 入站仍会要求认证
+                if settings.auth.is_none()
+                    && let Some(s) = existing.get_mut("settings").and_then(Value::as_object_mut)
+                {
+                    s.remove("accounts");
+                }
+            }
+            None => inbounds.push(inbound),
+        }
+    }
+}
+
+/// 用生成的字段覆盖已有入站，settings、sniffing 等对象只覆盖其中的同名字段
+fn patch(existing: &mut Value, generated: Value) {
+    match (existing, generated) {
+        (Value::Object(old), Value::Object(new)) => {
+            for (key, value) in new {
+                match (old.get_mut(&key), value) {
+                    (Some(Value::Object(old)), Value::Object(new)) => old.extend(new),
+                    (_, value) => {
+                        old.insert(key, value);
+                    }
+                }
+            }
+        }
+        (existing, generated) => *existing = generated,
+    }
+}
+
+/// 生成入站配置
+pub fn build(settings: &InboundSettings) -> Vec<Value> {
+    let mut inbounds = Vec::new();
+    if let Some(port) = settings.socks_port {
+        inbounds.push(inbound(settings, SOCKS_TAG, "socks", port));
+    }
+    if let Some(port) = settings.http_port {
+        inbounds.push(inbound(settings, HTTP_TAG, "http", port));
+    }
+    // Xray 的 socks 入站同时接受 HTTP 代理请求，混合端口即 socks 入站
+    if let Some(port) = settings.mixed_port {
+        inbounds.push(inbound(settings, MIXED_TAG, "socks", port));
+    }
+    inbounds
+}
+
+fn inbound(settings: &InboundSettings, tag: &str, protocol: &str, port: u16) -> Value {
+    let accounts: Vec<Value> = settings
+        .auth
+        .iter()
+        .map(|auth| json!({ "user": auth.user, "pass": auth.pass }))
+        .collect();
+
+    let inbound_settings = if protocol == "socks" {
+        let mut s = json!({
+            "auth": if accounts.is_empty() { "noauth" } else { "password" },
+            "udp": settings.udp
+        });
+        if !accounts.is_empty() {
+            s["accounts"] = json!(accounts);
+        }
+        s
+    } else if accounts.is_empty() {
+        json!({})
+    } else {
+        json!({ "accounts": accounts })
+    };
+
+    json!({
+        "tag": tag,
+        "listen": settings.listen,
+        "port": port,
+        "protocol": protocol,
+        "settings": inbound_settings,
+        "sniffing": {
+            "enabled": settings.sniffing,
+            "destOverride": ["http", "tls"]
+        }
+    })
+}
+
+/// 系统代理与状态显示使用的端口
+pub fn proxy_port() -> u16 {
+    load_effective()
+        .system_proxy_port()
+        .or(InboundSettings::default().system_proxy_port())
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn patches_managed_inbounds_in_place() {
+        let mut inbounds = vec![
+            json!({
+                "tag": SOCKS_TAG,
+                "listen": "0.0.0.0",
+                "port": 1080,
+                "protocol": "socks",
+                "settings": {
+                    "auth": "password",
+                    "accounts": [{ "user": "u", "pass": "p" }],
+                    "udp": true,
+                    "ip": "127.0.0.1"
+                },
+                "sniffing": { "enabled": true, "routeOnly": true }
+            }),
+            json!({ "tag": HTTP_TAG, "port": 1081, "protocol": "http" }),
+            json!({ "tag": "dokodemo-in", "port": 5353, "protocol": "dokodemo-door" }),
+        ];
+        let settings = InboundSettings {
+            socks_port: Some(2080),
+            http_port: None,
+            mixed_port: Some(2082),
+            auth: None,
+            ..Default::default()
+        };
+
+        merge(&mut inbounds, &settings);
+        let tags: Vec<_> = inbounds.iter().map(|i| i["tag"].clone()).collect();
+        assert_eq!(
+            tags,
+            [json!(SOCKS_TAG), json!("dokodemo-in"), json!(MIXED_TAG)]
+        );
+
+        let socks = &inbounds[0];
+        assert_eq!(socks["port"], 2080);
+        assert_eq!(socks["listen"], "127.0.0.1");
+        assert_eq!(socks["settings"]["auth"], "noauth");
+        assert_eq!(socks["settings"]["ip"], "127.0.0.1");
+        assert!(socks["settings"].get("accounts").is_none());
+        assert_eq!(socks["sniffing"]["routeOnly"], true);
+        assert_eq!(socks["sniffing"]["destOverride"], json!(["http", "tls"]));
+        assert_eq!(inbounds[1]["port"], 5353);
+    }
+
+    #[test]
+    fn parses_missing_listen_as_default() {
+        let settings = parse(&[json!({ "tag": MIXED_TAG, "port": 7890, "protocol": "socks" })]);
+        assert_eq!(settings.listen, InboundSettings::default().listen);
+        assert_eq!(settings.mixed_port, Some(7890));
+        assert_eq!(settings.socks_port, None);
+    }
+}
@@ -0,0 +1,133 @@
+// IP 信息：分别直连和经本地入站代理查询出口 IP 与地理位置，
+// 按设置的顺序回退多个查询服务，结果缓存到正在使用的节点变化为止；
+// 有查询失败时只短时间缓存，避免网络恢复后仍显示错误
+mod providers;
+pub mod settings;
+
+use crate::models::{IpInfo, IpLookup, IpProvider};
+use crate::services::{inbounds, xray};
+use serde_json::Value;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// 含失败结果的缓存有效期
+const ERROR_TTL: Duration = Duration::from_secs(30);
+
+static CACHE: Mutex<Option<(IpInfo, Instant)>> = Mutex::new(None);
+
+/// 查询 IP 信息；refresh 为 false 时，节点未变化则返回缓存
+pub async fn get(refresh: bool) -> IpInfo {
+    let node = xray::current_node();
+    if !refresh {
+        let cached = CACHE.lock().unwrap().clone();
+        if let Some((info, _)) = cached.filter(|(info, at)| {
+            info.node == node
+                && ((info.direct_error.is_none() && info.proxy_error.is_none())
+                    || at.elapsed() < ERROR_TTL)
+        }) {
+            return info;
+        }
+    }
+
+    let settings = settings::load();
+    let timeout = Duration::from_millis(settings.timeout_ms);
+
+    let direct = lookup(None, &settings.providers, timeout).await;
+    // 核心未运行时本地入站不可用
+    let proxy = match (&node, local_proxy()) {
+        (Some(_), Some(proxy)) => Some(lookup(Some(proxy), &settings.providers, timeout).await),
+        (Some(_), None) => Some(Err("未启用 SOCKS/HTTP 入站".to_string())),
+        (None, _) => None,
+    };
+
+    let (proxy, proxy_error) = match proxy {
+        Some(Ok(lookup)) => (Some(lookup), None),
+        Some(Err(e)) => (None, Some(e)),
+        None => (None, None),
+    };
+    let (direct, direct_error) = match direct {
+        Ok(lookup) => (Some(lookup), None),
+        Err(e) => (None, Some(e)),
+    };
+
+    let info = IpInfo {
+        external_ip: proxy.as_ref().or(direct.as_ref()).map(|l| l.ip.clone()),
+        internal_ip: local_ip_address::local_ip().ok().map(|ip| ip.to_string()),
+        direct,
+        proxy,
+        direct_error,
+        proxy_error,
+        node,
+    };
+    *CACHE.lock().unwrap() = Some((info.clone(), Instant::now()));
+    info
+}
+
+/// 清除缓存，下次查询重新请求
+pub fn invalidate() {
+    *CACHE.lock().unwrap() = None;
+}
+
+/// 本地入站对应的代理，优先 SOCKS（混合端口同样接受 SOCKS）
+fn local_proxy() -> Option<reqwest::Proxy> {
+    let settings = inbounds::load_effective();
+    let host = match settings.listen.as_str() {
+        "" | "0.0.0.0" => "127.0.0.1".to_string(),
+        "::" => "[::1]".to_string(),
+        host if host.contains(':') => format!("[{}]", host),
+        host => host.to_string(),
+    };
+    let url = match settings.mixed_port.or(settings.socks_port) {
+        Some(port) => format!("socks5h://{}:{}", host, port),
+        None => format!("http://{}:{}", host, settings.http_port?),
+    };
+
+    let proxy = reqwest::Proxy::all(url).ok()?;
+    Some(match settings.auth {
+        Some(auth) => proxy.basic_auth(&auth.user, &auth.pass),
+        None => proxy,
+    })
+}
+
+/// 依次尝试各查询服务，全部失败时返回每个服务的错误
+async fn lookup(
+    proxy: Option<reqwest::Proxy>,
+    providers: &[IpProvider],
+    timeout: Duration,
+) -> Result<IpLookup, String> {
+    let builder = reqwest::Client::builder().timeout(timeout);
+    // 直连时不使用环境变量中的代理
+    let builder = match proxy {
+        Some(proxy) => builder.proxy(proxy),
+        None => builder.no_proxy(),
+    };
+    let client = builder.build().map_err(|e| e.to_string())?;
+
+    let mut errors = Vec::new();
+    for &provider in providers {
+        match query(&client, provider).await {
+            Ok(mut lookup) => {
+                lookup.provider = Some(provider);
+                return Ok(lookup);
+            }
+            Err(e) => errors.push(format!("{}: {}", provider.as_str(), e)),
+        }
+    }
+
+    if errors.is_empty() {
+        Err("未配置 IP 查询服务".to_string())
+    } else {
+        Err(errors.join("; "))
+    }
+}
+
+async fn query(client: &reqwest::Client, provider: IpProvider) -> Result<IpLookup, String> {
+    let response = client
+        .get(providers::url(provider))
+        .send()
+        .await
+        .and_then(|r| r.error_for_status())
+        .map_err(|e| e.to_string())?;
+    let json: Value = response.json().await.map_err(|e| e.to_string())?;
+    providers::parse(provider, &json)
+}
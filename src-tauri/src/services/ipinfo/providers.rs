@@ -0,0 +1,291 @@
+// IP 查询服务：各服务的地址与响应解析
+use crate::models::{IpLookup, IpProvider};
+use serde_json::Value;
+
+pub fn url(provider: IpProvider) -> &'static str {
+    match provider {
+        // 免费接口仅支持 http
+        IpProvider::IpApi => {
+            "http://ip-api.com/json/?fields=status,message,query,country,countryCode,regionName,city,isp,org,as"
+        }
+        IpProvider::IpInfo => "https://ipinfo.io/json",
+        IpProvider::IpApiCo => "https://ipapi.co/json/",
+        IpProvider::IpSb => "https://api.ip.sb/geoip",
+        IpProvider::Ipify => "https://api.ipify.org?format=json",
+    }
+}
+
+/// 读取非空字符串字段，数字会被转换为字符串
+fn field(value: &Value, key: &str) -> Option<String> {
+    match value.get(key) {
+        Some(Value::String(s)) if !s.trim().is_empty() => Some(s.trim().to_string()),
+        Some(Value::Number(n)) => Some(n.to_string()),
+        _ => None,
+    }
+}
+
+/// 读取 IP 字段，缺失时整个响应无效
+fn ip(value: &Value, key: &str) -> Result<String, String> {
+    field(value, key).ok_or_else(|| "响应中没有 IP 地址".to_string())
+}
+
+/// 拆分 "AS15169 Google LLC" 形式的组织信息
+fn split_org(org: Option<String>) -> (Option<String>, Option<String>) {
+    let Some(org) = org else {
+        return (None, None);
+    };
+    match org.split_once(' ') {
+        Some((asn, name)) if asn.starts_with("AS") => {
+            (Some(asn.to_string()), Some(name.trim().to_string()))
+        }
+        _ if org.starts_with("AS") => (Some(org), None),
+        _ => (None, Some(org)),
+    }
+}
+
+pub fn parse(provider: IpProvider, value: &Value) -> Result<IpLookup, String> {
+    let lookup = match provider {
+        IpProvider::IpApi => {
+            if field(value, "status").as_deref() != Some("success") {
+                return Err(field(value, "message").unwrap_or_else(|| "查询失败".to_string()));
+            }
+            let (asn, as_name) = split_org(field(value, "as"));
+            IpLookup {
+                ip: ip(value, "query")?,
+                country: field(value, "country"),
+                country_code: field(value, "countryCode"),
+                region: field(value, "regionName"),
+                city: field(value, "city"),
+                asn,
+                isp: field(value, "isp").or(field(value, "org")).or(as_name),
+                provider: None,
+            }
+        }
+        IpProvider::IpInfo => {
+            if value.get("bogon").and_then(Value::as_bool) == Some(true) {
+                return Err("保留地址".to_string());
+            }
+            let (asn, isp) = split_org(field(value, "org"));
+            IpLookup {
+                ip: ip(value, "ip")?,
+                // ipinfo 的 country 字段为国家代码
+                country: None,
+                country_code: field(value, "country"),
+                region: field(value, "region"),
+                city: field(value, "city"),
+                asn,
+                isp,
+                provider: None,
+            }
+        }
+        IpProvider::IpApiCo => {
+            if value.get("error").and_then(Value::as_bool) == Some(true) {
+                return Err(field(value, "reason").unwrap_or_else(|| "查询失败".to_string()));
+            }
+            IpLookup {
+                ip: ip(value, "ip")?,
+                country: field(value, "country_name"),
+                country_code: field(value, "country_code"),
+                region: field(value, "region"),
+                city: field(value, "city"),
+                asn: field(value, "asn"),
+                isp: field(value, "org"),
+                provider: None,
+            }
+        }
+        IpProvider::IpSb => IpLookup {
+            ip: ip(value, "ip")?,
+            country: field(value, "country"),
+            country_code: field(value, "country_code"),
+            region: field(value, "region"),
+            city: field(value, "city"),
+            // ip.sb 的 asn 为数字
+            asn: field(value, "asn").map(|asn| format!("AS{}", asn)),
+            isp: field(value, "isp")
+                .or(field(value, "asn_organization"))
+                .or(field(value, "organization")),
+            provider: None,
+        },
+        IpProvider::Ipify => IpLookup {
+            ip: ip(value, "ip")?,
+            ..Default::default()
+        },
+    };
+    Ok(lookup)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    /// (ip, 国家, 国家代码, 地区, 城市, asn, isp)
+    fn fields(lookup: &IpLookup) -> [Option<&str>; 7] {
+        [
+            Some(lookup.ip.as_str()),
+            lookup.country.as_deref(),
+            lookup.country_code.as_deref(),
+            lookup.region.as_deref(),
+            lookup.city.as_deref(),
+            lookup.asn.as_deref(),
+            lookup.isp.as_deref(),
+        ]
+    }
+
+    #[test]
+    fn parses_ip_api() {
+        let value = json!({
+            "status": "success",
+            "country": "United States",
+            "countryCode": "US",
+            "regionName": "California",
+            "city": "Mountain View",
+            "isp": "Google LLC",
+            "org": "Google Public DNS",
+            "as": "AS15169 Google LLC",
+            "query": "8.8.8.8"
+        });
+        let lookup = parse(IpProvider::IpApi, &value).unwrap();
+        assert_eq!(
+            fields(&lookup),
+            [
+                Some("8.8.8.8"),
+                Some("United States"),
+                Some("US"),
+                Some("California"),
+                Some("Mountain View"),
+                Some("AS15169"),
+                Some("Google LLC"),
+            ]
+        );
+
+        let failed = json!({ "status": "fail", "message": "private range", "query": "10.0.0.1" });
+        assert_eq!(
+            parse(IpProvider::IpApi, &failed).unwrap_err(),
+            "private range"
+        );
+    }
+
+    #[test]
+    fn parses_ipinfo() {
+        let value = json!({
+            "ip": "1.1.1.1",
+            "hostname": "one.one.one.one",
+            "city": "Brisbane",
+            "region": "Queensland",
+            "country": "AU",
+            "loc": "-27.4816,153.0175",
+            "org": "AS13335 Cloudflare, Inc.",
+            "postal": "4101",
+            "timezone": "Australia/Brisbane",
+            "anycast": true
+        });
+        let lookup = parse(IpProvider::IpInfo, &value).unwrap();
+        assert_eq!(
+            fields(&lookup),
+            [
+                Some("1.1.1.1"),
+                None,
+                Some("AU"),
+                Some("Queensland"),
+                Some("Brisbane"),
+                Some("AS13335"),
+                Some("Cloudflare, Inc."),
+            ]
+        );
+
+        let bogon = json!({ "ip": "192.168.1.1", "bogon": true });
+        assert!(parse(IpProvider::IpInfo, &bogon).is_err());
+    }
+
+    #[test]
+    fn parses_ipapi_co() {
+        let value = json!({
+            "ip": "2606:4700:4700::1111",
+            "network": "2606:4700:4700::/48",
+            "version": "IPv6",
+            "city": "San Francisco",
+            "region": "California",
+            "region_code": "CA",
+            "country": "US",
+            "country_name": "United States",
+            "country_code": "US",
+            "asn": "AS13335",
+            "org": "CLOUDFLARENET"
+        });
+        let lookup = parse(IpProvider::IpApiCo, &value).unwrap();
+        assert_eq!(
+            fields(&lookup),
+            [
+                Some("2606:4700:4700::1111"),
+                Some("United States"),
+                Some("US"),
+                Some("California"),
+                Some("San Francisco"),
+                Some("AS13335"),
+                Some("CLOUDFLARENET"),
+            ]
+        );
+
+        let limited = json!({ "error": true, "reason": "RateLimited", "message": "..." });
+        assert_eq!(
+            parse(IpProvider::IpApiCo, &limited).unwrap_err(),
+            "RateLimited"
+        );
+    }
+
+    #[test]
+    fn parses_ip_sb() {
+        let value = json!({
+            "organization": "Cloudflare",
+            "longitude": -97.822,
+            "timezone": "America/Chicago",
+            "isp": "Cloudflare",
+            "offset": -18000,
+            "asn": 13335,
+            "asn_organization": "CLOUDFLARENET",
+            "country": "United States",
+            "ip": "104.16.0.1",
+            "latitude": 37.751,
+            "continent_code": "NA",
+            "country_code": "US"
+        });
+        let lookup = parse(IpProvider::IpSb, &value).unwrap();
+        assert_eq!(
+            fields(&lookup),
+            [
+                Some("104.16.0.1"),
+                Some("United States"),
+                Some("US"),
+                None,
+                None,
+                Some("AS13335"),
+                Some("Cloudflare"),
+            ]
+        );
+    }
+
+    #[test]
+    fn parses_ipify() {
+        let lookup = parse(IpProvider::Ipify, &json!({ "ip": "203.0.113.7" })).unwrap();
+        assert_eq!(
+            fields(&lookup),
+            [Some("203.0.113.7"), None, None, None, None, None, None]
+        );
+    }
+
+    #[test]
+    fn rejects_responses_without_ip() {
+        let providers = [
+            IpProvider::IpApi,
+            IpProvider::IpInfo,
+            IpProvider::IpApiCo,
+            IpProvider::IpSb,
+            IpProvider::Ipify,
+        ];
+        for provider in providers {
+            let value = json!({ "status": "success", "ip": " ", "country": "US" });
+            assert!(parse(provider, &value).is_err(), "{:?}", provider);
+        }
+    }
+}
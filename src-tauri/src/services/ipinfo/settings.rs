@@ -0,0 +1,13 @@
+// IP 查询设置：保存在配置目录的 ip_check.json
+use crate::models::IpCheckSettings;
+use crate::utils::settings;
+
+const FILE_NAME: &str = "ip_check.json";
+
+pub fn load() -> IpCheckSettings {
+    settings::load(FILE_NAME)
+}
+
+pub fn save(value: &IpCheckSettings) -> Result<(), Box<dyn std::error::Error>> {
+    settings::save(FILE_NAME, value)
+}
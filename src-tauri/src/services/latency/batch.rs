@@ -0,0 +1,257 @@
+// 批量延迟测试：所有节点共用一个临时实例，限制并发数，逐个节点完成时通过事件推送结果，支持取消
+use super::{TempCore, history, into_result, measure_via, read_outbound, test_node};
+use crate::models::{
+    LatencyBatchSummary, LatencyError, LatencyProgress, LatencyResult, LatencySettings,
+};
+use crate::services::{proxylink, xray};
+use serde_json::Value;
+use std::sync::atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use tauri::{AppHandle, Emitter};
+
+/// 单个节点测试完成事件
+pub const PROGRESS_EVENT: &str = "latency-progress";
+/// 批量测试结束事件
+pub const FINISHED_EVENT: &str = "latency-finished";
+
+static NEXT_BATCH_ID: AtomicU64 = AtomicU64::new(1);
+// 正在进行的批次，同一时间只运行一个批次
+static CURRENT_BATCH: Mutex<Option<Batch>> = Mutex::new(None);
+
+struct Batch {
+    id: u64,
+    cancelled: Arc<AtomicBool>,
+}
+
+/// 批量测试节点，新批次开始时会取消尚未结束的旧批次
+pub async fn run(
+    app: AppHandle,
+    node_files: Vec<String>,
+    settings: LatencySettings,
+) -> LatencyBatchSummary {
+    let batch_id = NEXT_BATCH_ID.fetch_add(1, Ordering::Relaxed);
+    let cancelled = Arc::new(AtomicBool::new(false));
+    let previous = CURRENT_BATCH.lock().unwrap().replace(Batch {
+        id: batch_id,
+        cancelled: cancelled.clone(),
+    });
+    if let Some(previous) = previous {
+        previous.cancelled.store(true, Ordering::Relaxed);
+    }
+
+    let total = node_files.len();
+    let completed = AtomicUsize::new(0);
+    let emitter = app.clone();
+    let results = test_all(node_files, settings, cancelled.clone(), move |result| {
+        let done = completed.fetch_add(1, Ordering::Relaxed) + 1;
+        let _ = emitter.emit(
+            PROGRESS_EVENT,
+            LatencyProgress {
+                batch_id,
+                completed: done,
+                total,
+                result: result.clone(),
+            },
+        );
+    })
+    .await;
+
+    // 仅清除自己的批次，避免覆盖已开始的新批次
+    let mut current = CURRENT_BATCH.lock().unwrap();
+    if current.as_ref().is_some_and(|b| b.id == batch_id) {
+        *current = None;
+    }
+    drop(current);
+
+    if let Err(e) = history::record(results.iter().map(history::sample_of).collect()) {
+        xray::output::log(format!("保存延迟记录失败: {}", e));
+    }
+
+    let summary = LatencyBatchSummary {
+        batch_id,
+        total,
+        completed: results.len(),
+        cancelled: cancelled.load(Ordering::Relaxed),
+        results,
+    };
+    let _ = app.emit(FINISHED_EVENT, summary.clone());
+    summary
+}
+
+/// 节点的测试方式
+enum Route {
+    /// 节点文件无法读取
+    Invalid(LatencyError),
+    /// 经共用实例的第 n 个入站测试
+    Shared(u16),
+    /// 单独启动实例测试
+    Separate,
+}
+
+/// 以 settings.concurrency 的并发数测试节点，每个节点完成时调用 on_result，
+/// cancelled 置位后不再开始新的测试。结果按 node_files 的顺序返回，不含未开始测试的节点
+pub async fn test_all<F>(
+    node_files: Vec<String>,
+    settings: LatencySettings,
+    cancelled: Arc<AtomicBool>,
+    on_result: F,
+) -> Vec<LatencyResult>
+where
+    F: Fn(&LatencyResult) + Send + Sync + 'static,
+{
+    let total = node_files.len();
+    let outbounds: Vec<_> = node_files.iter().map(|f| read_outbound(f)).collect();
+
+    // 全部有效节点共用一个临时实例；实例无法启动时（如某个出站不被 xray 接受）逐个单独启动
+    let valid: Vec<Value> = outbounds.iter().flatten().cloned().collect();
+    let core = if valid.is_empty() || cancelled.load(Ordering::Relaxed) {
+        None
+    } else {
+        TempCore::start(&valid).await.ok()
+    };
+    let mut ports = core.iter().flat_map(|core| core.ports.iter().copied());
+    let routes: Vec<Route> = outbounds
+        .into_iter()
+        .map(|outbound| match outbound {
+            Err(e) => Route::Invalid(e),
+            Ok(_) => ports.next().map_or(Route::Separate, Route::Shared),
+        })
+        .collect();
+
+    let node_files = Arc::new(node_files);
+    let routes = Arc::new(routes);
+    let settings = Arc::new(settings);
+    let on_result = Arc::new(on_result);
+    let next = Arc::new(AtomicUsize::new(0));
+    let results = Arc::new(Mutex::new(vec![None; total]));
+
+    // 固定数量的工作任务依次领取节点
+    let workers: Vec<_> = (0..settings.concurrency.clamp(1, total.max(1)))
+        .map(|_| {
+            let node_files = node_files.clone();
+            let routes = routes.clone();
+            let settings = settings.clone();
+            let on_result = on_result.clone();
+            let next = next.clone();
+            let results = results.clone();
+            let cancelled = cancelled.clone();
+
+            tauri::async_runtime::spawn(async move {
+                while !cancelled.load(Ordering::Relaxed) {
+                    let index = next.fetch_add(1, Ordering::Relaxed);
+                    let Some(node_file) = node_files.get(index) else {
+                        break;
+                    };
+
+                    let result = match &routes[index] {
+                        Route::Invalid(e) => into_result(node_file, Err(e.clone())),
+                        Route::Shared(port) => {
+                            into_result(node_file, measure_via(*port, &settings).await)
+                        }
+                        Route::Separate => test_node(node_file, &settings).await,
+                    };
+                    on_result(&result);
+                    results.lock().unwrap()[index] = Some(result);
+                }
+            })
+        })
+        .collect();
+
+    for worker in workers {
+        let _ = worker.await;
+    }
+    drop(core);
+
+    std::mem::take(&mut *results.lock().unwrap())
+        .into_iter()
+        .flatten()
+        .collect()
+}
+
+/// 取消正在进行的批量测试，已在测试中的节点会在超时前结束
+pub fn cancel() -> bool {
+    match CURRENT_BATCH.lock().unwrap().as_ref() {
+        Some(batch) => {
+            batch.cancelled.store(true, Ordering::Relaxed);
+            true
+        }
+        None => false,
+    }
+}
+
+/// 订阅目录中的全部节点文件，路径相对于 outbounds 目录
+pub fn subscription_node_files(sub_name: &str) -> Vec<String> {
+    proxylink::node_files(&proxylink::subscription_dir(sub_name))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::LatencyErrorKind;
+
+    fn missing(names: &[&str]) -> Vec<String> {
+        names
+            .iter()
+            .map(|name| format!("missing-latency-node-{}.json", name))
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn returns_results_in_input_order() {
+        let node_files = missing(&["c", "a", "b"]);
+        let settings = LatencySettings {
+            concurrency: 3,
+            ..Default::default()
+        };
+
+        let results = test_all(
+            node_files.clone(),
+            settings,
+            Arc::new(AtomicBool::new(false)),
+            |_| {},
+        )
+        .await;
+        let files: Vec<_> = results.iter().map(|r| r.node_file.clone()).collect();
+        assert_eq!(files, node_files);
+        assert!(
+            results.iter().all(|r| {
+                r.error.as_ref().map(|e| e.kind) == Some(LatencyErrorKind::InvalidNode)
+            })
+        );
+    }
+
+    #[tokio::test]
+    async fn stops_after_cancellation() {
+        let settings = LatencySettings {
+            concurrency: 1,
+            ..Default::default()
+        };
+
+        let cancelled = Arc::new(AtomicBool::new(false));
+        let flag = cancelled.clone();
+        let seen = Arc::new(AtomicUsize::new(0));
+        let counter = seen.clone();
+        let results = test_all(
+            missing(&["a", "b", "c"]),
+            settings.clone(),
+            cancelled,
+            move |_| {
+                counter.fetch_add(1, Ordering::Relaxed);
+                flag.store(true, Ordering::Relaxed);
+            },
+        )
+        .await;
+        assert_eq!(results.len(), 1);
+        assert_eq!(seen.load(Ordering::Relaxed), 1);
+
+        // 开始前已取消时不测试任何节点
+        let results = test_all(
+            missing(&["a"]),
+            settings,
+            Arc::new(AtomicBool::new(true)),
+            |_| panic!("不应测试节点"),
+        )
+        .await;
+        assert!(results.is_empty());
+    }
+}
@@ -0,0 +1,339 @@
+// 延迟历史：按节点文件保存每次测试结果（状态目录 latency_history.json），
+// 并在滑动窗口内统计中位数、抖动、丢包率与趋势
+use crate::models::{
+    LatencyMethod, LatencyResult, LatencySample, LatencyStats, LatencyTrend, NodeRename,
+};
+use crate::services::subscription::sync;
+use crate::utils::{paths, time};
+use once_cell::sync::Lazy;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::Mutex;
+
+/// 每个节点最多保留的记录数
+const MAX_SAMPLES: usize = 100;
+/// 统计使用的最近样本数
+const WINDOW: usize = 20;
+/// 新旧两半中位数变化超过该比例时视为有趋势
+const TREND_THRESHOLD: f64 = 0.2;
+
+type History = BTreeMap<String, Vec<LatencySample>>;
+
+// 首次访问时从文件加载，之后以内存为准
+static HISTORY: Lazy<Mutex<History>> = Lazy::new(|| Mutex::new(read()));
+
+fn history_path() -> PathBuf {
+    paths::get_state_dir().join("latency_history.json")
+}
+
+fn read() -> History {
+    fs::read_to_string(history_path())
+        .ok()
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default()
+}
+
+fn write(history: &History) -> Result<(), Box<dyn std::error::Error>> {
+    let path = history_path();
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    fs::write(&path, serde_json::to_string(history)?)?;
+    Ok(())
+}
+
+/// 记录一批测试结果
+pub fn record(samples: Vec<(String, LatencySample)>) -> Result<(), Box<dyn std::error::Error>> {
+    if samples.is_empty() {
+        return Ok(());
+    }
+
+    let mut history = HISTORY.lock().unwrap();
+    for (node_file, sample) in samples {
+        let entries = history.entry(node_file).or_default();
+        entries.push(sample);
+        truncate(entries);
+    }
+    write(&history)
+}
+
+/// 只保留最近 MAX_SAMPLES 条记录
+fn truncate(entries: &mut Vec<LatencySample>) {
+    if entries.len() > MAX_SAMPLES {
+        let excess = entries.len() - MAX_SAMPLES;
+        entries.drain(..excess);
+    }
+}
+
+/// 将真实延迟测试结果转换为记录
+pub fn sample_of(result: &LatencyResult) -> (String, LatencySample) {
+    (
+        result.node_file.clone(),
+        LatencySample {
+            timestamp: time::now_secs(),
+            method: LatencyMethod::Real,
+            delay_ms: result.delay_ms,
+            error: result.error.as_ref().map(|e| e.message.clone()),
+        },
+    )
+}
+
+pub fn samples(node_file: &str) -> Vec<LatencySample> {
+    HISTORY
+        .lock()
+        .unwrap()
+        .get(node_file)
+        .cloned()
+        .unwrap_or_default()
+}
+
+/// 节点统计，只统计 method 方式的样本（TCP 与真实延迟的数值不可比较）；
+/// node_files 为空时返回全部有记录的节点
+pub fn stats(node_files: &[String], method: LatencyMethod) -> Vec<LatencyStats> {
+    let history = HISTORY.lock().unwrap();
+    if node_files.is_empty() {
+        return history
+            .iter()
+            .map(|(file, samples)| compute_stats(file, method, samples))
+            .collect();
+    }
+
+    node_files
+        .iter()
+        .map(|file| {
+            let samples = history.get(file).map(Vec::as_slice).unwrap_or(&[]);
+            compute_stats(file, method, samples)
+        })
+        .collect()
+}
+
+/// 节点文件改名后迁移其记录
+pub fn rename(renames: &[NodeRename]) -> Result<(), Box<dyn std::error::Error>> {
+    let mut history = HISTORY.lock().unwrap();
+    if sync::migrate_keys(&mut history, renames, merge) {
+        write(&history)?;
+    }
+    Ok(())
+}
+
+/// 改名到已有记录的文件名时按时间合并
+fn merge(entries: &mut Vec<LatencySample>, samples: Vec<LatencySample>) {
+    entries.extend(samples);
+    entries.sort_by_key(|s| s.timestamp);
+    truncate(entries);
+}
+
+/// 删除节点的记录
+pub fn forget(node_files: &[String]) -> Result<(), Box<dyn std::error::Error>> {
+    let mut history = HISTORY.lock().unwrap();
+    let before = history.len();
+    for file in node_files {
+        history.remove(file);
+    }
+    if history.len() != before {
+        write(&history)?;
+    }
+    Ok(())
+}
+
+/// 清空全部记录
+pub fn clear() -> Result<(), Box<dyn std::error::Error>> {
+    let mut history = HISTORY.lock().unwrap();
+    history.clear();
+    write(&history)
+}
+
+fn compute_stats(
+    node_file: &str,
+    method: LatencyMethod,
+    samples: &[LatencySample],
+) -> LatencyStats {
+    let samples: Vec<&LatencySample> = samples.iter().filter(|s| s.method == method).collect();
+    let window = &samples[samples.len().saturating_sub(WINDOW)..];
+    let delays: Vec<u64> = window.iter().filter_map(|s| s.delay_ms).collect();
+    let last = window.last();
+
+    let loss_percent = if window.is_empty() {
+        0.0
+    } else {
+        (window.len() - delays.len()) as f64 * 100.0 / window.len() as f64
+    };
+
+    let jitter_ms = (delays.len() >= 2).then(|| {
+        let total: u64 = delays.windows(2).map(|w| w[0].abs_diff(w[1])).sum();
+        total / (delays.len() as u64 - 1)
+    });
+
+    LatencyStats {
+        node_file: node_file.to_string(),
+        method,
+        samples: window.len(),
+        last_delay_ms: last.and_then(|s| s.delay_ms),
+        last_tested: last.map(|s| s.timestamp),
+        median_ms: median(&delays),
+        jitter_ms,
+        loss_percent,
+        trend: trend(&delays),
+    }
+}
+
+fn median(values: &[u64]) -> Option<u64> {
+    if values.is_empty() {
+        return None;
+    }
+    let mut sorted = values.to_vec();
+    sorted.sort_unstable();
+    let mid = sorted.len() / 2;
+    Some(if sorted.len().is_multiple_of(2) {
+        (sorted[mid - 1] + sorted[mid]) / 2
+    } else {
+        sorted[mid]
+    })
+}
+
+/// 比较窗口内较早一半与较新一半的中位数
+fn trend(delays: &[u64]) -> LatencyTrend {
+    if delays.len() < 4 {
+        return LatencyTrend::Unknown;
+    }
+
+    let (older, newer) = delays.split_at(delays.len() / 2);
+    let (Some(older), Some(newer)) = (median(older), median(newer)) else {
+        return LatencyTrend::Unknown;
+    };
+    let change = (newer as f64 - older as f64) / older.max(1) as f64;
+
+    if change > TREND_THRESHOLD {
+        LatencyTrend::Degrading
+    } else if change < -TREND_THRESHOLD {
+        LatencyTrend::Improving
+    } else {
+        LatencyTrend::Stable
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample(timestamp: u64) -> LatencySample {
+        LatencySample {
+            timestamp,
+            method: LatencyMethod::Tcp,
+            delay_ms: Some(timestamp),
+            error: None,
+        }
+    }
+
+    fn rename(from: &str, to: &str) -> NodeRename {
+        NodeRename {
+            from: from.to_string(),
+            to: to.to_string(),
+        }
+    }
+
+    fn timestamps(history: &History, file: &str) -> Vec<u64> {
+        history
+            .get(file)
+            .map(|s| s.iter().map(|s| s.timestamp).collect())
+            .unwrap_or_default()
+    }
+
+    #[test]
+    fn merges_into_existing_history() {
+        let mut history = History::new();
+        history.insert("old".into(), vec![sample(1), sample(5)]);
+        history.insert("new".into(), vec![sample(3)]);
+
+        assert!(sync::migrate_keys(
+            &mut history,
+            &[rename("old", "new")],
+            merge
+        ));
+        assert!(!history.contains_key("old"));
+        assert_eq!(timestamps(&history, "new"), [1, 3, 5]);
+    }
+
+    #[test]
+    fn merged_history_is_truncated() {
+        let mut history = History::new();
+        history.insert("old".into(), (0..MAX_SAMPLES as u64).map(sample).collect());
+        history.insert("new".into(), vec![sample(MAX_SAMPLES as u64)]);
+
+        sync::migrate_keys(&mut history, &[rename("old", "new")], merge);
+        let merged = timestamps(&history, "new");
+        assert_eq!(merged.len(), MAX_SAMPLES);
+        assert_eq!(merged.first(), Some(&1));
+        assert_eq!(merged.last(), Some(&(MAX_SAMPLES as u64)));
+    }
+
+    fn measured(method: LatencyMethod, delay_ms: Option<u64>) -> LatencySample {
+        LatencySample {
+            timestamp: 0,
+            method,
+            delay_ms,
+            error: None,
+        }
+    }
+
+    #[test]
+    fn computes_stats_per_method() {
+        let samples = [
+            measured(LatencyMethod::Real, Some(300)),
+            measured(LatencyMethod::Tcp, Some(20)),
+            measured(LatencyMethod::Real, None),
+            measured(LatencyMethod::Tcp, Some(40)),
+            measured(LatencyMethod::Real, Some(200)),
+            measured(LatencyMethod::Real, Some(250)),
+        ];
+
+        let real = compute_stats("a.json", LatencyMethod::Real, &samples);
+        assert_eq!(real.samples, 4);
+        assert_eq!(real.last_delay_ms, Some(250));
+        assert_eq!(real.median_ms, Some(250));
+        // 300→200→250
+        assert_eq!(real.jitter_ms, Some(75));
+        assert_eq!(real.loss_percent, 25.0);
+
+        let tcp = compute_stats("a.json", LatencyMethod::Tcp, &samples);
+        assert_eq!(tcp.samples, 2);
+        assert_eq!(tcp.median_ms, Some(30));
+        assert_eq!(tcp.jitter_ms, Some(20));
+        assert_eq!(tcp.loss_percent, 0.0);
+
+        let empty = compute_stats("a.json", LatencyMethod::Tcp, &samples[..1]);
+        assert_eq!(empty.samples, 0);
+        assert_eq!(empty.median_ms, None);
+        assert_eq!(empty.jitter_ms, None);
+        assert_eq!(empty.loss_percent, 0.0);
+    }
+
+    #[test]
+    fn stats_use_recent_window() {
+        let samples: Vec<_> = (0..WINDOW as u64 + 5)
+            .map(|i| measured(LatencyMethod::Tcp, Some(i)))
+            .collect();
+        let stats = compute_stats("a.json", LatencyMethod::Tcp, &samples);
+        assert_eq!(stats.samples, WINDOW);
+        assert_eq!(stats.last_delay_ms, Some(WINDOW as u64 + 4));
+    }
+
+    #[test]
+    fn medians() {
+        assert_eq!(median(&[]), None);
+        assert_eq!(median(&[7]), Some(7));
+        assert_eq!(median(&[9, 1, 5]), Some(5));
+        assert_eq!(median(&[10, 40, 20, 30]), Some(25));
+    }
+
+    #[test]
+    fn trends() {
+        assert_eq!(trend(&[100, 500, 900]), LatencyTrend::Unknown);
+        assert_eq!(trend(&[100, 110, 200, 210]), LatencyTrend::Degrading);
+        assert_eq!(trend(&[200, 210, 100, 110]), LatencyTrend::Improving);
+        assert_eq!(trend(&[100, 110, 105, 115]), LatencyTrend::Stable);
+        // 较早一半为 0 时按 1 计算变化比例
+        assert_eq!(trend(&[0, 0, 5, 5]), LatencyTrend::Degrading);
+    }
+}
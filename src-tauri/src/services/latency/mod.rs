@@ -0,0 +1,392 @@
+// 真实延迟测试：为待测节点启动临时 xray 实例，经其本地 socks 入站请求测试地址，
+// 能反映协议、TLS 与凭据是否可用，而不只是服务器端口能否连通；
+// 批量测试时所有节点共用一个实例，每个节点对应一对入站与出站
+pub mod batch;
+pub mod history;
+pub mod settings;
+
+use crate::models::{LatencyError, LatencyErrorKind, LatencyResult, LatencySettings};
+use crate::services::proxylink;
+use crate::utils::{paths, process};
+use serde_json::Value;
+use std::error::Error;
+use std::fs::{self, File};
+use std::net::TcpListener;
+use std::path::PathBuf;
+use std::process::{Child, Command, Stdio};
+use std::time::{Duration, Instant};
+
+// 临时实例启动等待时间
+const CORE_START_TIMEOUT: Duration = Duration::from_secs(3);
+const CORE_POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+/// 测试单个节点，node_file 相对于 outbounds 目录
+pub async fn test_node(node_file: &str, settings: &LatencySettings) -> LatencyResult {
+    let result = match read_outbound(node_file) {
+        Ok(outbound) => match TempCore::start(&[outbound]).await {
+            // core 在本分支结束时被回收
+            Ok(core) => measure_via(core.ports[0], settings).await,
+            Err(e) => Err(e),
+        },
+        Err(e) => Err(e),
+    };
+    into_result(node_file, result)
+}
+
+/// 经临时实例的本地入站测量
+async fn measure_via(port: u16, settings: &LatencySettings) -> Result<Measurement, LatencyError> {
+    let client = proxy_client(port, settings)?;
+    measure(&client, &settings.test_url).await
+}
+
+fn into_result(node_file: &str, result: Result<Measurement, LatencyError>) -> LatencyResult {
+    match result {
+        Ok(m) => LatencyResult {
+            node_file: node_file.to_string(),
+            success: true,
+            delay_ms: Some(m.delay.as_millis() as u64),
+            estimated_handshake_ms: m
+                .warm
+                .map(|warm| m.delay.saturating_sub(warm).as_millis() as u64),
+            warm_ms: m.warm.map(|warm| warm.as_millis() as u64),
+            error: None,
+        },
+        Err(e) => LatencyResult {
+            node_file: node_file.to_string(),
+            success: false,
+            delay_ms: None,
+            estimated_handshake_ms: None,
+            warm_ms: None,
+            error: Some(e),
+        },
+    }
+}
+
+/// 测量结果
+pub struct Measurement {
+    /// 首次请求到收到响应头的耗时
+    pub delay: Duration,
+    /// 复用连接的第二次请求到收到响应头的耗时，失败时为 None
+    pub warm: Option<Duration>,
+}
+
+/// 通过给定客户端请求测试地址两次：第一次包含建立代理连接，第二次复用已有连接
+pub async fn measure(client: &reqwest::Client, url: &str) -> Result<Measurement, LatencyError> {
+    let start = Instant::now();
+    let response = client.get(url).send().await.map_err(classify)?;
+    let delay = start.elapsed();
+    check_status(&response)?;
+    // 读完响应体，连接才能被复用
+    let _ = response.bytes().await;
+
+    let start = Instant::now();
+    let warm = match client.get(url).send().await {
+        Ok(response) if check_status(&response).is_ok() => Some(start.elapsed()),
+        _ => None,
+    };
+
+    Ok(Measurement { delay, warm })
+}
+
+fn proxy_client(port: u16, settings: &LatencySettings) -> Result<reqwest::Client, LatencyError> {
+    // socks5h: 域名交给代理解析，与实际使用时一致
+    reqwest::Proxy::all(format!("socks5h://127.0.0.1:{}", port))
+        .and_then(|proxy| {
+            reqwest::Client::builder()
+                .proxy(proxy)
+                .timeout(Duration::from_millis(settings.timeout_ms))
+                .build()
+        })
+        .map_err(|e| error(LatencyErrorKind::Network, e))
+}
+
+fn check_status(response: &reqwest::Response) -> Result<(), LatencyError> {
+    let status = response.status();
+    if status.is_success() || status.is_redirection() {
+        Ok(())
+    } else {
+        Err(error(
+            LatencyErrorKind::BadStatus,
+            format!("HTTP {}", status),
+        ))
+    }
+}
+
+/// 按 reqwest 错误类型归类失败原因
+fn classify(e: reqwest::Error) -> LatencyError {
+    let kind = if e.is_timeout() {
+        LatencyErrorKind::Timeout
+    } else if e.is_connect() || e.is_request() || e.is_body() {
+        // 本地 socks 入站总能连上，连接被关闭说明节点握手失败
+        LatencyErrorKind::ProxyFailed
+    } else {
+        LatencyErrorKind::Network
+    };
+
+    // 拼接完整的错误链，便于定位原因
+    let mut message = e.to_string();
+    let mut source = e.source();
+    while let Some(inner) = source {
+        message.push_str(": ");
+        message.push_str(&inner.to_string());
+        source = inner.source();
+    }
+
+    LatencyError { kind, message }
+}
+
+fn error(kind: LatencyErrorKind, message: impl ToString) -> LatencyError {
+    LatencyError {
+        kind,
+        message: message.to_string(),
+    }
+}
+
+/// 临时 xray 实例：第 i 个 socks 入站经路由规则只转发到第 i 个出站，
+/// 离开作用域时结束进程并清理配置
+struct TempCore {
+    /// 仅在 drop 时取出
+    child: Option<Child>,
+    /// 与传入的出站一一对应的入站端口
+    ports: Vec<u16>,
+    config_path: PathBuf,
+    /// xray 的 stdout/stderr 写入该文件，启动失败时读取末尾几行
+    output_path: PathBuf,
+}
+
+impl TempCore {
+    async fn start(outbounds: &[Value]) -> Result<Self, LatencyError> {
+        let ports =
+            free_ports(outbounds.len()).map_err(|e| error(LatencyErrorKind::CoreFailed, e))?;
+
+        let dir = paths::get_state_dir().join("latency");
+        fs::create_dir_all(&dir).map_err(|e| error(LatencyErrorKind::CoreFailed, e))?;
+        let config_path = dir.join(format!("{}.json", ports[0]));
+        let output_path = dir.join(format!("{}.log", ports[0]));
+
+        let config = serde_json::json!({
+            "log": { "loglevel": "none" },
+            "inbounds": ports.iter().enumerate().map(|(i, port)| serde_json::json!({
+                "tag": format!("latency-in-{}", i),
+                "listen": "127.0.0.1",
+                "port": port,
+                "protocol": "socks",
+                "settings": { "udp": false }
+            })).collect::<Vec<_>>(),
+            "outbounds": outbounds.iter().enumerate().map(|(i, outbound)| {
+                let mut outbound = outbound.clone();
+                outbound["tag"] = Value::from(format!("latency-out-{}", i));
+                outbound
+            }).collect::<Vec<_>>(),
+            "routing": {
+                "rules": (0..ports.len()).map(|i| serde_json::json!({
+                    "type": "field",
+                    "inboundTag": [format!("latency-in-{}", i)],
+                    "outboundTag": format!("latency-out-{}", i)
+                })).collect::<Vec<_>>()
+            }
+        });
+        fs::write(&config_path, config.to_string())
+            .map_err(|e| error(LatencyErrorKind::CoreFailed, e))?;
+
+        // 输出写入文件而不是管道，避免无人读取时管道写满阻塞 xray
+        let spawned = File::create(&output_path)
+            .and_then(|out| Ok((out.try_clone()?, out)))
+            .and_then(|(stdout, stderr)| {
+                process::configure_background(
+                    Command::new(paths::get_xray_path())
+                        .arg("run")
+                        .arg("-c")
+                        .arg(&config_path)
+                        .current_dir(&dir)
+                        .stdin(Stdio::null())
+                        .stdout(stdout)
+                        .stderr(stderr),
+                )
+                .spawn()
+            });
+        let child = match spawned {
+            Ok(child) => child,
+            Err(e) => {
+                let _ = fs::remove_file(&config_path);
+                let _ = fs::remove_file(&output_path);
+                return Err(error(
+                    LatencyErrorKind::CoreFailed,
+                    format!("无法启动 xray: {}", e),
+                ));
+            }
+        };
+
+        let mut core = Self {
+            child: Some(child),
+            ports,
+            config_path,
+            output_path,
+        };
+        core.wait_ready().await?;
+        Ok(core)
+    }
+
+    /// 等待全部入站端口可连接；进程提前退出时返回其输出
+    async fn wait_ready(&mut self) -> Result<(), LatencyError> {
+        let deadline = Instant::now() + CORE_START_TIMEOUT;
+        let mut ready = 0;
+        while Instant::now() < deadline {
+            let exited = self
+                .child
+                .as_mut()
+                .and_then(|c| c.try_wait().ok().flatten());
+            if let Some(status) = exited {
+                let output = self.output_tail();
+                return Err(error(
+                    LatencyErrorKind::CoreFailed,
+                    format!("xray 已退出 ({}): {}", status, output),
+                ));
+            }
+            while let Some(port) = self.ports.get(ready)
+                && tokio::net::TcpStream::connect(("127.0.0.1", *port))
+                    .await
+                    .is_ok()
+            {
+                ready += 1;
+            }
+            if ready == self.ports.len() {
+                return Ok(());
+            }
+            tokio::time::sleep(CORE_POLL_INTERVAL).await;
+        }
+
+        Err(error(LatencyErrorKind::CoreFailed, "xray 启动超时"))
+    }
+
+    /// 已退出进程的最后几行输出
+    fn output_tail(&self) -> String {
+        let output = fs::read_to_string(&self.output_path).unwrap_or_default();
+        let lines: Vec<&str> = output.lines().filter(|l| !l.trim().is_empty()).collect();
+        lines[lines.len().saturating_sub(5)..].join("\n")
+    }
+}
+
+impl Drop for TempCore {
+    fn drop(&mut self) {
+        // terminate 会等待进程退出，放到阻塞线程池中，避免占用异步运行时的工作线程
+        let child = self.child.take();
+        let files = [self.config_path.clone(), self.output_path.clone()];
+        tauri::async_runtime::spawn_blocking(move || {
+            if let Some(mut child) = child {
+                process::terminate(&mut child);
+            }
+            for file in files {
+                let _ = fs::remove_file(file);
+            }
+        });
+    }
+}
+
+/// 读取节点文件中的出站
+fn read_outbound(node_file: &str) -> Result<Value, LatencyError> {
+    proxylink::read_outbound(node_file).map_err(|e| {
+        error(
+            LatencyErrorKind::InvalidNode,
+            format!("{}: {}", node_file, e),
+        )
+    })
+}
+
+/// 由系统分配 n 个互不相同的空闲本地端口，全部分配完才释放，避免重复
+fn free_ports(n: usize) -> std::io::Result<Vec<u16>> {
+    let listeners = (0..n)
+        .map(|_| TcpListener::bind("127.0.0.1:0"))
+        .collect::<std::io::Result<Vec<_>>>()?;
+    listeners
+        .iter()
+        .map(|listener| Ok(listener.local_addr()?.port()))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    /// 本地 HTTP 服务，对每个请求返回固定状态行；delay 不为空时延迟响应
+    async fn serve(status: &'static str, delay: Option<Duration>) -> String {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+
+        tokio::spawn(async move {
+            while let Ok((mut socket, _)) = listener.accept().await {
+                tokio::spawn(async move {
+                    // 保持连接，同一连接上可处理多个请求
+                    let mut request = Vec::new();
+                    let mut buf = [0u8; 1024];
+                    loop {
+                        match socket.read(&mut buf).await {
+                            Ok(0) | Err(_) => return,
+                            Ok(n) => request.extend_from_slice(&buf[..n]),
+                        }
+                        while let Some(end) = request.windows(4).position(|w| w == b"\r\n\r\n") {
+                            request.drain(..end + 4);
+                            if let Some(delay) = delay {
+                                tokio::time::sleep(delay).await;
+                            }
+                            let response =
+                                format!("HTTP/1.1 {}\r\nContent-Length: 0\r\n\r\n", status);
+                            if socket.write_all(response.as_bytes()).await.is_err() {
+                                return;
+                            }
+                        }
+                    }
+                });
+            }
+        });
+
+        format!("http://{}/generate_204", addr)
+    }
+
+    fn client(timeout: Duration) -> reqwest::Client {
+        reqwest::Client::builder()
+            .no_proxy()
+            .timeout(timeout)
+            .build()
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn measures_no_content_response() {
+        let url = serve("204 No Content", None).await;
+        let m = measure(&client(Duration::from_secs(5)), &url)
+            .await
+            .unwrap();
+
+        assert!(m.delay < Duration::from_secs(5));
+        // 第二次请求复用连接
+        assert!(m.warm.is_some());
+    }
+
+    #[tokio::test]
+    async fn classifies_failures() {
+        let bad_status = serve("503 Service Unavailable", None).await;
+        let slow = serve("204 No Content", Some(Duration::from_secs(5))).await;
+        // 绑定后立即释放，得到一个无人监听的端口
+        let closed = format!(
+            "http://127.0.0.1:{}/generate_204",
+            free_ports(1).unwrap()[0]
+        );
+
+        let cases = [
+            (bad_status, LatencyErrorKind::BadStatus, "503"),
+            (slow, LatencyErrorKind::Timeout, ""),
+            (closed, LatencyErrorKind::ProxyFailed, ""),
+        ];
+
+        for (url, kind, message) in cases {
+            let err = measure(&client(Duration::from_millis(300)), &url)
+                .await
+                .err()
+                .unwrap_or_else(|| panic!("{} 应当失败", url));
+            assert_eq!(err.kind, kind, "{}: {}", url, err.message);
+            assert!(err.message.contains(message), "{}: {}", url, err.message);
+        }
+    }
+}
@@ -0,0 +1,13 @@
+// 延迟测试设置：保存在配置目录的 latency.json
+use crate::models::LatencySettings;
+use crate::utils::settings;
+
+const FILE_NAME: &str = "latency.json";
+
+pub fn load() -> LatencySettings {
+    settings::load(FILE_NAME)
+}
+
+pub fn save(value: &LatencySettings) -> Result<(), Box<dyn std::error::Error>> {
+    settings::save(FILE_NAME, value)
+}
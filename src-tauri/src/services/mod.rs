@@ -1,4 +1,16 @@
+pub mod accounting;
+pub mod api;
+pub mod connections;
+pub mod failover;
+pub mod groups;
+pub mod inbounds;
+pub mod ipinfo;
+pub mod latency;
 pub mod monitor;
+pub mod ports;
 pub mod proxylink;
+pub mod selection;
+pub mod share_link;
+pub mod subscription;
 pub mod system_proxy;
 pub mod xray;
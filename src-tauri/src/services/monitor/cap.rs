@@ -0,0 +1,110 @@
+// 月流量上限：按结算日划分计费周期汇总每日流量，达到提醒比例或上限时发出事件
+use super::settings;
+use crate::models::{CapSource, TrafficCapSettings, TrafficCapStatus};
+use crate::services::accounting::store;
+use chrono::{Datelike, Local, NaiveDate};
+use std::sync::Mutex;
+use tauri::{AppHandle, Emitter};
+
+/// 用量达到提醒比例或上限的事件
+pub const CAP_WARNING_EVENT: &str = "traffic-cap-warning";
+
+// 本次运行中已提醒过的计费周期与级别，避免重复提醒
+static WARNED: Mutex<Option<(String, u8)>> = Mutex::new(None);
+
+/// 当前计费周期的开始日期
+fn period_start(today: NaiveDate, reset_day: u32) -> NaiveDate {
+    let reset_day = reset_day.clamp(1, 28);
+    let (year, month) = if today.day() >= reset_day {
+        (today.year(), today.month())
+    } else if today.month() == 1 {
+        (today.year() - 1, 12)
+    } else {
+        (today.year(), today.month() - 1)
+    };
+    NaiveDate::from_ymd_opt(year, month, reset_day).unwrap_or(today)
+}
+
+pub fn status(settings: &TrafficCapSettings) -> TrafficCapStatus {
+    let start = period_start(Local::now().date_naive(), settings.reset_day)
+        .format(store::DATE_FORMAT)
+        .to_string();
+    let used: u64 = store::daily_since(&start)
+        .iter()
+        .map(|d| match settings.source {
+            CapSource::System => d.system.total(),
+            CapSource::Proxy => d.proxy.total(),
+        })
+        .sum();
+    let percent = if settings.cap_bytes == 0 {
+        0.0
+    } else {
+        used as f64 * 100.0 / settings.cap_bytes as f64
+    };
+
+    TrafficCapStatus {
+        period_start: start,
+        used,
+        cap_bytes: settings.cap_bytes,
+        percent,
+        source: settings.source,
+        exceeded: settings.cap_bytes > 0 && used >= settings.cap_bytes,
+    }
+}
+
+/// 检查用量，每个计费周期在达到提醒比例和上限时各提醒一次
+pub fn check(app: &AppHandle) {
+    let settings = settings::load();
+    if !settings.enabled || settings.cap_bytes == 0 {
+        return;
+    }
+
+    let status = status(&settings);
+    let level = if status.exceeded {
+        100
+    } else if status.percent >= f64::from(settings.warn_percent) {
+        settings.warn_percent
+    } else {
+        return;
+    };
+
+    let mut warned = WARNED.lock().unwrap();
+    if let Some((period, warned_level)) = warned.as_ref()
+        && *period == status.period_start
+        && *warned_level >= level
+    {
+        return;
+    }
+    *warned = Some((status.period_start.clone(), level));
+    let _ = app.emit(CAP_WARNING_EVENT, status);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(year: i32, month: u32, day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(year, month, day).unwrap()
+    }
+
+    #[test]
+    fn starts_on_reset_day() {
+        assert_eq!(period_start(date(2026, 10, 18), 1), date(2026, 10, 1));
+        assert_eq!(period_start(date(2026, 10, 15), 15), date(2026, 10, 15));
+        assert_eq!(period_start(date(2026, 10, 14), 15), date(2026, 9, 15));
+    }
+
+    #[test]
+    fn wraps_to_previous_year() {
+        assert_eq!(period_start(date(2026, 1, 5), 10), date(2025, 12, 10));
+        assert_eq!(period_start(date(2026, 1, 10), 10), date(2026, 1, 10));
+    }
+
+    #[test]
+    fn clamps_reset_day() {
+        // 超过 28 的结算日按 28 处理，避免短月份没有该日期
+        assert_eq!(period_start(date(2026, 3, 1), 31), date(2026, 2, 28));
+        assert_eq!(period_start(date(2026, 3, 30), 31), date(2026, 3, 28));
+        assert_eq!(period_start(date(2026, 3, 30), 0), date(2026, 3, 1));
+    }
+}
@@ -0,0 +1,149 @@
+// 网卡选择：识别网卡类型，并按配置目录 interfaces.json 的包含/排除列表决定是否计入系统流量
+use crate::models::{InterfaceKind, InterfaceSettings};
+use crate::utils::settings;
+
+const FILE_NAME: &str = "interfaces.json";
+
+pub fn load() -> InterfaceSettings {
+    settings::load(FILE_NAME)
+}
+
+pub fn save(value: &InterfaceSettings) -> Result<(), Box<dyn std::error::Error>> {
+    settings::save(FILE_NAME, value)
+}
+
+/// 网卡是否计入系统流量；回环与 TUN 网卡上的流量会与物理网卡重复计算，默认排除
+pub fn is_selected(name: &str, kind: InterfaceKind, settings: &InterfaceSettings) -> bool {
+    if settings.included.iter().any(|n| n == name) {
+        return true;
+    }
+    if !settings.included.is_empty() || settings.excluded.iter().any(|n| n == name) {
+        return false;
+    }
+    !matches!(kind, InterfaceKind::Loopback | InterfaceKind::Tun)
+}
+
+/// 识别网卡类型，Linux 读取 /sys/class/net，其他平台按名称判断
+pub fn detect_kind(name: &str) -> InterfaceKind {
+    #[cfg(target_os = "linux")]
+    if let Some(kind) = detect_sysfs(name) {
+        return kind;
+    }
+    detect_by_name(name)
+}
+
+#[cfg(target_os = "linux")]
+fn detect_sysfs(name: &str) -> Option<InterfaceKind> {
+    let dir = std::path::Path::new("/sys/class/net").join(name);
+    // ARPHRD_*：772 回环，65534 无链路层（TUN），1 以太网
+    let link_type: u32 = std::fs::read_to_string(dir.join("type"))
+        .ok()?
+        .trim()
+        .parse()
+        .ok()?;
+
+    let kind = if link_type == 772 {
+        InterfaceKind::Loopback
+    } else if link_type == 65534 || dir.join("tun_flags").exists() {
+        InterfaceKind::Tun
+    } else if dir.join("wireless").exists() || dir.join("phy80211").exists() {
+        InterfaceKind::Wireless
+    } else if !dir.join("device").exists() {
+        // 没有对应的物理设备：网桥、veth、docker 等
+        InterfaceKind::Virtual
+    } else if link_type == 1 {
+        InterfaceKind::Ethernet
+    } else {
+        InterfaceKind::Other
+    };
+    Some(kind)
+}
+
+/// Unix 的短名称（如 enp3s0、wlan0、utun3）按前缀匹配，
+/// Windows 的友好名称（如 "Ethernet 2"、"vEthernet (WSL)"）按完整单词匹配
+fn detect_by_name(name: &str) -> InterfaceKind {
+    let lower = name.to_lowercase();
+    let words: Vec<&str> = lower
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|w| !w.is_empty())
+        .collect();
+    let prefix = |patterns: &[&str]| patterns.iter().any(|p| lower.starts_with(p));
+    let word = |patterns: &[&str]| words.iter().any(|w| patterns.contains(w));
+
+    if lower == "lo" || prefix(&["lo0"]) || word(&["loopback"]) {
+        InterfaceKind::Loopback
+    } else if prefix(&["tun", "tap", "utun", "wg"])
+        || word(&[
+            "tun",
+            "tap",
+            "wintun",
+            "wireguard",
+            "openvpn",
+            "vpn",
+            "tunnel",
+            "tunneling",
+        ])
+    {
+        InterfaceKind::Tun
+    } else if prefix(&[
+        "veth", "docker", "br-", "virbr", "vmnet", "vboxnet", "bridge",
+    ]) || word(&[
+        "vethernet",
+        "virtual",
+        "vmware",
+        "virtualbox",
+        "vbox",
+        "hyper",
+        "docker",
+    ]) {
+        InterfaceKind::Virtual
+    } else if prefix(&["wl"])
+        || word(&["wifi", "wlan", "wireless"])
+        || lower.contains("wi-fi")
+        || lower.contains("无线")
+    {
+        InterfaceKind::Wireless
+    } else if prefix(&["eth", "en"]) || word(&["ethernet"]) || lower.contains("以太网") {
+        InterfaceKind::Ethernet
+    } else {
+        InterfaceKind::Other
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn detects_kind_by_name() {
+        let cases = [
+            ("lo", InterfaceKind::Loopback),
+            ("lo0", InterfaceKind::Loopback),
+            ("Npcap Loopback Adapter", InterfaceKind::Loopback),
+            ("utun3", InterfaceKind::Tun),
+            ("tun0", InterfaceKind::Tun),
+            ("wg0", InterfaceKind::Tun),
+            ("TAP-Windows Adapter V9", InterfaceKind::Tun),
+            ("OpenVPN Data Channel Offload", InterfaceKind::Tun),
+            ("WireGuard Tunnel", InterfaceKind::Tun),
+            ("vEthernet (WSL)", InterfaceKind::Virtual),
+            ("docker0", InterfaceKind::Virtual),
+            ("br-1a2b3c", InterfaceKind::Virtual),
+            ("VMware Network Adapter VMnet8", InterfaceKind::Virtual),
+            ("wlan0", InterfaceKind::Wireless),
+            ("wlp2s0", InterfaceKind::Wireless),
+            ("Wi-Fi", InterfaceKind::Wireless),
+            ("WLAN", InterfaceKind::Wireless),
+            ("en0", InterfaceKind::Ethernet),
+            ("enp3s0", InterfaceKind::Ethernet),
+            ("eth0", InterfaceKind::Ethernet),
+            ("Ethernet 2", InterfaceKind::Ethernet),
+            ("以太网", InterfaceKind::Ethernet),
+            ("Bluetooth Network Connection", InterfaceKind::Other),
+            ("gif0", InterfaceKind::Other),
+        ];
+        for (name, kind) in cases {
+            assert_eq!(detect_by_name(name), kind, "{}", name);
+        }
+    }
+}
@@ -0,0 +1,348 @@
+// 流量监控：后台任务按固定间隔采样，保存最近的时间序列并向窗口推送 traffic 事件，
+// 同时把系统流量与各出站的增量交给流量记账
+pub mod cap;
+pub mod interfaces;
+pub mod settings;
+
+use crate::models::{
+    ByteCount, InterfaceKind, InterfaceSettings, NetworkInterface, TrafficCounter, TrafficSample,
+    TrafficSource, TrafficStats,
+};
+use crate::services::api::stats;
+use crate::services::{accounting, xray};
+use crate::utils::time;
+use once_cell::sync::Lazy;
+use std::collections::{HashMap, VecDeque};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use sysinfo::Networks;
+use tauri::{AppHandle, Emitter};
+
+/// 每次采样推送的流量事件
+pub const TRAFFIC_EVENT: &str = "traffic";
+
+const SAMPLE_INTERVAL: Duration = Duration::from_secs(1);
+/// 保留最近 10 分钟的采样
+const HISTORY_LEN: usize = 600;
+/// 每隔多少次采样把累计的增量记账
+const RECORD_TICKS: u32 = 10;
+/// 每隔多少次采样写回流量记账并检查流量上限
+const FLUSH_TICKS: u32 = 60;
+
+static HISTORY: Mutex<VecDeque<TrafficSample>> = Mutex::new(VecDeque::new());
+static LATEST: Mutex<Option<TrafficStats>> = Mutex::new(None);
+
+/// 列表刷新间隔，用于发现新出现的网卡
+const LIST_REFRESH_INTERVAL: Duration = Duration::from_secs(30);
+
+// 全局监控状态
+pub struct MonitorState {
+    networks: Networks,
+    settings: InterfaceSettings,
+    kinds: HashMap<String, InterfaceKind>,
+    last_update: Instant,
+    last_list_refresh: Instant,
+    // 每个网卡上次的累计计数 (发送, 接收)
+    last_totals: HashMap<String, (u64, u64)>,
+    // 每个网卡的速率 (上传, 下载)
+    speeds: HashMap<String, (u64, u64)>,
+    // 自上次 take_delta 以来所选网卡的增量
+    pending: ByteCount,
+}
+
+impl MonitorState {
+    pub fn new() -> Self {
+        // 初始化网络列表 (需刷新)
+        let networks = Networks::new_with_refreshed_list();
+        let last_totals = networks
+            .iter()
+            .map(|(name, data)| {
+                (
+                    name.clone(),
+                    (data.total_transmitted(), data.total_received()),
+                )
+            })
+            .collect();
+
+        Self {
+            networks,
+            settings: interfaces::load(),
+            kinds: HashMap::new(),
+            last_update: Instant::now(),
+            last_list_refresh: Instant::now(),
+            last_totals,
+            speeds: HashMap::new(),
+            pending: ByteCount::default(),
+        }
+    }
+
+    fn kind_of(&mut self, name: &str) -> InterfaceKind {
+        *self
+            .kinds
+            .entry(name.to_string())
+            .or_insert_with(|| interfaces::detect_kind(name))
+    }
+
+    fn is_selected(&mut self, name: &str) -> bool {
+        let kind = self.kind_of(name);
+        interfaces::is_selected(name, kind, &self.settings)
+    }
+
+    /// 刷新计数并计算每个网卡的速率
+    fn refresh(&mut self) {
+        let now = Instant::now();
+        let elapsed = now.duration_since(self.last_update).as_secs_f64();
+
+        // 避免除以零或间隔过短，沿用上次的速率
+        if elapsed < 0.5 {
+            return;
+        }
+
+        if now.duration_since(self.last_list_refresh) >= LIST_REFRESH_INTERVAL {
+            self.networks.refresh_list();
+            self.last_list_refresh = now;
+        } else {
+            self.networks.refresh();
+        }
+
+        let current: Vec<(String, (u64, u64))> = self
+            .networks
+            .iter()
+            .map(|(name, data)| {
+                (
+                    name.clone(),
+                    (data.total_transmitted(), data.total_received()),
+                )
+            })
+            .collect();
+
+        let mut speeds = HashMap::new();
+        let mut last_totals = HashMap::new();
+        for (name, (tx, rx)) in current {
+            // 新出现的网卡从本次开始计数；计数回退时视为没有增量
+            let (last_tx, last_rx) = self.last_totals.get(&name).copied().unwrap_or((tx, rx));
+            let delta = ByteCount {
+                uplink: tx.saturating_sub(last_tx),
+                downlink: rx.saturating_sub(last_rx),
+            };
+
+            // 计算网速 (字节/秒)
+            speeds.insert(
+                name.clone(),
+                (
+                    (delta.uplink as f64 / elapsed) as u64,
+                    (delta.downlink as f64 / elapsed) as u64,
+                ),
+            );
+            if self.is_selected(&name) {
+                self.pending.add(delta);
+            }
+            last_totals.insert(name, (tx, rx));
+        }
+
+        // 更新状态
+        self.speeds = speeds;
+        self.last_totals = last_totals;
+        self.last_update = now;
+    }
+
+    /// 取出自上次调用以来所选网卡的流量增量
+    pub fn take_delta(&mut self) -> ByteCount {
+        std::mem::take(&mut self.pending)
+    }
+
+    pub fn set_settings(&mut self, settings: InterfaceSettings) {
+        self.settings = settings;
+    }
+
+    /// 全部网卡及其类型与是否计入统计
+    pub fn interfaces(&mut self) -> Vec<NetworkInterface> {
+        self.networks.refresh_list();
+        self.last_list_refresh = Instant::now();
+
+        let networks: Vec<(String, String, u64, u64)> = self
+            .networks
+            .iter()
+            .map(|(name, data)| {
+                (
+                    name.clone(),
+                    data.mac_address().to_string(),
+                    data.total_transmitted(),
+                    data.total_received(),
+                )
+            })
+            .collect();
+
+        let mut interfaces: Vec<NetworkInterface> = networks
+            .into_iter()
+            .map(
+                |(name, mac_address, transmitted, received)| NetworkInterface {
+                    kind: self.kind_of(&name),
+                    included: self.is_selected(&name),
+                    name,
+                    mac_address,
+                    transmitted,
+                    received,
+                },
+            )
+            .collect();
+        interfaces.sort_by(|a, b| a.name.cmp(&b.name));
+        interfaces
+    }
+
+    /// 按所选网卡生成的流量统计
+    pub fn system_stats(&mut self) -> TrafficStats {
+        self.refresh();
+
+        let mut names: Vec<String> = self.last_totals.keys().cloned().collect();
+        names.retain(|name| self.is_selected(name));
+        names.sort();
+        let interfaces: Vec<TrafficCounter> = names
+            .into_iter()
+            .map(|name| {
+                let (uplink, downlink) = self.last_totals[&name];
+                let (upload_speed, download_speed) =
+                    self.speeds.get(&name).copied().unwrap_or_default();
+                TrafficCounter {
+                    tag: name,
+                    uplink,
+                    downlink,
+                    upload_speed,
+                    download_speed,
+                }
+            })
+            .collect();
+
+        TrafficStats {
+            upload_speed: interfaces.iter().map(|i| i.upload_speed).sum(),
+            download_speed: interfaces.iter().map(|i| i.download_speed).sum(),
+            upload_total: interfaces.iter().map(|i| i.uplink).sum(),
+            download_total: interfaces.iter().map(|i| i.downlink).sum(),
+            source: TrafficSource::System,
+            inbounds: Vec::new(),
+            outbounds: Vec::new(),
+            interfaces,
+        }
+    }
+}
+
+// 全局实例
+pub static MONITOR: Lazy<Mutex<MonitorState>> = Lazy::new(|| Mutex::new(MonitorState::new()));
+
+/// 各出站与上次计数的差值，累加到 pending
+fn add_outbound_deltas(
+    last: &mut HashMap<String, (u64, u64)>,
+    outbounds: &[TrafficCounter],
+    pending: &mut HashMap<String, ByteCount>,
+) {
+    for counter in outbounds {
+        let (last_up, last_down) = last
+            .insert(counter.tag.clone(), (counter.uplink, counter.downlink))
+            .unwrap_or_default();
+        let delta = ByteCount {
+            uplink: counter.uplink.saturating_sub(last_up),
+            downlink: counter.downlink.saturating_sub(last_down),
+        };
+        if delta.total() > 0 {
+            pending.entry(counter.tag.clone()).or_default().add(delta);
+        }
+    }
+}
+
+pub fn start(app: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        let mut ticker = tokio::time::interval(SAMPLE_INTERVAL);
+        ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Skip);
+        // 上次读取的出站计数及对应的核心进程，核心重启后计数从零开始；
+        // 系统流量的增量由 MonitorState 按网卡计算
+        let mut last_outbounds: HashMap<String, (u64, u64)> = HashMap::new();
+        let mut last_pid = None;
+        // 尚未记账的增量
+        let mut pending_system = ByteCount::default();
+        let mut pending_outbounds: HashMap<String, ByteCount> = HashMap::new();
+        let mut ticks: u32 = 0;
+
+        loop {
+            ticker.tick().await;
+
+            // 系统计数始终采样，核心运行时另取 xray 的统计
+            let (system, system_delta) = {
+                let mut monitor = MONITOR.lock().unwrap();
+                (monitor.system_stats(), monitor.take_delta())
+            };
+            let pid = xray::current_pid();
+            if pid != last_pid {
+                last_outbounds.clear();
+                last_pid = pid;
+            }
+            let proxy = if pid.is_some() {
+                stats::traffic().await.ok()
+            } else {
+                None
+            };
+
+            pending_system.add(system_delta);
+            if let Some(proxy) = &proxy {
+                add_outbound_deltas(
+                    &mut last_outbounds,
+                    &proxy.outbounds,
+                    &mut pending_outbounds,
+                );
+            }
+
+            // 界面显示代理流量，核心未运行或 API 不可用时显示系统流量；网卡读数始终附带
+            let stats = match proxy {
+                Some(proxy) => TrafficStats {
+                    interfaces: system.interfaces,
+                    ..proxy
+                },
+                None => system,
+            };
+            record(&stats);
+            let _ = app.emit(TRAFFIC_EVENT, &stats);
+
+            ticks = ticks.wrapping_add(1);
+            if ticks.is_multiple_of(RECORD_TICKS) {
+                accounting::record(
+                    std::mem::take(&mut pending_system),
+                    std::mem::take(&mut pending_outbounds),
+                );
+            }
+            if ticks.is_multiple_of(FLUSH_TICKS) {
+                if let Err(e) = accounting::store::flush() {
+                    xray::output::log(format!("保存流量记录失败: {}", e));
+                }
+                cap::check(&app);
+            }
+        }
+    });
+}
+
+fn record(stats: &TrafficStats) {
+    let sample = TrafficSample {
+        timestamp: time::now_millis(),
+        upload_speed: stats.upload_speed,
+        download_speed: stats.download_speed,
+        upload_total: stats.upload_total,
+        download_total: stats.download_total,
+        source: stats.source,
+    };
+
+    let mut history = HISTORY.lock().unwrap();
+    if history.len() >= HISTORY_LEN {
+        history.pop_front();
+    }
+    history.push_back(sample);
+    *LATEST.lock().unwrap() = Some(stats.clone());
+}
+
+/// 最近一次采样；后台任务尚未采样时直接读取系统计数
+pub fn latest() -> TrafficStats {
+    let latest = LATEST.lock().unwrap().clone();
+    latest.unwrap_or_else(|| MONITOR.lock().unwrap().system_stats())
+}
+
+/// 缓存的流量时间序列，最旧的在前
+pub fn history() -> Vec<TrafficSample> {
+    HISTORY.lock().unwrap().iter().cloned().collect()
+}
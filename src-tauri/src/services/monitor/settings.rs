@@ -0,0 +1,13 @@
+// 流量上限设置：保存在配置目录的 traffic_cap.json
+use crate::models::TrafficCapSettings;
+use crate::utils::settings;
+
+const FILE_NAME: &str = "traffic_cap.json";
+
+pub fn load() -> TrafficCapSettings {
+    settings::load(FILE_NAME)
+}
+
+pub fn save(value: &TrafficCapSettings) -> Result<(), Box<dyn std::error::Error>> {
+    settings::save(FILE_NAME, value)
+}
@@ -0,0 +1,52 @@
+// Linux: 由 /proc/net/tcp{,6} 找到监听该端口的 socket inode，再在 /proc/<pid>/fd 中查找持有者
+use std::fs;
+
+// /proc/net/tcp 中的 LISTEN 状态
+const TCP_LISTEN: &str = "0A";
+
+pub fn find_listener_pid(port: u16) -> Option<u32> {
+    let inodes: Vec<String> = ["/proc/net/tcp", "/proc/net/tcp6"]
+        .iter()
+        .filter_map(|path| fs::read_to_string(path).ok())
+        .flat_map(|content| {
+            content
+                .lines()
+                .skip(1)
+                .filter_map(|line| listening_inode(line, port))
+                .collect::<Vec<_>>()
+        })
+        .collect();
+    if inodes.is_empty() {
+        return None;
+    }
+
+    let targets: Vec<String> = inodes.iter().map(|i| format!("socket:[{}]", i)).collect();
+    fs::read_dir("/proc")
+        .ok()?
+        .flatten()
+        .filter_map(|entry| entry.file_name().to_str()?.parse::<u32>().ok())
+        .find(|pid| {
+            // 其他用户的进程无权读取，跳过
+            fs::read_dir(format!("/proc/{}/fd", pid))
+                .map(|fds| {
+                    fds.flatten().any(|fd| {
+                        fs::read_link(fd.path()).is_ok_and(|link| {
+                            targets.iter().any(|t| link.as_os_str() == t.as_str())
+                        })
+                    })
+                })
+                .unwrap_or(false)
+        })
+}
+
+/// 解析一行 `sl local_address rem_address st ... inode`，端口匹配且处于监听状态时返回 inode
+fn listening_inode(line: &str, port: u16) -> Option<String> {
+    let fields: Vec<&str> = line.split_whitespace().collect();
+    let local = fields.get(1)?;
+    let state = fields.get(3)?;
+    let inode = fields.get(9)?;
+
+    let (_, hex_port) = local.rsplit_once(':')?;
+    let local_port = u16::from_str_radix(hex_port, 16).ok()?;
+    (local_port == port && *state == TCP_LISTEN && *inode != "0").then(|| inode.to_string())
+}
@@ -0,0 +1,263 @@
+// 端口占用检测：启动前检查入站与 API 端口，报告占用端口的进程，并可自动改用空闲端口；
+// 改用的端口写入生成的 08_ports.json，按 tag 覆盖入站并覆盖 api，不修改用户的配置文件
+#[cfg(target_os = "linux")]
+mod linux;
+#[cfg(windows)]
+mod windows;
+
+#[cfg(target_os = "linux")]
+use linux::find_listener_pid;
+#[cfg(windows)]
+use windows::find_listener_pid;
+
+use crate::models::{PortCheck, PortOwner, PortReassignment};
+use crate::services::inbounds;
+use crate::utils::{json, paths};
+use serde_json::{Value, json};
+use std::fs;
+use std::net::TcpListener;
+use sysinfo::{Pid, System};
+
+const API_CONFIG: &str = "05_api.json";
+/// 生成的端口覆盖配置，排在其他配置之后加载
+const OVERRIDE_CONFIG: &str = "08_ports.json";
+pub const API_TAG: &str = "api";
+// 自动选择端口时向后查找的范围
+const SEARCH_RANGE: u16 = 100;
+
+struct Endpoint {
+    tag: String,
+    listen: String,
+    port: u16,
+}
+
+/// 配置中需要监听的全部端口：01_inbounds.json 中的入站与 05_api.json 中的 API
+fn endpoints() -> Vec<Endpoint> {
+    let settings = inbounds::load();
+    let mut endpoints: Vec<Endpoint> = [
+        (inbounds::SOCKS_TAG, settings.socks_port),
+        (inbounds::HTTP_TAG, settings.http_port),
+        (inbounds::MIXED_TAG, settings.mixed_port),
+    ]
+    .into_iter()
+    .filter_map(|(tag, port)| {
+        Some(Endpoint {
+            tag: tag.to_string(),
+            listen: settings.listen.clone(),
+            port: port?,
+        })
+    })
+    .collect();
+
+    if let Some((listen, port)) = read_config(API_CONFIG).as_ref().and_then(parse_api_listen) {
+        endpoints.push(Endpoint {
+            tag: API_TAG.to_string(),
+            listen,
+            port,
+        });
+    }
+    endpoints
+}
+
+fn read_config(name: &str) -> Option<Value> {
+    let content = fs::read_to_string(paths::get_confdir().join(name)).ok()?;
+    serde_json::from_str(&content).ok()
+}
+
+fn parse_api_listen(config: &Value) -> Option<(String, u16)> {
+    let listen = config.pointer("/api/listen")?.as_str()?;
+    let (host, port) = listen.rsplit_once(':')?;
+    Some((
+        host.trim_matches(['[', ']']).to_string(),
+        port.parse().ok()?,
+    ))
+}
+
+/// 核心实际使用的 API 监听地址，如 "127.0.0.1:8080"；端口被自动改用时以 08_ports.json 为准
+pub fn api_listen() -> Option<(String, u16)> {
+    read_config(OVERRIDE_CONFIG)
+        .as_ref()
+        .and_then(parse_api_listen)
+        .or_else(|| read_config(API_CONFIG).as_ref().and_then(parse_api_listen))
+}
+
+/// 自动改用端口后覆盖的入站
+pub fn override_inbounds() -> Vec<Value> {
+    read_config(OVERRIDE_CONFIG)
+        .and_then(|config| config.get("inbounds").and_then(Value::as_array).cloned())
+        .unwrap_or_default()
+}
+
+/// 检查所有端口；ignore_pid 为正在运行的 xray，切换节点时它会先被停止，其占用不算冲突
+pub fn check(ignore_pid: Option<u32>) -> Vec<PortCheck> {
+    endpoints()
+        .into_iter()
+        .map(|endpoint| {
+            let free = is_free(&endpoint.listen, endpoint.port);
+            let owner = if free {
+                None
+            } else {
+                find_owner(endpoint.port)
+            };
+            let own = owner.as_ref().is_some_and(|o| Some(o.pid) == ignore_pid);
+
+            PortCheck {
+                tag: endpoint.tag,
+                listen: endpoint.listen,
+                port: endpoint.port,
+                available: free || own,
+                owner,
+            }
+        })
+        .collect()
+}
+
+fn is_free(listen: &str, port: u16) -> bool {
+    TcpListener::bind((listen, port)).is_ok()
+}
+
+fn find_owner(port: u16) -> Option<PortOwner> {
+    let pid = find_listener_pid(port)?;
+    let mut system = System::new();
+    system.refresh_process(Pid::from_u32(pid));
+    let process = system.process(Pid::from_u32(pid));
+
+    Some(PortOwner {
+        pid,
+        name: process.map(|p| p.name().to_string()).unwrap_or_default(),
+        exe: process
+            .and_then(|p| p.exe())
+            .map(|exe| exe.to_string_lossy().to_string()),
+    })
+}
+
+/// 冲突说明，如 "端口 10808 (socks-in) 已被 foo (PID 123) 占用"
+pub fn describe(conflicts: &[PortCheck]) -> String {
+    conflicts
+        .iter()
+        .map(|c| match &c.owner {
+            Some(owner) => format!(
+                "端口 {} ({}) 已被 {} (PID {}) 占用",
+                c.port, c.tag, owner.name, owner.pid
+            ),
+            None => format!("端口 {} ({}) 不可用", c.port, c.tag),
+        })
+        .collect::<Vec<_>>()
+        .join("; ")
+}
+
+/// 为冲突的端口选择空闲端口并写入 08_ports.json，没有冲突时删除该文件，恢复使用配置中的端口。
+/// 上次改用的端口仍空闲或被正在运行的 xray（ignore_pid）占用时继续使用，避免每次启动都换端口
+pub fn reassign(
+    conflicts: &[PortCheck],
+    ignore_pid: Option<u32>,
+) -> Result<Vec<PortReassignment>, Box<dyn std::error::Error>> {
+    let previous = read_config(OVERRIDE_CONFIG);
+    let configured = inbounds::configured().unwrap_or_default();
+    let mut used: Vec<u16> = endpoints().iter().map(|e| e.port).collect();
+    let mut config = json!({});
+    let mut changes = Vec::new();
+
+    for conflict in conflicts {
+        let port = previous
+            .as_ref()
+            .and_then(|previous| previous_port(previous, &conflict.tag))
+            .filter(|port| {
+                !used.contains(port)
+                    && (is_free(&conflict.listen, *port)
+                        || ignore_pid.is_some_and(|pid| find_listener_pid(*port) == Some(pid)))
+            })
+            .or_else(|| pick_free(&conflict.listen, conflict.port, &used))
+            .ok_or_else(|| format!("找不到可替代端口 {} 的空闲端口", conflict.port))?;
+        used.push(port);
+
+        if conflict.tag == API_TAG {
+            let mut api = read_config(API_CONFIG)
+                .and_then(|c| c.get("api").cloned())
+                .ok_or("无法读取 API 配置")?;
+            api["listen"] = json!(format_listen(&conflict.listen, port));
+            config["api"] = api;
+        } else {
+            let Some(mut inbound) = configured
+                .iter()
+                .find(|i| i.get("tag").and_then(Value::as_str) == Some(conflict.tag.as_str()))
+                .cloned()
+            else {
+                continue;
+            };
+            inbound["port"] = json!(port);
+            if !config["inbounds"].is_array() {
+                config["inbounds"] = json!([]);
+            }
+            if let Some(inbounds) = config["inbounds"].as_array_mut() {
+                inbounds.push(inbound);
+            }
+        }
+        changes.push(PortReassignment {
+            tag: conflict.tag.clone(),
+            from: conflict.port,
+            to: port,
+        });
+    }
+
+    // 内容未变时不重写
+    let path = paths::get_confdir().join(OVERRIDE_CONFIG);
+    if changes.is_empty() {
+        if path.exists() {
+            fs::remove_file(&path)?;
+        }
+    } else if previous.as_ref() != Some(&config) {
+        json::save_json(&path, &config)?;
+    }
+    Ok(changes)
+}
+
+/// 上次为该 tag 改用的端口
+fn previous_port(previous: &Value, tag: &str) -> Option<u16> {
+    if tag == API_TAG {
+        return parse_api_listen(previous).map(|(_, port)| port);
+    }
+    previous
+        .get("inbounds")?
+        .as_array()?
+        .iter()
+        .find(|i| i.get("tag").and_then(Value::as_str) == Some(tag))?
+        .get("port")?
+        .as_u64()
+        .and_then(|port| u16::try_from(port).ok())
+}
+
+/// 从 start 之后查找空闲端口；不再交给系统分配随机端口，
+/// 否则释放端口到 xray 监听之间它可能被其他程序占用
+fn pick_free(listen: &str, start: u16, used: &[u16]) -> Option<u16> {
+    (start.saturating_add(1)..=start.saturating_add(SEARCH_RANGE))
+        .find(|port| !used.contains(port) && is_free(listen, *port))
+}
+
+/// 监听地址与端口，IPv6 地址加方括号
+fn format_listen(listen: &str, port: u16) -> String {
+    if listen.contains(':') {
+        format!("[{}]:{}", listen, port)
+    } else {
+        format!("{}:{}", listen, port)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_previous_ports_from_override() {
+        let previous = json!({
+            "inbounds": [{ "tag": inbounds::SOCKS_TAG, "port": 10818 }],
+            "api": { "tag": API_TAG, "listen": format_listen("::1", 10086) }
+        });
+
+        assert_eq!(previous["api"]["listen"], "[::1]:10086");
+        assert_eq!(previous_port(&previous, API_TAG), Some(10086));
+        assert_eq!(previous_port(&previous, inbounds::SOCKS_TAG), Some(10818));
+        assert_eq!(previous_port(&previous, inbounds::HTTP_TAG), None);
+        assert_eq!(previous_port(&json!({}), API_TAG), None);
+    }
+}
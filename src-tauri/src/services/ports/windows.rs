@@ -0,0 +1,26 @@
+// Windows: 解析 netstat -ano 输出中监听该端口的 PID
+use crate::utils::process;
+use std::process::Command;
+
+pub fn find_listener_pid(port: u16) -> Option<u32> {
+    let output = process::configure_background(Command::new("netstat").arg("-ano"))
+        .output()
+        .ok()?;
+    let text = String::from_utf8_lossy(&output.stdout);
+    let suffix = format!(":{}", port);
+
+    // 行格式: TCP  127.0.0.1:10808  0.0.0.0:0  LISTENING  1234
+    // 状态列会被本地化，因此以远端地址为 0 端口判断监听
+    text.lines().find_map(|line| {
+        let fields: Vec<&str> = line.split_whitespace().collect();
+        let (proto, local, remote, pid) = (
+            fields.first()?,
+            fields.get(1)?,
+            fields.get(2)?,
+            fields.last()?,
+        );
+        (*proto == "TCP" && local.ends_with(&suffix) && remote.ends_with(":0"))
+            .then(|| pid.parse().ok())
+            .flatten()
+    })
+}
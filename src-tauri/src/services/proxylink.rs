@@ -1,69 +1,151 @@
-use crate::models::NodeInfo;
-use crate::utils::paths;
+use crate::models::{NodeInfo, Subscription};
+use crate::services::share_link::{self, ProxyNode};
+use crate::services::subscription;
+use crate::utils::{json, paths};
 use std::fs;
-use std::process::Command;
+use std::path::{Path, PathBuf};
 
 pub fn import_link(link: &str) -> Result<NodeInfo, Box<dyn std::error::Error>> {
-    let proxylink_path = paths::get_proxylink_path();
+    let node = share_link::parse(link)?;
     let outbounds_dir = paths::get_outbounds_dir();
 
     // 确保输出目录存在
     fs::create_dir_all(&outbounds_dir)?;
 
-    let output = Command::new(&proxylink_path)
-        .arg("-parse")
-        .arg(link)
-        .arg("-format")
-        .arg("xray")
-        .arg("-auto")
-        .arg("-dir")
-        .arg(&outbounds_dir)
-        .current_dir(&outbounds_dir) // 设置工作目录
-        .output()?;
-
-    if !output.status.success() {
-        let stderr = String::from_utf8_lossy(&output.stderr);
-        return Err(format!("proxylink failed: {}", stderr).into());
+    write_node(&outbounds_dir, &outbounds_dir, &node)
+}
+
+/// 将节点写入 dir 下的独立 json 文件，返回的 file_name 相对于 base_dir
+pub fn write_node(
+    base_dir: &Path,
+    dir: &Path,
+    node: &ProxyNode,
+) -> Result<NodeInfo, Box<dyn std::error::Error>> {
+    let stem = unique_file_stem(dir, &sanitize_file_name(&node.name));
+    let path = dir.join(format!("{}.json", stem));
+
+    write_outbound(&path, &node.to_xray_outbound("proxy"))?;
+
+    Ok(node_info(base_dir, &path, node))
+}
+
+/// 写入单个出站配置: { "outbounds": [outbound] }
+pub fn write_outbound(
+    path: &Path,
+    outbound: &serde_json::Value,
+) -> Result<(), Box<dyn std::error::Error>> {
+    json::save_json(path, &serde_json::json!({ "outbounds": [outbound] }))
+}
+
+/// 读取节点文件中的第一个出站，兼容 { "outbounds": [...] } 包装与单个出站对象
+pub fn read_outbound(node_file: &str) -> Result<serde_json::Value, Box<dyn std::error::Error>> {
+    let path = paths::get_outbounds_dir().join(node_file);
+    let json: serde_json::Value = serde_json::from_str(&fs::read_to_string(&path)?)?;
+
+    let outbound = json
+        .get("outbounds")
+        .and_then(|v| v.as_array())
+        .and_then(|a| a.first())
+        .cloned()
+        .unwrap_or(json);
+    if outbound.get("protocol").is_none() {
+        return Err("节点文件中没有出站配置".into());
+    }
+    Ok(outbound)
+}
+
+/// 节点文件对应的 NodeInfo，file_name 相对于 base_dir
+pub fn node_info(base_dir: &Path, path: &Path, node: &ProxyNode) -> NodeInfo {
+    NodeInfo {
+        name: path
+            .file_stem()
+            .map(|s| s.to_string_lossy().to_string())
+            .unwrap_or_default(),
+        protocol: node.protocol().to_string(),
+        address: node.address.clone(),
+        port: node.port,
+        file_name: path
+            .strip_prefix(base_dir)
+            .unwrap_or(path)
+            .to_string_lossy()
+            .to_string(),
     }
+}
+
+/// 将节点名转换为合法的文件名
+pub fn sanitize_file_name(name: &str) -> String {
+    let cleaned: String = name
+        .chars()
+        .map(|c| match c {
+            '/' | '\\' | ':' | '*' | '?' | '"' | '<' | '>' | '|' => '_',
+            c if c.is_control() => '_',
+            c => c,
+        })
+        .take(80)
+        .collect();
+
+    let cleaned = cleaned.trim().trim_matches('.').to_string();
+    if cleaned.is_empty() {
+        "node".to_string()
+    } else {
+        cleaned
+    }
+}
 
-    // 解析输出获取节点信息
-    let _stdout = String::from_utf8_lossy(&output.stdout);
+/// 同名文件已存在时追加序号
+pub fn unique_file_stem(dir: &Path, stem: &str) -> String {
+    if !dir.join(format!("{}.json", stem)).exists() {
+        return stem.to_string();
+    }
 
-    Ok(NodeInfo {
-        name: "Imported".to_string(),
-        protocol: "unknown".to_string(),
-        address: "".to_string(),
-        port: 0,
-        file_name: "imported.json".to_string(),
-    })
+    (2..)
+        .map(|i| format!("{}_{}", stem, i))
+        .find(|candidate| !dir.join(format!("{}.json", candidate)).exists())
+        .unwrap_or_else(|| stem.to_string())
 }
 
-pub fn import_subscription(
+/// 导入订阅：保存订阅信息后立即拉取一次
+pub async fn import_subscription(
     url: &str,
     sub_name: &str,
 ) -> Result<Vec<NodeInfo>, Box<dyn std::error::Error>> {
-    let proxylink_path = paths::get_proxylink_path();
-    let outbounds_dir = paths::get_outbounds_dir();
+    subscription::store::upsert(Subscription::new(sub_name, url))?;
+    Ok(subscription::refresh(sub_name).await?.nodes)
+}
 
-    // 创建订阅专用目录: outbounds/sub_订阅名称
-    let sub_dir = outbounds_dir.join(format!("sub_{}", sub_name));
-    fs::create_dir_all(&sub_dir)?;
-
-    let output = Command::new(&proxylink_path)
-        .arg("-sub")
-        .arg(url)
-        .arg("-format")
-        .arg("xray")
-        .arg("-dir")
-        .arg(&sub_dir)
-        .current_dir(&sub_dir) // 设置工作目录
-        .output()?;
-
-    if !output.status.success() {
-        let stderr = String::from_utf8_lossy(&output.stderr);
-        return Err(format!("proxylink subscription failed: {}", stderr).into());
+/// dir 下（含子目录）的全部节点文件，路径相对于 outbounds 目录并排序
+pub fn node_files(dir: &Path) -> Vec<String> {
+    fn collect(dir: &Path, files: &mut Vec<PathBuf>) {
+        let Ok(entries) = fs::read_dir(dir) else {
+            return;
+        };
+        for path in entries.flatten().map(|entry| entry.path()) {
+            if path.is_dir() {
+                collect(&path, files);
+            } else if path.extension().is_some_and(|ext| ext == "json") {
+                files.push(path);
+            }
+        }
     }
 
-    // 返回空列表，实际节点会被 list_nodes 读取
-    Ok(Vec::new())
+    let outbounds_dir = paths::get_outbounds_dir();
+    let mut files = Vec::new();
+    collect(dir, &mut files);
+
+    let mut files: Vec<String> = files
+        .iter()
+        .map(|path| {
+            path.strip_prefix(&outbounds_dir)
+                .unwrap_or(path)
+                .to_string_lossy()
+                .to_string()
+        })
+        .collect();
+    files.sort();
+    files
+}
+
+/// 订阅专用目录: outbounds/sub_订阅名称
+pub fn subscription_dir(sub_name: &str) -> PathBuf {
+    paths::get_outbounds_dir().join(format!("sub_{}", sanitize_file_name(sub_name)))
 }
@@ -0,0 +1,54 @@
+// 当前选中的节点：内存中缓存，并持久化到 selected_node.txt
+use crate::utils::paths;
+use once_cell::sync::Lazy;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::Mutex;
+
+// 全局存储选中的节点
+static SELECTED_NODE: Lazy<Mutex<Option<String>>> = Lazy::new(|| Mutex::new(None));
+
+fn selection_file() -> PathBuf {
+    paths::get_state_dir().join("selected_node.txt")
+}
+
+pub fn set(node_file: &str) -> Result<(), Box<dyn std::error::Error>> {
+    let mut selected = SELECTED_NODE.lock().unwrap();
+    *selected = Some(node_file.to_string());
+
+    // 保存到配置文件
+    fs::write(selection_file(), node_file)?;
+
+    Ok(())
+}
+
+pub fn get() -> Option<String> {
+    let mut selected = SELECTED_NODE.lock().unwrap();
+    // 先尝试从内存读取
+    if selected.is_some() {
+        return selected.clone();
+    }
+
+    // 从文件读取并更新内存
+    let node = fs::read_to_string(selection_file())
+        .ok()?
+        .trim()
+        .to_string();
+    if node.is_empty() {
+        return None;
+    }
+    *selected = Some(node.clone());
+    Some(node)
+}
+
+/// 清除选中的节点（节点已被删除时）
+pub fn clear() -> Result<(), Box<dyn std::error::Error>> {
+    *SELECTED_NODE.lock().unwrap() = None;
+
+    let path = selection_file();
+    if path.exists() {
+        fs::remove_file(path)?;
+    }
+
+    Ok(())
+}
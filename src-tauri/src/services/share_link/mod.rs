@@ -0,0 +1,29 @@
+// 分享链接解析：将 vmess:// vless:// 等链接解析为类型化的节点模型，
+// 再由模型生成 Xray 出站配置
+mod model;
+mod protocols;
+mod uri;
+
+pub use model::{ProtocolSettings, ProxyNode, StreamSettings};
+pub use uri::decode_base64;
+
+/// 解析单条分享链接
+pub fn parse(link: &str) -> Result<ProxyNode, Box<dyn std::error::Error>> {
+    let link = link.trim();
+    let scheme = link
+        .split_once("://")
+        .map(|(scheme, _)| scheme.to_lowercase())
+        .ok_or("不是有效的分享链接")?;
+
+    match scheme.as_str() {
+        "vmess" => protocols::parse_vmess(link),
+        "vless" => protocols::parse_vless(link),
+        "trojan" => protocols::parse_trojan(link),
+        "ss" => protocols::parse_shadowsocks(link),
+        "socks" | "socks5" => protocols::parse_socks(link),
+        "http" | "https" => protocols::parse_http(link),
+        "hysteria2" | "hy2" => protocols::parse_hysteria2(link),
+        "wireguard" | "wg" => protocols::parse_wireguard(link),
+        _ => Err(format!("不支持的协议: {}", scheme).into()),
+    }
+}
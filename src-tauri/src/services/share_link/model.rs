@@ -0,0 +1,330 @@
+use serde_json::{Map, Value, json};
+
+/// 解析后的代理节点
+#[derive(Debug, Clone, PartialEq)]
+pub struct ProxyNode {
+    pub name: String,
+    pub address: String,
+    pub port: u16,
+    pub settings: ProtocolSettings,
+    pub stream: StreamSettings,
+}
+
+/// 各协议的认证与协议参数
+#[derive(Debug, Clone, PartialEq)]
+pub enum ProtocolSettings {
+    Vmess {
+        id: String,
+        alter_id: u16,
+        security: String,
+    },
+    Vless {
+        id: String,
+        flow: String,
+        encryption: String,
+    },
+    Trojan {
+        password: String,
+    },
+    Shadowsocks {
+        method: String,
+        password: String,
+    },
+    Socks {
+        user: Option<String>,
+        pass: Option<String>,
+    },
+    Http {
+        user: Option<String>,
+        pass: Option<String>,
+    },
+    Hysteria2 {
+        password: String,
+        obfs: Option<String>,
+        obfs_password: Option<String>,
+    },
+    Wireguard {
+        secret_key: String,
+        public_key: String,
+        pre_shared_key: Option<String>,
+        local_address: Vec<String>,
+        mtu: Option<u32>,
+        reserved: Vec<u8>,
+    },
+}
+
+/// 传输层与 TLS 设置
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct StreamSettings {
+    /// tcp / ws / grpc / httpupgrade / xhttp / kcp / h2
+    pub network: String,
+    /// none / tls / reality
+    pub security: String,
+    pub sni: String,
+    pub alpn: Vec<String>,
+    pub fingerprint: String,
+    pub allow_insecure: bool,
+    pub public_key: String,
+    pub short_id: String,
+    pub spider_x: String,
+    pub host: String,
+    pub path: String,
+    pub header_type: String,
+    pub service_name: String,
+    pub authority: String,
+    pub mode: String,
+    pub seed: String,
+}
+
+impl ProxyNode {
+    /// 对应的 Xray 出站协议名
+    pub fn protocol(&self) -> &'static str {
+        match self.settings {
+            ProtocolSettings::Vmess { .. } => "vmess",
+            ProtocolSettings::Vless { .. } => "vless",
+            ProtocolSettings::Trojan { .. } => "trojan",
+            ProtocolSettings::Shadowsocks { .. } => "shadowsocks",
+            ProtocolSettings::Socks { .. } => "socks",
+            ProtocolSettings::Http { .. } => "http",
+            ProtocolSettings::Hysteria2 { .. } => "hysteria",
+            ProtocolSettings::Wireguard { .. } => "wireguard",
+        }
+    }
+
+    /// 生成 Xray 出站配置
+    pub fn to_xray_outbound(&self, tag: &str) -> Value {
+        let mut outbound = json!({
+            "tag": tag,
+            "protocol": self.protocol(),
+            "settings": self.xray_settings(),
+        });
+
+        if let Some(stream) = self.xray_stream_settings() {
+            outbound["streamSettings"] = stream;
+        }
+
+        outbound
+    }
+
+    fn xray_settings(&self) -> Value {
+        let address = &self.address;
+        let port = self.port;
+
+        match &self.settings {
+            ProtocolSettings::Vmess {
+                id,
+                alter_id,
+                security,
+            } => json!({
+                "vnext": [{
+                    "address": address,
+                    "port": port,
+                    "users": [{ "id": id, "alterId": alter_id, "security": security }]
+                }]
+            }),
+            ProtocolSettings::Vless {
+                id,
+                flow,
+                encryption,
+            } => {
+                let mut user = json!({ "id": id, "encryption": encryption });
+                if !flow.is_empty() {
+                    user["flow"] = json!(flow);
+                }
+                json!({ "vnext": [{ "address": address, "port": port, "users": [user] }] })
+            }
+            ProtocolSettings::Trojan { password } => json!({
+                "servers": [{ "address": address, "port": port, "password": password }]
+            }),
+            ProtocolSettings::Shadowsocks { method, password } => json!({
+                "servers": [{
+                    "address": address,
+                    "port": port,
+                    "method": method,
+                    "password": password
+                }]
+            }),
+            ProtocolSettings::Socks { user, pass } | ProtocolSettings::Http { user, pass } => {
+                let mut server = json!({ "address": address, "port": port });
+                if let Some(user) = user {
+                    server["users"] =
+                        json!([{ "user": user, "pass": pass.clone().unwrap_or_default() }]);
+                }
+                json!({ "servers": [server] })
+            }
+            ProtocolSettings::Hysteria2 { .. } => json!({
+                "version": 2,
+                "address": address,
+                "port": port
+            }),
+            ProtocolSettings::Wireguard {
+                secret_key,
+                public_key,
+                pre_shared_key,
+                local_address,
+                mtu,
+                reserved,
+            } => {
+                let mut peer = json!({
+                    "publicKey": public_key,
+                    "endpoint": format_endpoint(address, port),
+                });
+                if let Some(psk) = pre_shared_key {
+                    peer["preSharedKey"] = json!(psk);
+                }
+
+                let mut settings = json!({
+                    "secretKey": secret_key,
+                    "address": local_address,
+                    "peers": [peer],
+                });
+                if let Some(mtu) = mtu {
+                    settings["mtu"] = json!(mtu);
+                }
+                if !reserved.is_empty() {
+                    settings["reserved"] = json!(reserved);
+                }
+                settings
+            }
+        }
+    }
+
+    fn xray_stream_settings(&self) -> Option<Value> {
+        let s = &self.stream;
+        let mut stream = Map::new();
+
+        match &self.settings {
+            // WireGuard 不使用 streamSettings
+            ProtocolSettings::Wireguard { .. } => return None,
+            ProtocolSettings::Hysteria2 {
+                password,
+                obfs,
+                obfs_password,
+            } => {
+                let mut hysteria = json!({ "version": 2, "auth": password });
+                if let (Some(obfs), Some(obfs_password)) = (obfs, obfs_password) {
+                    hysteria["obfs"] = json!({ "type": obfs, "password": obfs_password });
+                }
+                stream.insert("network".into(), json!("hysteria"));
+                stream.insert("hysteriaSettings".into(), hysteria);
+            }
+            _ => {
+                let network = if s.network.is_empty() {
+                    "tcp"
+                } else {
+                    &s.network
+                };
+                stream.insert("network".into(), json!(network));
+                if let Some((key, value)) = self.transport_settings(network) {
+                    stream.insert(key.into(), value);
+                }
+            }
+        }
+
+        match s.security.as_str() {
+            "tls" => {
+                let mut tls = json!({ "allowInsecure": s.allow_insecure });
+                set_if_present(&mut tls, "serverName", &s.sni);
+                set_if_present(&mut tls, "fingerprint", &s.fingerprint);
+                if !s.alpn.is_empty() {
+                    tls["alpn"] = json!(s.alpn);
+                }
+                stream.insert("security".into(), json!("tls"));
+                stream.insert("tlsSettings".into(), tls);
+            }
+            "reality" => {
+                let mut reality = json!({
+                    "publicKey": s.public_key,
+                    "fingerprint": if s.fingerprint.is_empty() { "chrome" } else { &s.fingerprint },
+                });
+                set_if_present(&mut reality, "serverName", &s.sni);
+                set_if_present(&mut reality, "shortId", &s.short_id);
+                set_if_present(&mut reality, "spiderX", &s.spider_x);
+                stream.insert("security".into(), json!("reality"));
+                stream.insert("realitySettings".into(), reality);
+            }
+            _ => {}
+        }
+
+        Some(Value::Object(stream))
+    }
+
+    /// 传输层配置，返回 (字段名, 配置)
+    fn transport_settings(&self, network: &str) -> Option<(&'static str, Value)> {
+        let s = &self.stream;
+        match network {
+            "ws" => {
+                let mut ws = json!({ "path": path_or_root(&s.path) });
+                set_if_present(&mut ws, "host", &s.host);
+                Some(("wsSettings", ws))
+            }
+            "grpc" => {
+                let mut grpc = json!({
+                    "serviceName": s.service_name,
+                    "multiMode": s.mode == "multi",
+                });
+                set_if_present(&mut grpc, "authority", &s.authority);
+                Some(("grpcSettings", grpc))
+            }
+            "httpupgrade" => {
+                let mut hu = json!({ "path": path_or_root(&s.path) });
+                set_if_present(&mut hu, "host", &s.host);
+                Some(("httpupgradeSettings", hu))
+            }
+            "xhttp" | "splithttp" => {
+                let mut xhttp = json!({ "path": path_or_root(&s.path) });
+                set_if_present(&mut xhttp, "host", &s.host);
+                set_if_present(&mut xhttp, "mode", &s.mode);
+                Some(("xhttpSettings", xhttp))
+            }
+            "h2" | "http" => {
+                let mut h2 = json!({ "path": path_or_root(&s.path) });
+                if !s.host.is_empty() {
+                    h2["host"] = json!(s.host.split(',').map(str::trim).collect::<Vec<_>>());
+                }
+                Some(("httpSettings", h2))
+            }
+            "kcp" | "mkcp" => {
+                let header_type = if s.header_type.is_empty() {
+                    "none"
+                } else {
+                    &s.header_type
+                };
+                let mut kcp = json!({ "header": { "type": header_type } });
+                set_if_present(&mut kcp, "seed", &s.seed);
+                Some(("kcpSettings", kcp))
+            }
+            "tcp" | "raw" if s.header_type == "http" => {
+                let mut request = json!({ "path": [path_or_root(&s.path)] });
+                if !s.host.is_empty() {
+                    request["headers"] = json!({
+                        "Host": s.host.split(',').map(str::trim).collect::<Vec<_>>()
+                    });
+                }
+                Some((
+                    "tcpSettings",
+                    json!({ "header": { "type": "http", "request": request } }),
+                ))
+            }
+            _ => None,
+        }
+    }
+}
+
+fn set_if_present(target: &mut Value, key: &str, value: &str) {
+    if !value.is_empty() {
+        target[key] = json!(value);
+    }
+}
+
+fn path_or_root(path: &str) -> &str {
+    if path.is_empty() { "/" } else { path }
+}
+
+fn format_endpoint(address: &str, port: u16) -> String {
+    if address.contains(':') {
+        format!("[{}]:{}", address, port)
+    } else {
+        format!("{}:{}", address, port)
+    }
+}
@@ -0,0 +1,263 @@
+use base64::Engine;
+use base64::alphabet;
+use base64::engine::{DecodePaddingMode, GeneralPurpose, GeneralPurposeConfig};
+use percent_encoding::percent_decode_str;
+use std::collections::HashMap;
+
+const LENIENT: GeneralPurposeConfig =
+    GeneralPurposeConfig::new().with_decode_padding_mode(DecodePaddingMode::Indifferent);
+const STANDARD: GeneralPurpose = GeneralPurpose::new(&alphabet::STANDARD, LENIENT);
+const URL_SAFE: GeneralPurpose = GeneralPurpose::new(&alphabet::URL_SAFE, LENIENT);
+
+/// 拆分后的分享链接
+///
+/// 不使用 `url` crate 是因为分享链接中的 userinfo 常含有 base64 的 `/`、`+`、`=`，
+/// 按标准 URL 解析会被截断。
+#[derive(Debug, Default)]
+pub struct ShareUri {
+    pub scheme: String,
+    /// 原始 userinfo（未解码）
+    pub user_info: Option<String>,
+    pub host: String,
+    pub port: Option<u16>,
+    /// 查询参数，键统一为小写，值已解码
+    pub query: HashMap<String, String>,
+    /// 备注（已解码）
+    pub fragment: String,
+}
+
+impl ShareUri {
+    pub fn parse(link: &str) -> Result<Self, Box<dyn std::error::Error>> {
+        let link = link.trim();
+        let (scheme, rest) = link.split_once("://").ok_or("缺少协议头")?;
+
+        let (rest, fragment) = match rest.split_once('#') {
+            Some((r, f)) => (r, decode(f)),
+            None => (rest, String::new()),
+        };
+        let (rest, query) = match rest.split_once('?') {
+            Some((r, q)) => (r, parse_query(q)),
+            None => (rest, HashMap::new()),
+        };
+
+        // userinfo 可能包含 '/'，因此以最后一个 '@' 作为分界
+        let (user_info, host_part) = match rest.rfind('@') {
+            Some(pos) => (Some(rest[..pos].to_string()), &rest[pos + 1..]),
+            None => (None, rest),
+        };
+        // 忽略路径部分（如 hysteria2 链接中的 "/?"）
+        let authority = host_part.split('/').next().unwrap_or_default();
+        let (host, port) = split_host_port(authority)?;
+
+        Ok(Self {
+            scheme: scheme.to_lowercase(),
+            user_info,
+            host,
+            port,
+            query,
+            fragment,
+        })
+    }
+
+    /// 获取查询参数，空字符串视为不存在
+    pub fn param(&self, key: &str) -> Option<&str> {
+        self.query
+            .get(key)
+            .map(String::as_str)
+            .filter(|v| !v.is_empty())
+    }
+
+    pub fn param_or(&self, key: &str, default: &str) -> String {
+        self.param(key).unwrap_or(default).to_string()
+    }
+
+    /// 解析布尔型参数（1/true/yes）
+    pub fn flag(&self, key: &str) -> bool {
+        matches!(
+            self.param(key).map(str::to_lowercase).as_deref(),
+            Some("1" | "true" | "yes")
+        )
+    }
+
+    pub fn require_port(&self) -> Result<u16, Box<dyn std::error::Error>> {
+        self.port.ok_or_else(|| "缺少端口".into())
+    }
+
+    /// 解码后的 userinfo
+    pub fn user_info_decoded(&self) -> Option<String> {
+        self.user_info.as_deref().map(decode)
+    }
+}
+
+/// 拆分 host:port，支持 IPv6 `[::1]:443`
+fn split_host_port(authority: &str) -> Result<(String, Option<u16>), Box<dyn std::error::Error>> {
+    if authority.is_empty() {
+        return Err("缺少服务器地址".into());
+    }
+
+    let (host, port) = if let Some(rest) = authority.strip_prefix('[') {
+        let (host, after) = rest.split_once(']').ok_or("IPv6 地址格式错误")?;
+        (host, after.strip_prefix(':'))
+    } else {
+        match authority.rsplit_once(':') {
+            Some((h, p)) => (h, Some(p)),
+            None => (authority, None),
+        }
+    };
+
+    let port = match port {
+        Some(p) if !p.is_empty() => Some(p.parse::<u16>().map_err(|_| format!("端口无效: {}", p))?),
+        _ => None,
+    };
+
+    Ok((decode(host), port))
+}
+
+fn parse_query(query: &str) -> HashMap<String, String> {
+    query
+        .split('&')
+        .filter(|pair| !pair.is_empty())
+        .map(|pair| match pair.split_once('=') {
+            Some((k, v)) => (decode(k).to_lowercase(), decode(v)),
+            None => (decode(pair).to_lowercase(), String::new()),
+        })
+        .collect()
+}
+
+/// 百分号解码
+pub fn decode(value: &str) -> String {
+    percent_decode_str(value).decode_utf8_lossy().into_owned()
+}
+
+/// 宽松的 base64 解码：兼容标准/URL 安全字母表，填充可有可无，忽略空白
+pub fn decode_base64(value: &str) -> Option<String> {
+    let cleaned: String = value.chars().filter(|c| !c.is_whitespace()).collect();
+    let bytes = STANDARD
+        .decode(&cleaned)
+        .or_else(|_| URL_SAFE.decode(&cleaned))
+        .ok()?;
+    String::from_utf8(bytes).ok()
+}
+
+/// 逗号分隔的列表
+pub fn split_list(value: Option<&str>) -> Vec<String> {
+    value
+        .map(|v| {
+            v.split(',')
+                .map(str::trim)
+                .filter(|s| !s.is_empty())
+                .map(str::to_string)
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn splits_host_and_port() {
+        let cases = [
+            ("example.com:443", "example.com", Some(443)),
+            ("example.com", "example.com", None),
+            ("example.com:", "example.com", None),
+            ("1.2.3.4:8388", "1.2.3.4", Some(8388)),
+            ("[2001:db8::1]:443", "2001:db8::1", Some(443)),
+            ("[2001:db8::1]", "2001:db8::1", None),
+            ("[::1]:", "::1", None),
+            ("%E4%B8%AD.example.com:80", "中.example.com", Some(80)),
+        ];
+
+        for (authority, host, port) in cases {
+            let parsed = split_host_port(authority).unwrap();
+            assert_eq!(parsed, (host.to_string(), port), "{}", authority);
+        }
+    }
+
+    #[test]
+    fn rejects_invalid_authority() {
+        let cases = [
+            ("", "缺少服务器地址"),
+            ("example.com:http", "端口无效"),
+            ("example.com:65536", "端口无效"),
+            ("[2001:db8::1:443", "IPv6"),
+        ];
+
+        for (authority, expected) in cases {
+            let err = split_host_port(authority).expect_err(authority).to_string();
+            assert!(err.contains(expected), "{}: {}", authority, err);
+        }
+    }
+
+    #[test]
+    fn parses_share_uri() {
+        let uri = ShareUri::parse(
+            "VLESS://id%40x@[::1]:443/path?Type=ws&path=%2Fws%3Fed%3D2048&empty=&flag#%E8%8A%82%E7%82%B9%201",
+        )
+        .unwrap();
+
+        assert_eq!(uri.scheme, "vless");
+        assert_eq!(uri.user_info.as_deref(), Some("id%40x"));
+        assert_eq!(uri.user_info_decoded().as_deref(), Some("id@x"));
+        assert_eq!(uri.host, "::1");
+        assert_eq!(uri.port, Some(443));
+        assert_eq!(uri.param("type"), Some("ws"));
+        assert_eq!(uri.param("path"), Some("/ws?ed=2048"));
+        assert_eq!(uri.param("empty"), None);
+        assert_eq!(uri.param("flag"), None);
+        assert_eq!(uri.fragment, "节点 1");
+    }
+
+    #[test]
+    fn user_info_splits_on_last_at() {
+        // base64 userinfo 中可能出现 '/'，'@' 也可能出现在未编码的密码中
+        let uri = ShareUri::parse("trojan://a/b@c@example.com:443").unwrap();
+        assert_eq!(uri.user_info.as_deref(), Some("a/b@c"));
+        assert_eq!(uri.host, "example.com");
+    }
+
+    #[test]
+    fn parses_flags() {
+        let uri = ShareUri::parse("x://h:1?a=1&b=true&c=YES&d=0&e=no").unwrap();
+        let flags: Vec<bool> = ["a", "b", "c", "d", "e", "f"]
+            .iter()
+            .map(|k| uri.flag(k))
+            .collect();
+        assert_eq!(flags, [true, true, true, false, false, false]);
+    }
+
+    #[test]
+    fn decodes_lenient_base64() {
+        let cases = [
+            ("dXNlcjpwYXNz", Some("user:pass")),
+            (
+                "YWVzLTEyOC1nY206cHdAWzo6MV06ODM4OA==",
+                Some("aes-128-gcm:pw@[::1]:8388"),
+            ),
+            (
+                "YWVzLTEyOC1nY206cHdAWzo6MV06ODM4OA",
+                Some("aes-128-gcm:pw@[::1]:8388"),
+            ),
+            ("YWVz\nLTI1\r\nNi1n Y20=", Some("aes-256-gcm")),
+            // '+' '/' 与 '-' '_' 两种字母表
+            ("Pz8-Pj4=", Some("??>>>")),
+            ("Pz8+Pj4", Some("??>>>")),
+            ("!!!", None),
+            ("YQ=a", None),
+            // 非 UTF-8
+            ("//79", None),
+        ];
+
+        for (input, expected) in cases {
+            assert_eq!(decode_base64(input).as_deref(), expected, "{:?}", input);
+        }
+    }
+
+    #[test]
+    fn splits_lists() {
+        assert_eq!(split_list(Some(" h2, http/1.1 ,,")), vec!["h2", "http/1.1"]);
+        assert!(split_list(Some("")).is_empty());
+        assert!(split_list(None).is_empty());
+    }
+}
@@ -0,0 +1,179 @@
+// Clash / Mihomo YAML 订阅：读取 proxies 列表
+use super::{get_bool, get_opt_str, get_port, get_str, get_str_list};
+use crate::services::share_link::{ProtocolSettings, ProxyNode, StreamSettings};
+use serde_json::Value;
+use std::error::Error;
+
+pub fn parse(content: &str) -> Result<Vec<ProxyNode>, Box<dyn Error>> {
+    let doc: Value = serde_yaml::from_str(content)?;
+    let proxies = doc
+        .get("proxies")
+        .and_then(Value::as_array)
+        .ok_or("Clash 配置缺少 proxies")?;
+
+    Ok(proxies.iter().filter_map(|p| convert(p).ok()).collect())
+}
+
+fn convert(proxy: &Value) -> Result<ProxyNode, Box<dyn Error>> {
+    let kind = get_str(proxy, "type").to_lowercase();
+    let address = get_str(proxy, "server");
+    let port = get_port(proxy, "port").ok_or("缺少端口")?;
+    if address.is_empty() {
+        return Err("缺少服务器地址".into());
+    }
+
+    let mut stream = stream_settings(proxy);
+    let settings = match kind.as_str() {
+        "vmess" => ProtocolSettings::Vmess {
+            id: get_str(proxy, "uuid"),
+            alter_id: get_str(proxy, "alterId").parse().unwrap_or(0),
+            security: get_opt_str(proxy, "cipher").unwrap_or_else(|| "auto".to_string()),
+        },
+        "vless" => ProtocolSettings::Vless {
+            id: get_str(proxy, "uuid"),
+            flow: get_str(proxy, "flow"),
+            encryption: "none".to_string(),
+        },
+        "trojan" => {
+            // Clash 的 trojan 始终使用 TLS
+            if stream.security == "none" {
+                stream.security = "tls".to_string();
+            }
+            ProtocolSettings::Trojan {
+                password: get_str(proxy, "password"),
+            }
+        }
+        "ss" => {
+            if let Some(plugin) = get_opt_str(proxy, "plugin") {
+                return Err(format!("Xray 不支持 Shadowsocks 插件: {}", plugin).into());
+            }
+            ProtocolSettings::Shadowsocks {
+                method: get_str(proxy, "cipher").to_lowercase(),
+                password: get_str(proxy, "password"),
+            }
+        }
+        "socks5" => ProtocolSettings::Socks {
+            user: get_opt_str(proxy, "username"),
+            pass: get_opt_str(proxy, "password"),
+        },
+        "http" => ProtocolSettings::Http {
+            user: get_opt_str(proxy, "username"),
+            pass: get_opt_str(proxy, "password"),
+        },
+        "hysteria2" => {
+            stream.security = "tls".to_string();
+            ProtocolSettings::Hysteria2 {
+                password: get_str(proxy, "password"),
+                obfs: get_opt_str(proxy, "obfs"),
+                obfs_password: get_opt_str(proxy, "obfs-password"),
+            }
+        }
+        "wireguard" => {
+            let mut local_address = Vec::new();
+            if let Some(ip) = get_opt_str(proxy, "ip") {
+                local_address.push(with_prefix(ip, 32));
+            }
+            if let Some(ip) = get_opt_str(proxy, "ipv6") {
+                local_address.push(with_prefix(ip, 128));
+            }
+            ProtocolSettings::Wireguard {
+                secret_key: get_str(proxy, "private-key"),
+                public_key: get_str(proxy, "public-key"),
+                pre_shared_key: get_opt_str(proxy, "pre-shared-key"),
+                local_address,
+                mtu: get_str(proxy, "mtu").parse().ok(),
+                reserved: get_str_list(proxy, "reserved")
+                    .iter()
+                    .filter_map(|v| v.parse().ok())
+                    .collect(),
+            }
+        }
+        _ => return Err(format!("不支持的类型: {}", kind).into()),
+    };
+
+    Ok(ProxyNode {
+        name: get_opt_str(proxy, "name").unwrap_or_else(|| format!("{}:{}", address, port)),
+        address,
+        port,
+        settings,
+        stream,
+    })
+}
+
+fn stream_settings(proxy: &Value) -> StreamSettings {
+    let reality = proxy.get("reality-opts");
+    let security = if reality.is_some() {
+        "reality"
+    } else if get_bool(proxy, "tls") {
+        "tls"
+    } else {
+        "none"
+    };
+
+    let mut stream = StreamSettings {
+        network: get_opt_str(proxy, "network").unwrap_or_else(|| "tcp".to_string()),
+        security: security.to_string(),
+        sni: get_opt_str(proxy, "servername")
+            .or_else(|| get_opt_str(proxy, "sni"))
+            .unwrap_or_default(),
+        alpn: get_str_list(proxy, "alpn"),
+        fingerprint: get_str(proxy, "client-fingerprint"),
+        allow_insecure: get_bool(proxy, "skip-cert-verify"),
+        ..Default::default()
+    };
+
+    if let Some(reality) = reality {
+        stream.public_key = get_str(reality, "public-key");
+        stream.short_id = get_str(reality, "short-id");
+    }
+
+    match stream.network.as_str() {
+        "ws" => {
+            if let Some(opts) = proxy.get("ws-opts") {
+                stream.path = get_str(opts, "path");
+                stream.host = opts
+                    .get("headers")
+                    .map(|h| get_str(h, "Host"))
+                    .unwrap_or_default();
+            }
+        }
+        "grpc" => {
+            if let Some(opts) = proxy.get("grpc-opts") {
+                stream.service_name = get_str(opts, "grpc-service-name");
+            }
+        }
+        "h2" => {
+            if let Some(opts) = proxy.get("h2-opts") {
+                stream.path = get_str(opts, "path");
+                stream.host = get_str_list(opts, "host").join(",");
+            }
+        }
+        // Clash 的 http 网络即 TCP + HTTP 伪装
+        "http" => {
+            stream.network = "tcp".to_string();
+            stream.header_type = "http".to_string();
+            if let Some(opts) = proxy.get("http-opts") {
+                stream.path = get_str_list(opts, "path")
+                    .into_iter()
+                    .next()
+                    .unwrap_or_default();
+                stream.host = opts
+                    .get("headers")
+                    .map(|h| get_str_list(h, "Host").join(","))
+                    .unwrap_or_default();
+            }
+        }
+        _ => {}
+    }
+
+    stream
+}
+
+/// 为不带前缀长度的地址补全 /32 或 /128
+fn with_prefix(ip: String, prefix: u8) -> String {
+    if ip.contains('/') {
+        ip
+    } else {
+        format!("{}/{}", ip, prefix)
+    }
+}
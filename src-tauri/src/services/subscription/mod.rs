@@ -0,0 +1,520 @@
+// 订阅获取与解析：拉取订阅内容，自动识别格式并转换为节点模型
+mod clash;
+pub mod scheduler;
+mod sing_box;
+mod sip008;
+pub mod store;
+pub mod sync;
+pub mod usage;
+
+use crate::models::{SubscriptionDiff, SubscriptionUserinfo};
+use crate::services::share_link::{self, ProxyNode};
+use crate::services::{groups, proxylink};
+use crate::utils::time;
+use once_cell::sync::Lazy;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::error::Error;
+use std::fs;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tauri::async_runtime::Mutex as AsyncMutex;
+
+/// 默认 User-Agent，多数机场据此返回通用的 base64 链接列表
+pub const DEFAULT_USER_AGENT: &str = "v2rayN/7.0";
+
+const FETCH_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// 订阅内容格式
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SubscriptionFormat {
+    /// base64 编码的链接列表
+    Base64,
+    /// 明文链接列表，每行一条
+    UriList,
+    /// Clash / Mihomo YAML
+    Clash,
+    /// sing-box JSON
+    SingBox,
+    /// Shadowsocks SIP008 JSON
+    Sip008,
+}
+
+/// 拉取到的订阅内容及响应元数据
+#[derive(Debug)]
+pub struct FetchedSubscription {
+    pub content: String,
+    /// subscription-userinfo 响应头中的流量与到期信息
+    pub userinfo: Option<SubscriptionUserinfo>,
+}
+
+/// 拉取订阅内容
+pub async fn fetch(
+    url: &str,
+    user_agent: Option<&str>,
+) -> Result<FetchedSubscription, Box<dyn Error>> {
+    let client = reqwest::Client::builder()
+        .timeout(FETCH_TIMEOUT)
+        .user_agent(user_agent.unwrap_or(DEFAULT_USER_AGENT))
+        .build()?;
+
+    let response = client.get(url).send().await?.error_for_status()?;
+    let userinfo = response
+        .headers()
+        .get("subscription-userinfo")
+        .and_then(|v| v.to_str().ok())
+        .and_then(usage::parse_userinfo);
+
+    Ok(FetchedSubscription {
+        content: response.text().await?,
+        userinfo,
+    })
+}
+
+/// 每个订阅的刷新锁，定时刷新、手动刷新与导入不会同时改写同一订阅目录
+static REFRESH_LOCKS: Lazy<Mutex<HashMap<String, Arc<AsyncMutex<()>>>>> =
+    Lazy::new(|| Mutex::new(HashMap::new()));
+
+fn refresh_lock(name: &str) -> Arc<AsyncMutex<()>> {
+    REFRESH_LOCKS
+        .lock()
+        .unwrap()
+        .entry(name.to_string())
+        .or_default()
+        .clone()
+}
+
+/// 按已保存的订阅设置拉取并增量同步节点，同时记录更新时间和错误
+pub async fn refresh(name: &str) -> Result<SubscriptionDiff, Box<dyn Error>> {
+    let lock = refresh_lock(name);
+    let _guard = lock.lock().await;
+    let subscription = store::get(name).ok_or_else(|| format!("订阅不存在: {}", name))?;
+
+    // Box<dyn Error> 不是 Send，转为字符串后结果才能跨越下面的 await
+    let (result, userinfo) =
+        match fetch(&subscription.url, subscription.user_agent.as_deref()).await {
+            Ok(fetched) => (
+                decode(&fetched.content)
+                    .map_err(|e| sync::SyncError::from(e.to_string()))
+                    .and_then(|nodes| sync::apply(name, &nodes)),
+                fetched.userinfo,
+            ),
+            Err(e) => (Err(e.to_string().into()), subscription.userinfo),
+        };
+
+    store::update(name, |s| {
+        let now = time::now_secs();
+        s.last_attempt = Some(now);
+        s.userinfo = userinfo;
+        match &result {
+            Ok(diff) => {
+                s.last_update = Some(now);
+                s.node_count = diff.nodes.len();
+                s.last_error = None;
+                s.failures = 0;
+            }
+            Err(e) => {
+                s.last_error = Some(e.error.clone());
+                s.failures = s.failures.saturating_add(1);
+            }
+        }
+    })?;
+
+    // 同步中途失败时，已完成的删除与改名同样需要迁移选中节点和记录
+    let (diff, error) = match result {
+        Ok(diff) => (diff, None),
+        Err(e) if e.is_partial() => (*e.diff, Some(e.error)),
+        Err(e) => return Err(e.error.into()),
+    };
+    let diff = migrate(diff).await?;
+    match error {
+        Some(e) => Err(e.into()),
+        None => Ok(diff),
+    }
+}
+
+/// 删除订阅及其节点目录，与刷新时删除节点一样清理延迟记录、节点组成员和选中节点
+pub async fn remove(name: &str) -> Result<(), Box<dyn Error>> {
+    let lock = refresh_lock(name);
+    let _guard = lock.lock().await;
+    store::remove(name)?;
+
+    let sub_dir = proxylink::subscription_dir(name);
+    let diff = SubscriptionDiff {
+        removed: proxylink::node_files(&sub_dir),
+        ..Default::default()
+    };
+    if sub_dir.exists() {
+        fs::remove_dir_all(&sub_dir)?;
+    }
+
+    migrate(diff).await?;
+    Ok(())
+}
+
+/// 按节点差异迁移记录、节点组与选中节点；
+/// 节点变化时可能需要重启核心，放到阻塞线程池中执行
+async fn migrate(mut diff: SubscriptionDiff) -> Result<SubscriptionDiff, Box<dyn Error>> {
+    let diff = tauri::async_runtime::spawn_blocking(move || {
+        sync::migrate_history(&diff)
+            .and_then(|_| groups::sync_nodes(&mut diff))
+            .and_then(|_| sync::reconcile_selection(&mut diff))
+            .map(|_| diff)
+            .map_err(|e| e.to_string())
+    })
+    .await??;
+    Ok(diff)
+}
+
+/// 识别订阅内容格式
+pub fn detect_format(content: &str) -> Option<SubscriptionFormat> {
+    let content = content.trim_start_matches('\u{feff}').trim();
+    if content.is_empty() {
+        return None;
+    }
+
+    if content.starts_with('{') {
+        let json: Value = serde_json::from_str(content).ok()?;
+        if json.get("outbounds").is_some_and(Value::is_array) {
+            return Some(SubscriptionFormat::SingBox);
+        }
+        if json.get("servers").is_some_and(Value::is_array) {
+            return Some(SubscriptionFormat::Sip008);
+        }
+        return None;
+    }
+
+    if content.lines().any(|line| line.starts_with("proxies:")) {
+        return Some(SubscriptionFormat::Clash);
+    }
+
+    if content.lines().any(is_share_link) {
+        return Some(SubscriptionFormat::UriList);
+    }
+
+    share_link::decode_base64(content)
+        .filter(|decoded| decoded.lines().any(is_share_link))
+        .map(|_| SubscriptionFormat::Base64)
+}
+
+/// 解析订阅内容，无法识别的单个节点会被跳过
+pub fn decode(content: &str) -> Result<Vec<ProxyNode>, Box<dyn Error>> {
+    let content = content.trim_start_matches('\u{feff}').trim();
+    let format = detect_format(content).ok_or("无法识别的订阅格式")?;
+
+    let nodes = match format {
+        SubscriptionFormat::Base64 => {
+            let decoded = share_link::decode_base64(content).ok_or("订阅内容不是有效的 base64")?;
+            parse_uri_list(&decoded)
+        }
+        SubscriptionFormat::UriList => parse_uri_list(content),
+        SubscriptionFormat::Clash => clash::parse(content)?,
+        SubscriptionFormat::SingBox => sing_box::parse(content)?,
+        SubscriptionFormat::Sip008 => sip008::parse(content)?,
+    };
+
+    if nodes.is_empty() {
+        return Err("订阅中没有可用的节点".into());
+    }
+    Ok(nodes)
+}
+
+fn is_share_link(line: &str) -> bool {
+    let line = line.trim();
+    line.split_once("://").is_some_and(|(scheme, _)| {
+        !scheme.is_empty() && scheme.chars().all(|c| c.is_ascii_alphanumeric())
+    })
+}
+
+fn parse_uri_list(content: &str) -> Vec<ProxyNode> {
+    content
+        .lines()
+        .map(str::trim)
+        .filter(|line| is_share_link(line))
+        .filter_map(|line| share_link::parse(line).ok())
+        .collect()
+}
+
+// ==================== JSON 取值辅助 ====================
+
+/// 读取字符串字段，数字会被转换为字符串
+fn get_str(value: &Value, key: &str) -> String {
+    match value.get(key) {
+        Some(Value::String(s)) => s.trim().to_string(),
+        Some(Value::Number(n)) => n.to_string(),
+        _ => String::new(),
+    }
+}
+
+fn get_opt_str(value: &Value, key: &str) -> Option<String> {
+    Some(get_str(value, key)).filter(|s| !s.is_empty())
+}
+
+fn get_port(value: &Value, key: &str) -> Option<u16> {
+    get_str(value, key).parse().ok()
+}
+
+fn get_bool(value: &Value, key: &str) -> bool {
+    match value.get(key) {
+        Some(Value::Bool(b)) => *b,
+        Some(Value::String(s)) => matches!(s.as_str(), "1" | "true"),
+        Some(Value::Number(n)) => n.as_u64() == Some(1),
+        _ => false,
+    }
+}
+
+/// 读取字符串列表，兼容数组与逗号分隔的字符串
+fn get_str_list(value: &Value, key: &str) -> Vec<String> {
+    match value.get(key) {
+        Some(Value::Array(items)) => items
+            .iter()
+            .filter_map(|v| match v {
+                Value::String(s) => Some(s.trim().to_string()),
+                Value::Number(n) => Some(n.to_string()),
+                _ => None,
+            })
+            .filter(|s| !s.is_empty())
+            .collect(),
+        Some(Value::String(s)) => s
+            .split(',')
+            .map(str::trim)
+            .filter(|s| !s.is_empty())
+            .map(str::to_string)
+            .collect(),
+        _ => Vec::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{NodeInfo, Subscription};
+    use std::path::Path;
+    use std::sync::Once;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpListener;
+
+    const USERINFO: &str = "upload=1024; download=2048; total=10240; expire=1893456000";
+
+    struct Fixture {
+        content: &'static str,
+        format: SubscriptionFormat,
+        /// (节点文件名, 协议, 地址, 端口)
+        nodes: &'static [(&'static str, &'static str, &'static str, u16)],
+    }
+
+    const FIXTURES: &[Fixture] = &[
+        Fixture {
+            content: include_str!("fixtures/base64.txt"),
+            format: SubscriptionFormat::Base64,
+            nodes: &[
+                ("Trojan A", "trojan", "t.example.com", 443),
+                ("SS B", "shadowsocks", "1.2.3.4", 8388),
+                ("VLESS C", "vless", "v.example.com", 443),
+            ],
+        },
+        Fixture {
+            content: include_str!("fixtures/uri_list.txt"),
+            format: SubscriptionFormat::UriList,
+            nodes: &[
+                ("香港 Trojan", "trojan", "trojan.example.com", 443),
+                ("VLESS IPv6", "vless", "2001:db8::1", 443),
+                ("Hy2", "hysteria", "hy.example.com", 8443),
+                ("127.0.0.1_1080", "socks", "127.0.0.1", 1080),
+            ],
+        },
+        Fixture {
+            content: include_str!("fixtures/clash.yaml"),
+            format: SubscriptionFormat::Clash,
+            nodes: &[
+                ("🇭🇰 HK 01", "shadowsocks", "hk.example.com", 8388),
+                ("JP VMess", "vmess", "jp.example.com", 443),
+                ("US_Trojan", "trojan", "2001:db8::2", 443),
+            ],
+        },
+        Fixture {
+            content: include_str!("fixtures/sing_box.json"),
+            format: SubscriptionFormat::SingBox,
+            nodes: &[
+                ("Reality", "vless", "reality.example.com", 443),
+                ("Hy2", "hysteria", "hy.example.com", 8443),
+                ("SS", "shadowsocks", "ss.example.com", 8388),
+            ],
+        },
+        Fixture {
+            content: include_str!("fixtures/sip008.json"),
+            format: SubscriptionFormat::Sip008,
+            nodes: &[
+                ("SIP008 A", "shadowsocks", "a.example.com", 8388),
+                ("b.example.com_8389", "shadowsocks", "b.example.com", 8389),
+            ],
+        },
+    ];
+
+    /// 将数据目录指向临时目录，需在首次访问路径前调用
+    fn init_data_dir() {
+        static INIT: Once = Once::new();
+        INIT.call_once(|| {
+            let dir = std::env::temp_dir().join(format!("netproxy-test-{}", std::process::id()));
+            let _ = std::fs::remove_dir_all(&dir);
+            // 测试进程中只在此处修改环境变量
+            unsafe { std::env::set_var("NETPROXY_DATA_DIR", &dir) };
+        });
+    }
+
+    /// 在 127.0.0.1 的随机端口上提供订阅内容，返回订阅地址
+    async fn serve(body: &'static str) -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+
+        tokio::spawn(async move {
+            while let Ok((mut socket, _)) = listener.accept().await {
+                // 读完请求头后返回固定内容
+                let mut request = Vec::new();
+                let mut buf = [0u8; 1024];
+                while !request.windows(4).any(|w| w == b"\r\n\r\n") {
+                    match socket.read(&mut buf).await {
+                        Ok(0) | Err(_) => break,
+                        Ok(n) => request.extend_from_slice(&buf[..n]),
+                    }
+                }
+
+                let response = format!(
+                    "HTTP/1.1 200 OK\r\nContent-Type: text/plain; charset=utf-8\r\nContent-Length: {}\r\nsubscription-userinfo: {}\r\nConnection: close\r\n\r\n{}",
+                    body.len(),
+                    USERINFO,
+                    body
+                );
+                let _ = socket.write_all(response.as_bytes()).await;
+                let _ = socket.shutdown().await;
+            }
+        });
+
+        format!("http://{}/sub", addr)
+    }
+
+    #[tokio::test]
+    async fn imports_fixtures_from_local_server() {
+        init_data_dir();
+
+        for fixture in FIXTURES {
+            let url = serve(fixture.content).await;
+            let label = format!("{:?}", fixture.format);
+
+            let fetched = fetch(&url, None).await.unwrap();
+            let userinfo = fetched.userinfo.as_ref().expect(&label);
+            assert_eq!(
+                (userinfo.upload, userinfo.download, userinfo.total),
+                (1024, 2048, 10240),
+                "{}",
+                label
+            );
+            assert_eq!(
+                detect_format(&fetched.content),
+                Some(fixture.format),
+                "{}",
+                label
+            );
+
+            let decoded = decode(&fetched.content).unwrap();
+            let decoded: Vec<_> = decoded
+                .iter()
+                .map(|n| (n.protocol(), n.address.as_str(), n.port))
+                .collect();
+            let expected: Vec<_> = fixture
+                .nodes
+                .iter()
+                .map(|(_, protocol, address, port)| (*protocol, *address, *port))
+                .collect();
+            assert_eq!(decoded, expected, "{}", label);
+
+            let sub_name = format!("fixture-{}", label.to_lowercase());
+            let nodes = proxylink::import_subscription(&url, &sub_name)
+                .await
+                .unwrap();
+
+            let sub_dir = format!("sub_{}", sub_name);
+            let expected: Vec<NodeInfo> = fixture
+                .nodes
+                .iter()
+                .map(|(name, protocol, address, port)| NodeInfo {
+                    name: name.to_string(),
+                    protocol: protocol.to_string(),
+                    address: address.to_string(),
+                    port: *port,
+                    file_name: Path::new(&sub_dir)
+                        .join(format!("{}.json", name))
+                        .to_string_lossy()
+                        .to_string(),
+                })
+                .collect();
+            assert_eq!(nodes, expected, "{}", label);
+
+            for node in &nodes {
+                let outbound = proxylink::read_outbound(&node.file_name).unwrap();
+                assert_eq!(outbound["protocol"], node.protocol, "{}", node.file_name);
+            }
+            assert_eq!(
+                proxylink::node_files(&proxylink::subscription_dir(&sub_name)).len(),
+                nodes.len()
+            );
+
+            let saved = store::get(&sub_name).unwrap();
+            assert_eq!(saved.node_count, nodes.len());
+            assert!(saved.last_error.is_none());
+            assert_eq!(saved.failures, 0);
+            assert_eq!(saved.last_update, saved.last_attempt);
+            assert_eq!(saved.userinfo.map(|u| u.total), Some(10240));
+        }
+    }
+
+    #[tokio::test]
+    async fn failed_refresh_keeps_last_update() {
+        init_data_dir();
+
+        let url = serve("<html>Not Found</html>").await;
+        store::upsert(Subscription::new("fixture-broken", &url)).unwrap();
+
+        for attempt in 1..=2 {
+            assert!(refresh("fixture-broken").await.is_err());
+            let saved = store::get("fixture-broken").unwrap();
+            assert_eq!(saved.last_update, None);
+            assert!(saved.last_attempt.is_some());
+            assert_eq!(saved.failures, attempt);
+            assert!(saved.last_error.is_some());
+        }
+    }
+
+    #[test]
+    fn detects_unknown_formats() {
+        let cases = [
+            "",
+            "\u{feff}  \n",
+            "<html>Not Found</html>",
+            "{\"error\": \"token expired\"}",
+            "{ not json",
+            // base64 解码后不含任何链接
+            "aGVsbG8gd29ybGQ=",
+        ];
+
+        for content in cases {
+            assert_eq!(detect_format(content), None, "{:?}", content);
+            assert!(decode(content).is_err(), "{:?}", content);
+        }
+    }
+
+    #[test]
+    fn rejects_subscription_without_usable_nodes() {
+        let cases = [
+            "tuic://uuid:pw@tuic.example.com:443#Unsupported",
+            "{\"version\": 1, \"servers\": []}",
+            "proxies:\n  - name: Obfs\n    type: ss\n    server: a.example.com\n    port: 8388\n    cipher: aes-128-gcm\n    password: pw\n    plugin: obfs\n",
+        ];
+
+        for content in cases {
+            assert!(detect_format(content).is_some(), "{:?}", content);
+            let err = decode(content).expect_err(content).to_string();
+            assert!(err.contains("没有可用的节点"), "{}: {}", content, err);
+        }
+    }
+}
@@ -0,0 +1,69 @@
+// 订阅自动更新：后台定期检查到期的订阅并刷新，结果通过事件通知前端
+use super::{refresh, store, usage};
+use crate::models::SubscriptionUpdateEvent;
+use crate::utils::time;
+use std::time::Duration;
+use tauri::{AppHandle, Emitter};
+
+/// 订阅更新完成事件
+pub const UPDATE_EVENT: &str = "subscription-updated";
+/// 订阅流量不足或即将到期事件
+pub const USAGE_WARNING_EVENT: &str = "subscription-usage-warning";
+
+const CHECK_INTERVAL: Duration = Duration::from_secs(60);
+// 启动后稍作等待，避免与界面初始化争抢网络
+const STARTUP_DELAY: Duration = Duration::from_secs(10);
+
+pub fn start(app: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        tokio::time::sleep(STARTUP_DELAY).await;
+
+        loop {
+            let now = time::now_secs();
+            let due: Vec<String> = store::list()
+                .into_iter()
+                .filter(|s| s.is_due(now))
+                .map(|s| s.name)
+                .collect();
+
+            for name in due {
+                let event = update(&name).await;
+                let _ = app.emit(UPDATE_EVENT, event);
+                emit_usage_warning(&app, &name);
+            }
+
+            tokio::time::sleep(CHECK_INTERVAL).await;
+        }
+    });
+}
+
+/// 刷新单个订阅并转换为事件
+async fn update(name: &str) -> SubscriptionUpdateEvent {
+    match refresh(name).await {
+        Ok(diff) => SubscriptionUpdateEvent {
+            name: name.to_string(),
+            success: true,
+            node_count: diff.nodes.len(),
+            added: diff.added.len(),
+            removed: diff.removed.len(),
+            changed: diff.changed.len(),
+            error: None,
+        },
+        Err(e) => SubscriptionUpdateEvent {
+            name: name.to_string(),
+            success: false,
+            node_count: 0,
+            added: 0,
+            removed: 0,
+            changed: 0,
+            error: Some(e.to_string()),
+        },
+    }
+}
+
+/// 订阅存在用量告警时通知前端
+pub fn emit_usage_warning(app: &AppHandle, name: &str) {
+    if let Some(usage) = usage::get(name).filter(|u| !u.warnings.is_empty()) {
+        let _ = app.emit(USAGE_WARNING_EVENT, usage);
+    }
+}
@@ -0,0 +1,147 @@
+// sing-box JSON 订阅：读取 outbounds 中的代理出站，忽略 selector/direct 等
+use super::{get_bool, get_opt_str, get_port, get_str, get_str_list};
+use crate::services::share_link::{ProtocolSettings, ProxyNode, StreamSettings};
+use serde_json::Value;
+use std::error::Error;
+
+pub fn parse(content: &str) -> Result<Vec<ProxyNode>, Box<dyn Error>> {
+    let doc: Value = serde_json::from_str(content)?;
+    let outbounds = doc
+        .get("outbounds")
+        .and_then(Value::as_array)
+        .ok_or("sing-box 配置缺少 outbounds")?;
+
+    Ok(outbounds.iter().filter_map(|o| convert(o).ok()).collect())
+}
+
+fn convert(outbound: &Value) -> Result<ProxyNode, Box<dyn Error>> {
+    let kind = get_str(outbound, "type");
+    let address = get_str(outbound, "server");
+    let port = get_port(outbound, "server_port").ok_or("缺少端口")?;
+    if address.is_empty() {
+        return Err("缺少服务器地址".into());
+    }
+
+    let mut stream = stream_settings(outbound);
+    let settings = match kind.as_str() {
+        "vmess" => ProtocolSettings::Vmess {
+            id: get_str(outbound, "uuid"),
+            alter_id: get_str(outbound, "alter_id").parse().unwrap_or(0),
+            security: get_opt_str(outbound, "security").unwrap_or_else(|| "auto".to_string()),
+        },
+        "vless" => ProtocolSettings::Vless {
+            id: get_str(outbound, "uuid"),
+            flow: get_str(outbound, "flow"),
+            encryption: "none".to_string(),
+        },
+        "trojan" => ProtocolSettings::Trojan {
+            password: get_str(outbound, "password"),
+        },
+        "shadowsocks" => {
+            if let Some(plugin) = get_opt_str(outbound, "plugin") {
+                return Err(format!("Xray 不支持 Shadowsocks 插件: {}", plugin).into());
+            }
+            ProtocolSettings::Shadowsocks {
+                method: get_str(outbound, "method").to_lowercase(),
+                password: get_str(outbound, "password"),
+            }
+        }
+        "socks" => ProtocolSettings::Socks {
+            user: get_opt_str(outbound, "username"),
+            pass: get_opt_str(outbound, "password"),
+        },
+        "http" => ProtocolSettings::Http {
+            user: get_opt_str(outbound, "username"),
+            pass: get_opt_str(outbound, "password"),
+        },
+        "hysteria2" => {
+            stream.security = "tls".to_string();
+            let obfs = outbound.get("obfs");
+            ProtocolSettings::Hysteria2 {
+                password: get_str(outbound, "password"),
+                obfs: obfs.and_then(|o| get_opt_str(o, "type")),
+                obfs_password: obfs.and_then(|o| get_opt_str(o, "password")),
+            }
+        }
+        "wireguard" => ProtocolSettings::Wireguard {
+            secret_key: get_str(outbound, "private_key"),
+            public_key: get_str(outbound, "peer_public_key"),
+            pre_shared_key: get_opt_str(outbound, "pre_shared_key"),
+            local_address: get_str_list(outbound, "local_address"),
+            mtu: get_str(outbound, "mtu").parse().ok(),
+            reserved: get_str_list(outbound, "reserved")
+                .iter()
+                .filter_map(|v| v.parse().ok())
+                .collect(),
+        },
+        _ => return Err(format!("不支持的类型: {}", kind).into()),
+    };
+
+    Ok(ProxyNode {
+        name: get_opt_str(outbound, "tag").unwrap_or_else(|| format!("{}:{}", address, port)),
+        address,
+        port,
+        settings,
+        stream,
+    })
+}
+
+fn stream_settings(outbound: &Value) -> StreamSettings {
+    let mut stream = StreamSettings {
+        network: "tcp".to_string(),
+        security: "none".to_string(),
+        ..Default::default()
+    };
+
+    if let Some(tls) = outbound.get("tls").filter(|t| get_bool(t, "enabled")) {
+        let reality = tls.get("reality").filter(|r| get_bool(r, "enabled"));
+        stream.security = if reality.is_some() { "reality" } else { "tls" }.to_string();
+        stream.sni = get_str(tls, "server_name");
+        stream.allow_insecure = get_bool(tls, "insecure");
+        stream.alpn = get_str_list(tls, "alpn");
+        stream.fingerprint = tls
+            .get("utls")
+            .filter(|u| get_bool(u, "enabled"))
+            .map(|u| get_str(u, "fingerprint"))
+            .unwrap_or_default();
+        if let Some(reality) = reality {
+            stream.public_key = get_str(reality, "public_key");
+            stream.short_id = get_str(reality, "short_id");
+        }
+    }
+
+    if let Some(transport) = outbound.get("transport") {
+        let kind = get_str(transport, "type");
+        stream.path = get_str(transport, "path");
+        match kind.as_str() {
+            "ws" => {
+                stream.network = "ws".to_string();
+                stream.host = transport
+                    .get("headers")
+                    .map(|h| get_str(h, "Host"))
+                    .unwrap_or_default();
+            }
+            "grpc" => {
+                stream.network = "grpc".to_string();
+                stream.service_name = get_str(transport, "service_name");
+            }
+            "httpupgrade" => {
+                stream.network = "httpupgrade".to_string();
+                stream.host = get_str(transport, "host");
+            }
+            // sing-box 的 http 传输在启用 TLS 时为 h2，否则为 HTTP 伪装
+            "http" => {
+                let host = get_str_list(transport, "host").join(",");
+                if stream.security == "none" {
+                    stream.header_type = "http".to_string();
+                } else {
+                    stream.network = "h2".to_string();
+                }
+                stream.host = host;
+            }
+            _ => {}
+        }
+    }
+
+    stream
+}
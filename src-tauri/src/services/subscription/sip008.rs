@@ -0,0 +1,36 @@
+// Shadowsocks SIP008 JSON 订阅：{ "version": 1, "servers": [...] }
+use super::{get_opt_str, get_port, get_str};
+use crate::services::share_link::{ProtocolSettings, ProxyNode, StreamSettings};
+use serde_json::Value;
+use std::error::Error;
+
+pub fn parse(content: &str) -> Result<Vec<ProxyNode>, Box<dyn Error>> {
+    let doc: Value = serde_json::from_str(content)?;
+    let servers = doc
+        .get("servers")
+        .and_then(Value::as_array)
+        .ok_or("SIP008 配置缺少 servers")?;
+
+    Ok(servers.iter().filter_map(convert).collect())
+}
+
+fn convert(server: &Value) -> Option<ProxyNode> {
+    // Xray 不支持 SIP003 插件
+    if get_opt_str(server, "plugin").is_some() {
+        return None;
+    }
+
+    let address = get_opt_str(server, "server")?;
+    let port = get_port(server, "server_port")?;
+
+    Some(ProxyNode {
+        name: get_opt_str(server, "remarks").unwrap_or_else(|| format!("{}:{}", address, port)),
+        address,
+        port,
+        settings: ProtocolSettings::Shadowsocks {
+            method: get_str(server, "method").to_lowercase(),
+            password: get_str(server, "password"),
+        },
+        stream: StreamSettings::default(),
+    })
+}
@@ -0,0 +1,73 @@
+// 订阅列表持久化：保存在配置目录的 subscriptions.json
+use crate::models::Subscription;
+use crate::utils::{json, paths};
+use once_cell::sync::Lazy;
+use std::path::PathBuf;
+use std::sync::Mutex;
+
+// 串行化读写，避免后台更新与前端编辑互相覆盖
+static STORE_LOCK: Lazy<Mutex<()>> = Lazy::new(|| Mutex::new(()));
+
+fn store_path() -> PathBuf {
+    paths::get_config_dir().join("subscriptions.json")
+}
+
+// 文件损坏时返回错误，修改操作因此不会用空列表覆盖原有内容
+fn read() -> Result<Vec<Subscription>, Box<dyn std::error::Error>> {
+    json::read_json(&store_path())
+}
+
+fn write(subscriptions: &[Subscription]) -> Result<(), Box<dyn std::error::Error>> {
+    json::save_json(&store_path(), subscriptions)
+}
+
+pub fn list() -> Vec<Subscription> {
+    let _guard = STORE_LOCK.lock().unwrap();
+    read().unwrap_or_default()
+}
+
+pub fn get(name: &str) -> Option<Subscription> {
+    list().into_iter().find(|s| s.name == name)
+}
+
+/// 新增订阅；同名订阅已存在时只更新地址，保留其余设置
+pub fn upsert(subscription: Subscription) -> Result<(), Box<dyn std::error::Error>> {
+    let _guard = STORE_LOCK.lock().unwrap();
+    let mut subscriptions = read()?;
+
+    match subscriptions
+        .iter_mut()
+        .find(|s| s.name == subscription.name)
+    {
+        Some(existing) => existing.url = subscription.url,
+        None => subscriptions.push(subscription),
+    }
+
+    write(&subscriptions)
+}
+
+/// 修改指定订阅
+pub fn update<F>(name: &str, f: F) -> Result<Subscription, Box<dyn std::error::Error>>
+where
+    F: FnOnce(&mut Subscription),
+{
+    let _guard = STORE_LOCK.lock().unwrap();
+    let mut subscriptions = read()?;
+
+    let subscription = subscriptions
+        .iter_mut()
+        .find(|s| s.name == name)
+        .ok_or_else(|| format!("订阅不存在: {}", name))?;
+    f(subscription);
+    let updated = subscription.clone();
+
+    write(&subscriptions)?;
+    Ok(updated)
+}
+
+pub fn remove(name: &str) -> Result<(), Box<dyn std::error::Error>> {
+    let _guard = STORE_LOCK.lock().unwrap();
+    let mut subscriptions = read()?;
+    subscriptions.retain(|s| s.name != name);
+    write(&subscriptions)
+}
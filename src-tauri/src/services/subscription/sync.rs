@@ -0,0 +1,614 @@
+// 订阅节点增量同步：按稳定标识（协议+地址+端口+凭据）匹配新旧节点，
+// 保留已有节点的文件名，使选中节点等按文件名记录的数据在刷新后仍然有效
+use crate::models::{NodeRename, SubscriptionDiff};
+use crate::services::proxylink;
+use crate::services::share_link::ProxyNode;
+use crate::services::{accounting, latency, selection, xray};
+use crate::utils::paths;
+use serde_json::Value;
+use std::collections::btree_map::Entry;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::error::Error;
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// 订阅目录中已有的节点文件
+struct ExistingNode {
+    path: PathBuf,
+    outbound: Value,
+}
+
+impl ExistingNode {
+    fn stem(&self) -> String {
+        self.path
+            .file_stem()
+            .map(|s| s.to_string_lossy().to_string())
+            .unwrap_or_default()
+    }
+}
+
+/// 节点的稳定标识，由出站配置中的协议、地址、端口与凭据组成
+pub fn node_identity(outbound: &Value) -> Option<String> {
+    let protocol = outbound.get("protocol")?.as_str()?;
+    let settings = outbound.get("settings")?;
+
+    let (address, port, credential) = if let Some(server) = first(settings, "vnext") {
+        let user = first(server, "users");
+        (
+            str_of(server, "address"),
+            port_of(server),
+            user.map(|u| str_of(u, "id")).unwrap_or_default(),
+        )
+    } else if let Some(server) = first(settings, "servers") {
+        let credential = match first(server, "users") {
+            Some(user) => format!("{}:{}", str_of(user, "user"), str_of(user, "pass")),
+            None => str_of(server, "password"),
+        };
+        (str_of(server, "address"), port_of(server), credential)
+    } else if let Some(peer) = first(settings, "peers") {
+        // WireGuard: endpoint 为 host:port，凭据为双方密钥
+        let endpoint = str_of(peer, "endpoint");
+        let (host, port) = endpoint.rsplit_once(':')?;
+        (
+            host.trim_matches(|c| c == '[' || c == ']').to_string(),
+            port.parse().ok()?,
+            format!(
+                "{}:{}",
+                str_of(settings, "secretKey"),
+                str_of(peer, "publicKey")
+            ),
+        )
+    } else {
+        // 扁平结构 (hysteria)，密码位于 streamSettings
+        let auth = outbound
+            .pointer("/streamSettings/hysteriaSettings/auth")
+            .and_then(Value::as_str)
+            .unwrap_or_default()
+            .to_string();
+        (str_of(settings, "address"), port_of(settings), auth)
+    };
+
+    if address.is_empty() || port == 0 {
+        return None;
+    }
+
+    Some(format!(
+        "{}|{}|{}|{}",
+        protocol,
+        address.to_lowercase(),
+        port,
+        credential
+    ))
+}
+
+fn first<'a>(value: &'a Value, key: &str) -> Option<&'a Value> {
+    value.get(key)?.as_array()?.first()
+}
+
+fn str_of(value: &Value, key: &str) -> String {
+    value
+        .get(key)
+        .and_then(Value::as_str)
+        .unwrap_or_default()
+        .to_string()
+}
+
+fn port_of(value: &Value) -> u16 {
+    value.get("port").and_then(Value::as_u64).unwrap_or(0) as u16
+}
+
+/// 两步改名时的临时后缀：节点先改为 {最终名}.json.renaming，再统一改为最终名
+const RENAMING_SUFFIX: &str = "renaming";
+
+/// 完成上次中断的两步改名，最终名已被占用时追加序号
+fn finish_renames(dir: &Path) {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return;
+    };
+
+    for path in entries.flatten().map(|entry| entry.path()) {
+        if path.extension().is_none_or(|ext| ext != RENAMING_SUFFIX) {
+            continue;
+        }
+        let Some(stem) = path
+            .file_stem()
+            .and_then(|s| s.to_str())
+            .and_then(|s| s.strip_suffix(".json"))
+        else {
+            continue;
+        };
+        let stem = proxylink::unique_file_stem(dir, stem);
+        let _ = fs::rename(&path, dir.join(format!("{}.json", stem)));
+    }
+}
+
+fn read_existing(dir: &Path) -> Vec<ExistingNode> {
+    finish_renames(dir);
+    let Ok(entries) = fs::read_dir(dir) else {
+        return Vec::new();
+    };
+
+    let mut nodes: Vec<_> = entries
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "json"))
+        .map(|path| {
+            let outbound = fs::read_to_string(&path)
+                .ok()
+                .and_then(|content| serde_json::from_str::<Value>(&content).ok())
+                .and_then(|json| first(&json, "outbounds").cloned())
+                .unwrap_or(Value::Null);
+            ExistingNode { path, outbound }
+        })
+        .collect();
+    // 目录遍历顺序不固定，排序后重复节点的匹配结果才稳定
+    nodes.sort_by(|a, b| a.path.cmp(&b.path));
+    nodes
+}
+
+/// 节点的匹配键：优先使用稳定标识，提取不到标识时比较完整的出站配置
+fn match_key(outbound: &Value) -> String {
+    node_identity(outbound).unwrap_or_else(|| outbound.to_string())
+}
+
+/// 文件名是否仍对应该节点名（允许重名时追加的 _2、_3 序号）
+fn stem_matches(stem: &str, desired: &str) -> bool {
+    stem == desired
+        || stem
+            .strip_prefix(desired)
+            .and_then(|rest| rest.strip_prefix('_'))
+            .is_some_and(|n| !n.is_empty() && n.chars().all(|c| c.is_ascii_digit()))
+}
+
+/// 在已占用的文件名之外取一个可用的文件名，比较时忽略大小写以兼容 Windows 与 macOS
+fn reserve_stem(reserved: &mut HashSet<String>, desired: &str) -> String {
+    let stem = std::iter::once(desired.to_string())
+        .chain((2..).map(|i| format!("{}_{}", desired, i)))
+        .find(|candidate| !reserved.contains(&candidate.to_lowercase()))
+        .unwrap_or_else(|| desired.to_string());
+    reserved.insert(stem.to_lowercase());
+    stem
+}
+
+/// 同步计划，在改动任何文件之前确定每个节点的最终文件名
+struct Plan {
+    /// 每个订阅节点匹配到的已有文件序号与最终文件名
+    targets: Vec<(Option<usize>, String)>,
+    /// 订阅中已不存在、需要删除的已有文件序号
+    removed: Vec<usize>,
+}
+
+/// 按匹配键配对新旧节点并分配文件名：
+/// 无法解析的文件原样保留并占用其文件名，名称未变的节点保留原文件名，
+/// 改名的节点优先于新增节点取得期望的文件名
+fn plan(existing: &[ExistingNode], outbounds: &[Value], names: &[String]) -> Plan {
+    // 相同匹配键可能出现多次，按文件名顺序依次匹配
+    let mut by_key: HashMap<String, Vec<usize>> = HashMap::new();
+    for (index, node) in existing.iter().enumerate().rev() {
+        if !node.outbound.is_null() {
+            by_key
+                .entry(match_key(&node.outbound))
+                .or_default()
+                .push(index);
+        }
+    }
+
+    let matched: Vec<Option<usize>> = outbounds
+        .iter()
+        .map(|outbound| by_key.get_mut(&match_key(outbound)).and_then(Vec::pop))
+        .collect();
+    let mut removed: Vec<usize> = by_key.into_values().flatten().collect();
+    removed.sort_unstable();
+
+    let mut reserved: HashSet<String> = existing
+        .iter()
+        .filter(|node| node.outbound.is_null())
+        .map(|node| node.stem().to_lowercase())
+        .collect();
+    let mut targets: Vec<Option<String>> = vec![None; outbounds.len()];
+
+    for (target, (index, name)) in targets.iter_mut().zip(matched.iter().zip(names)) {
+        if let Some(index) = index {
+            let stem = existing[*index].stem();
+            if stem_matches(&stem, name) {
+                reserved.insert(stem.to_lowercase());
+                *target = Some(stem);
+            }
+        }
+    }
+    for renamed in [true, false] {
+        for (target, (index, name)) in targets.iter_mut().zip(matched.iter().zip(names)) {
+            if target.is_none() && index.is_some() == renamed {
+                *target = Some(reserve_stem(&mut reserved, name));
+            }
+        }
+    }
+
+    Plan {
+        targets: matched
+            .into_iter()
+            .zip(targets.into_iter().map(Option::unwrap_or_default))
+            .collect(),
+        removed,
+    }
+}
+
+/// 同步中途失败，diff 中为失败前已完成的变化，调用方仍需据此迁移相关记录
+#[derive(Debug)]
+pub struct SyncError {
+    pub diff: Box<SubscriptionDiff>,
+    pub error: String,
+}
+
+impl SyncError {
+    /// 失败前是否已删除、改名或改写了节点文件
+    pub fn is_partial(&self) -> bool {
+        !(self.diff.removed.is_empty()
+            && self.diff.renamed.is_empty()
+            && self.diff.changed.is_empty())
+    }
+}
+
+impl From<String> for SyncError {
+    fn from(error: String) -> Self {
+        Self {
+            diff: Box::default(),
+            error,
+        }
+    }
+}
+
+impl fmt::Display for SyncError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.error)
+    }
+}
+
+impl Error for SyncError {}
+
+/// 将订阅节点同步到订阅目录：未变化的节点保持原文件，配置或名称变化的节点原位更新，
+/// 仅新增和删除的节点会增删文件
+pub fn apply(sub_name: &str, nodes: &[ProxyNode]) -> Result<SubscriptionDiff, SyncError> {
+    sync_dir(
+        &paths::get_outbounds_dir(),
+        &proxylink::subscription_dir(sub_name),
+        nodes,
+    )
+}
+
+fn sync_dir(
+    outbounds_dir: &Path,
+    sub_dir: &Path,
+    nodes: &[ProxyNode],
+) -> Result<SubscriptionDiff, SyncError> {
+    let mut diff = SubscriptionDiff::default();
+    match execute(outbounds_dir, sub_dir, nodes, &mut diff) {
+        Ok(()) => Ok(diff),
+        Err(e) => Err(SyncError {
+            diff: Box::new(diff),
+            error: e.to_string(),
+        }),
+    }
+}
+
+/// 按计划改动文件，每完成一步即记入 diff：
+/// 改名分两步进行以支持节点互换名称，删除先于写入新节点以释放文件名
+fn execute(
+    outbounds_dir: &Path,
+    sub_dir: &Path,
+    nodes: &[ProxyNode],
+    diff: &mut SubscriptionDiff,
+) -> Result<(), Box<dyn Error>> {
+    fs::create_dir_all(sub_dir)?;
+    let relative = |path: &Path| {
+        path.strip_prefix(outbounds_dir)
+            .unwrap_or(path)
+            .to_string_lossy()
+            .to_string()
+    };
+
+    let existing = read_existing(sub_dir);
+    let outbounds: Vec<Value> = nodes.iter().map(|n| n.to_xray_outbound("proxy")).collect();
+    let names: Vec<String> = nodes
+        .iter()
+        .map(|n| proxylink::sanitize_file_name(&n.name))
+        .collect();
+    let plan = plan(&existing, &outbounds, &names);
+
+    let path_of = |stem: &str| sub_dir.join(format!("{}.json", stem));
+    let renames: Vec<(&ExistingNode, PathBuf)> = plan
+        .targets
+        .iter()
+        .filter_map(|(index, stem)| {
+            let old = &existing[(*index)?];
+            (old.stem() != *stem).then(|| (old, path_of(stem)))
+        })
+        .collect();
+    let temp_of = |path: &Path| {
+        let mut name = path.file_name().unwrap_or_default().to_os_string();
+        name.push(format!(".{}", RENAMING_SUFFIX));
+        path.with_file_name(name)
+    };
+
+    for (old, path) in &renames {
+        fs::rename(&old.path, temp_of(path))?;
+    }
+    for index in &plan.removed {
+        let path = &existing[*index].path;
+        fs::remove_file(path)?;
+        diff.removed.push(relative(path));
+    }
+    for (old, path) in &renames {
+        fs::rename(temp_of(path), path)?;
+        diff.renamed.push(NodeRename {
+            from: relative(&old.path),
+            to: relative(path),
+        });
+    }
+
+    for ((node, outbound), (index, stem)) in nodes.iter().zip(&outbounds).zip(&plan.targets) {
+        let path = path_of(stem);
+        match index {
+            Some(index) if existing[*index].outbound == *outbound => {}
+            Some(_) => {
+                proxylink::write_outbound(&path, outbound)?;
+                diff.changed.push(relative(&path));
+            }
+            None => {
+                proxylink::write_outbound(&path, outbound)?;
+                diff.added.push(relative(&path));
+            }
+        }
+        diff.nodes
+            .push(proxylink::node_info(outbounds_dir, &path, node));
+    }
+
+    Ok(())
+}
+
+/// 根据差异更新选中节点与正在运行的核心：
+/// 改名的节点重新指向新文件，已删除的节点取消选中，正在使用的节点配置变化时重启核心
+pub fn reconcile_selection(diff: &mut SubscriptionDiff) -> Result<(), Box<dyn Error>> {
+    let renamed = |file: &str| {
+        diff.renamed
+            .iter()
+            .find(|r| r.from == file)
+            .map(|r| r.to.clone())
+    };
+
+    let mut selected = selection::get();
+    if let Some(file) = selected.clone() {
+        if diff.removed.contains(&file) {
+            selection::clear()?;
+            selected = None;
+        } else if let Some(to) = renamed(&file) {
+            selection::set(&to)?;
+            selected = Some(to);
+        }
+    }
+    diff.selected_node = selected;
+
+    // 已删除的节点不主动停止核心，当前连接保持可用，直到用户切换节点
+    if let Some(active) = xray::current_node() {
+        let active = match renamed(&active) {
+            Some(to) => {
+                xray::rename_current_node(&to);
+                to
+            }
+            None => active,
+        };
+        if diff.changed.contains(&active) {
+            xray::start(&active)?;
+            diff.restarted = true;
+        }
+    }
+
+    Ok(())
+}
+
+/// 按改名迁移以节点文件名为键的记录：先取出全部旧文件名的记录再写入新文件名，
+/// 链式改名（A→B、B→C）或互换不会互相覆盖；新文件名已有记录时由 merge 合并。
+/// 返回是否有记录被迁移
+pub fn migrate_keys<T>(
+    map: &mut BTreeMap<String, T>,
+    renames: &[NodeRename],
+    mut merge: impl FnMut(&mut T, T),
+) -> bool {
+    let moved: Vec<(String, T)> = renames
+        .iter()
+        .filter_map(|r| map.remove(&r.from).map(|value| (r.to.clone(), value)))
+        .collect();
+    let changed = !moved.is_empty();
+
+    for (to, value) in moved {
+        match map.entry(to) {
+            Entry::Occupied(mut entry) => merge(entry.get_mut(), value),
+            Entry::Vacant(entry) => {
+                entry.insert(value);
+            }
+        }
+    }
+    changed
+}
+
+/// 延迟记录随节点文件迁移或删除，流量记账随节点文件迁移
+pub fn migrate_history(diff: &SubscriptionDiff) -> Result<(), Box<dyn Error>> {
+    latency::history::forget(&diff.removed)?;
+    latency::history::rename(&diff.renamed)?;
+    accounting::store::rename(&diff.renamed)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::share_link;
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir =
+            std::env::temp_dir().join(format!("netproxy-sync-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    fn node(name: &str, link: &str) -> ProxyNode {
+        share_link::parse(&format!("trojan://pw@{}#{}", link, name)).unwrap()
+    }
+
+    fn sync(dir: &Path, nodes: &[ProxyNode]) -> SubscriptionDiff {
+        sync_dir(dir, &dir.join("sub"), nodes).unwrap()
+    }
+
+    /// 订阅目录中的全部文件名，含未完成改名的临时文件
+    fn files(dir: &Path) -> Vec<String> {
+        let mut files: Vec<_> = fs::read_dir(dir.join("sub"))
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name().to_string_lossy().to_string())
+            .collect();
+        files.sort();
+        files
+    }
+
+    fn rel(stem: &str) -> String {
+        Path::new("sub")
+            .join(format!("{}.json", stem))
+            .to_string_lossy()
+            .to_string()
+    }
+
+    fn address(dir: &Path, stem: &str) -> String {
+        let json: Value =
+            serde_json::from_str(&fs::read_to_string(dir.join(rel(stem))).unwrap()).unwrap();
+        json.pointer("/outbounds/0/settings/servers/0/address")
+            .and_then(Value::as_str)
+            .unwrap()
+            .to_string()
+    }
+
+    fn renames(diff: &SubscriptionDiff) -> Vec<(String, String)> {
+        diff.renamed
+            .iter()
+            .map(|r| (r.from.clone(), r.to.clone()))
+            .collect()
+    }
+
+    #[test]
+    fn renames_and_updates_in_place() {
+        let dir = temp_dir("rename");
+        sync(&dir, &[node("A", "a.com:443"), node("B", "b.com:443")]);
+
+        let diff = sync(
+            &dir,
+            &[node("A2", "a.com:443"), node("B", "b.com:443?sni=x.com")],
+        );
+        assert_eq!(renames(&diff), [(rel("A"), rel("A2"))]);
+        assert_eq!(diff.changed, [rel("B")]);
+        assert!(diff.added.is_empty() && diff.removed.is_empty());
+        assert_eq!(files(&dir), ["A2.json", "B.json"]);
+
+        let diff = sync(
+            &dir,
+            &[node("A2", "a.com:443"), node("B", "b.com:443?sni=x.com")],
+        );
+        assert!(diff.renamed.is_empty() && diff.changed.is_empty());
+        assert_eq!(diff.nodes.len(), 2);
+    }
+
+    #[test]
+    fn swaps_names_in_two_phases() {
+        let dir = temp_dir("swap");
+        sync(&dir, &[node("X", "a.com:443"), node("Y", "b.com:443")]);
+
+        let diff = sync(&dir, &[node("Y", "a.com:443"), node("X", "b.com:443")]);
+        assert_eq!(renames(&diff), [(rel("X"), rel("Y")), (rel("Y"), rel("X"))]);
+        assert!(diff.added.is_empty() && diff.removed.is_empty() && diff.changed.is_empty());
+        assert_eq!(files(&dir), ["X.json", "Y.json"]);
+        assert_eq!(address(&dir, "X"), "b.com");
+        assert_eq!(address(&dir, "Y"), "a.com");
+    }
+
+    #[test]
+    fn matches_duplicate_identities_in_order() {
+        let dir = temp_dir("duplicate");
+        let diff = sync(&dir, &[node("N", "a.com:443"), node("N", "a.com:443")]);
+        assert_eq!(diff.added, [rel("N"), rel("N_2")]);
+
+        let diff = sync(&dir, &[node("N", "a.com:443"), node("N", "a.com:443")]);
+        assert!(diff.added.is_empty() && diff.removed.is_empty() && diff.renamed.is_empty());
+
+        let diff = sync(&dir, &[node("N", "a.com:443")]);
+        assert_eq!(diff.removed, [rel("N_2")]);
+        assert_eq!(files(&dir), ["N.json"]);
+    }
+
+    #[test]
+    fn removes_vanished_nodes_and_keeps_unreadable_files() {
+        let dir = temp_dir("remove");
+        fs::create_dir_all(dir.join("sub")).unwrap();
+        fs::write(dir.join(rel("broken")), "{").unwrap();
+        sync(&dir, &[node("A", "a.com:443"), node("B", "b.com:443")]);
+
+        let diff = sync(&dir, &[node("broken", "a.com:443")]);
+        assert_eq!(diff.removed, [rel("B")]);
+        assert_eq!(renames(&diff), [(rel("A"), rel("broken_2"))]);
+        assert_eq!(files(&dir), ["broken.json", "broken_2.json"]);
+        assert_eq!(fs::read_to_string(dir.join(rel("broken"))).unwrap(), "{");
+    }
+
+    #[test]
+    fn existing_nodes_keep_names_before_new_nodes() {
+        let dir = temp_dir("reserve");
+        sync(&dir, &[node("X", "a.com:443")]);
+
+        let diff = sync(&dir, &[node("X", "b.com:443"), node("X", "a.com:443")]);
+        assert!(diff.renamed.is_empty());
+        assert_eq!(diff.added, [rel("X_2")]);
+        assert_eq!(address(&dir, "X"), "a.com");
+        assert_eq!(address(&dir, "X_2"), "b.com");
+    }
+
+    #[test]
+    fn migrates_chained_renames_and_merges() {
+        let rename = |from: &str, to: &str| NodeRename {
+            from: from.to_string(),
+            to: to.to_string(),
+        };
+        let mut map: BTreeMap<String, u32> = [("a", 1), ("b", 2), ("c", 3), ("d", 4), ("e", 5)]
+            .map(|(k, v)| (k.to_string(), v))
+            .into();
+        let sum = |total: &mut u32, value: u32| *total += value;
+
+        // a→b、b→c 与 c→a 构成轮换，d 合并到已有的 e
+        assert!(migrate_keys(
+            &mut map,
+            &[
+                rename("a", "b"),
+                rename("b", "c"),
+                rename("c", "a"),
+                rename("d", "e")
+            ],
+            sum
+        ));
+        assert_eq!(
+            map.into_iter().collect::<Vec<_>>(),
+            [("a", 3), ("b", 1), ("c", 2), ("e", 9)].map(|(k, v)| (k.to_string(), v))
+        );
+
+        let mut empty = BTreeMap::new();
+        assert!(!migrate_keys(&mut empty, &[rename("x", "y")], sum));
+        assert!(empty.is_empty());
+    }
+
+    #[test]
+    fn finishes_interrupted_renames() {
+        let dir = temp_dir("interrupted");
+        sync(&dir, &[node("Y", "a.com:443")]);
+        fs::rename(dir.join(rel("Y")), dir.join("sub").join("Y.json.renaming")).unwrap();
+
+        let diff = sync(&dir, &[node("Y", "a.com:443")]);
+        assert!(diff.added.is_empty() && diff.removed.is_empty() && diff.renamed.is_empty());
+        assert_eq!(files(&dir), ["Y.json"]);
+    }
+}
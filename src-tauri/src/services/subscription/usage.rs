@@ -0,0 +1,216 @@
+// 订阅用量：解析 subscription-userinfo 响应头，并根据剩余流量与到期时间生成告警
+use super::store;
+use crate::models::{Subscription, SubscriptionUsage, SubscriptionUserinfo, UsageWarning};
+use crate::utils::time;
+
+const SECS_PER_DAY: u64 = 86_400;
+
+/// 解析 `upload=123; download=456; total=789; expire=1700000000`
+///
+/// 字段缺失按 0 处理，expire 为 0 或缺失表示长期有效；没有任何可识别字段时返回 None。
+pub fn parse_userinfo(header: &str) -> Option<SubscriptionUserinfo> {
+    let mut info = SubscriptionUserinfo::default();
+    let mut found = false;
+
+    for pair in header.split([';', ',']) {
+        let Some((key, value)) = pair.split_once('=') else {
+            continue;
+        };
+        // 部分面板会返回浮点数或科学计数法
+        let Some(value) = value
+            .trim()
+            .parse::<f64>()
+            .ok()
+            .filter(|v| v.is_finite() && *v >= 0.0)
+            .map(|v| v as u64)
+        else {
+            continue;
+        };
+
+        match key.trim().to_lowercase().as_str() {
+            "upload" => info.upload = value,
+            "download" => info.download = value,
+            "total" => info.total = value,
+            "expire" => info.expire = Some(value).filter(|v| *v > 0),
+            _ => continue,
+        }
+        found = true;
+    }
+
+    found.then_some(info)
+}
+
+/// 根据用量信息与订阅的告警阈值生成告警
+pub fn warnings(
+    info: &SubscriptionUserinfo,
+    low_quota_percent: u8,
+    expire_warn_days: u64,
+    now: u64,
+) -> Vec<UsageWarning> {
+    let mut warnings = Vec::new();
+
+    if let Some(remaining) = info.remaining() {
+        if remaining == 0 {
+            warnings.push(UsageWarning::Exhausted);
+        } else if (remaining as f64) < info.total as f64 * f64::from(low_quota_percent) / 100.0 {
+            warnings.push(UsageWarning::LowQuota {
+                remaining,
+                total: info.total,
+            });
+        }
+    }
+
+    if let Some(expire) = info.expire {
+        if expire <= now {
+            warnings.push(UsageWarning::Expired { expire });
+        } else if expire - now < expire_warn_days.saturating_mul(SECS_PER_DAY) {
+            warnings.push(UsageWarning::ExpiringSoon {
+                expire,
+                days_left: (expire - now) / SECS_PER_DAY,
+            });
+        }
+    }
+
+    warnings
+}
+
+/// 单个订阅的用量概况，未返回用量信息的订阅为 None
+pub fn usage_of(subscription: &Subscription, now: u64) -> Option<SubscriptionUsage> {
+    let info = subscription.userinfo.as_ref()?;
+    Some(SubscriptionUsage {
+        name: subscription.name.clone(),
+        upload: info.upload,
+        download: info.download,
+        total: info.total,
+        used: info.used(),
+        remaining: info.remaining(),
+        expire: info.expire,
+        warnings: warnings(
+            info,
+            subscription.low_quota_percent,
+            subscription.expire_warn_days,
+            now,
+        ),
+    })
+}
+
+/// 所有订阅的用量概况
+pub fn list() -> Vec<SubscriptionUsage> {
+    let now = time::now_secs();
+    store::list()
+        .iter()
+        .filter_map(|s| usage_of(s, now))
+        .collect()
+}
+
+/// 指定订阅的用量概况
+pub fn get(name: &str) -> Option<SubscriptionUsage> {
+    store::get(name).and_then(|s| usage_of(&s, time::now_secs()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const GB: u64 = 1 << 30;
+    const NOW: u64 = 1_760_000_000;
+
+    fn info(upload: u64, download: u64, total: u64, expire: Option<u64>) -> SubscriptionUserinfo {
+        SubscriptionUserinfo {
+            upload,
+            download,
+            total,
+            expire,
+        }
+    }
+
+    #[test]
+    fn parses_userinfo() {
+        assert_eq!(
+            parse_userinfo("upload=123; download=456; total=789; expire=1700000000"),
+            Some(info(123, 456, 789, Some(1_700_000_000)))
+        );
+        // 逗号分隔、大小写与空格不敏感，浮点数与科学计数法取整
+        assert_eq!(
+            parse_userinfo(" Upload = 1.5e3 ,DOWNLOAD=2048.9,total=1e10 "),
+            Some(info(1500, 2048, 10_000_000_000, None))
+        );
+    }
+
+    #[test]
+    fn treats_missing_fields_as_zero() {
+        assert_eq!(parse_userinfo("total=1024"), Some(info(0, 0, 1024, None)));
+        // expire 为 0 表示长期有效
+        assert_eq!(
+            parse_userinfo("upload=1; download=2; total=0; expire=0"),
+            Some(info(1, 2, 0, None))
+        );
+    }
+
+    #[test]
+    fn skips_malformed_values() {
+        assert_eq!(
+            parse_userinfo("upload=abc; download=-5; total=NaN; expire=inf; download=10"),
+            Some(info(0, 10, 0, None))
+        );
+        assert_eq!(parse_userinfo(""), None);
+        assert_eq!(parse_userinfo("upload; total"), None);
+        assert_eq!(parse_userinfo("foo=1; bar=2"), None);
+        assert_eq!(parse_userinfo("upload=; total=x"), None);
+    }
+
+    #[test]
+    fn warns_on_quota() {
+        // 不限流量不告警
+        assert!(warnings(&info(100 * GB, 0, 0, None), 10, 3, NOW).is_empty());
+        assert!(warnings(&info(5 * GB, 4 * GB, 100 * GB, None), 10, 3, NOW).is_empty());
+        assert_eq!(
+            warnings(&info(50 * GB, 45 * GB, 100 * GB, None), 10, 3, NOW),
+            vec![UsageWarning::LowQuota {
+                remaining: 5 * GB,
+                total: 100 * GB,
+            }]
+        );
+        // 超出总量视为用完
+        assert_eq!(
+            warnings(&info(60 * GB, 60 * GB, 100 * GB, None), 10, 3, NOW),
+            vec![UsageWarning::Exhausted]
+        );
+        // 阈值为 0 时只在用完时告警
+        assert!(warnings(&info(0, 99 * GB, 100 * GB, None), 0, 3, NOW).is_empty());
+    }
+
+    #[test]
+    fn warns_on_expiry() {
+        let day = SECS_PER_DAY;
+        assert!(warnings(&info(0, 0, 0, Some(NOW + 10 * day)), 10, 3, NOW).is_empty());
+        assert_eq!(
+            warnings(&info(0, 0, 0, Some(NOW + 2 * day + 60)), 10, 3, NOW),
+            vec![UsageWarning::ExpiringSoon {
+                expire: NOW + 2 * day + 60,
+                days_left: 2,
+            }]
+        );
+        assert_eq!(
+            warnings(&info(0, 0, 0, Some(NOW)), 10, 3, NOW),
+            vec![UsageWarning::Expired { expire: NOW }]
+        );
+        assert_eq!(
+            warnings(&info(0, 0, 100, Some(NOW - day)), 10, 0, NOW),
+            vec![UsageWarning::Expired { expire: NOW - day }]
+        );
+        // 提醒天数为 0 时不提前告警
+        assert!(warnings(&info(0, 0, 0, Some(NOW + 60)), 10, 0, NOW).is_empty());
+    }
+
+    #[test]
+    fn reports_quota_and_expiry_together() {
+        assert_eq!(
+            warnings(&info(0, 100, 100, Some(NOW - 1)), 10, 3, NOW),
+            vec![
+                UsageWarning::Exhausted,
+                UsageWarning::Expired { expire: NOW - 1 },
+            ]
+        );
+    }
+}
@@ -0,0 +1,350 @@
+use once_cell::sync::Lazy;
+use std::env;
+use std::fs;
+use std::path::PathBuf;
+use std::process::Command;
+
+const PROXY_HOST: &str = "127.0.0.1";
+const NO_PROXY: &str = "localhost,127.0.0.1,::1";
+
+const GNOME_PROXY_SCHEMA: &str = "org.gnome.system.proxy";
+const KDE_PROXY_GROUP: &str = "[Proxy Settings]";
+const ENV_FILE_NAME: &str = "90-netproxy.conf";
+
+// 桌面环境在运行期间不会改变，只检测一次，避免每次轮询都启动 gsettings
+static DESKTOP: Lazy<Desktop> = Lazy::new(|| {
+    let session = env::var("XDG_CURRENT_DESKTOP")
+        .or_else(|_| env::var("DESKTOP_SESSION"))
+        .unwrap_or_default();
+    detect_desktop(&session, || command_exists("gsettings"))
+});
+
+/// Linux 桌面环境，决定使用哪种方式设置系统代理
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Desktop {
+    Gnome,
+    Kde,
+    Other,
+}
+
+pub fn set_proxy(enable: bool, port: u16) -> Result<(), Box<dyn std::error::Error>> {
+    match *DESKTOP {
+        Desktop::Gnome => gnome_set_proxy(enable, port),
+        Desktop::Kde => kde_set_proxy(enable, port),
+        Desktop::Other => env_file_set_proxy(enable, port),
+    }
+}
+
+pub fn is_enabled() -> bool {
+    match *DESKTOP {
+        Desktop::Gnome => gnome_is_enabled(),
+        Desktop::Kde => kde_is_enabled(),
+        Desktop::Other => env_file_path().is_some_and(|p| p.exists()),
+    }
+}
+
+/// 根据 XDG_CURRENT_DESKTOP / DESKTOP_SESSION 判断桌面环境，GNOME 系桌面还需有 gsettings
+fn detect_desktop(session: &str, has_gsettings: impl Fn() -> bool) -> Desktop {
+    let session = session.to_uppercase();
+    for name in session.split(':') {
+        match name.trim() {
+            "KDE" | "PLASMA" => return Desktop::Kde,
+            // 以下桌面均使用 org.gnome.system.proxy
+            "GNOME" | "UNITY" | "CINNAMON" | "BUDGIE" | "PANTHEON" | "MATE" | "X-CINNAMON"
+                if has_gsettings() =>
+            {
+                return Desktop::Gnome;
+            }
+            _ => {}
+        }
+    }
+
+    Desktop::Other
+}
+
+fn command_exists(program: &str) -> bool {
+    Command::new(program).arg("--version").output().is_ok()
+}
+
+fn run(program: &str, args: &[&str]) -> Result<String, Box<dyn std::error::Error>> {
+    let output = Command::new(program).args(args).output()?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(format!("{} failed: {}", program, stderr.trim()).into());
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+fn config_home() -> Option<PathBuf> {
+    env::var_os("XDG_CONFIG_HOME")
+        .filter(|v| !v.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")))
+}
+
+// ==================== GNOME ====================
+
+fn gnome_set_proxy(enable: bool, port: u16) -> Result<(), Box<dyn std::error::Error>> {
+    if !enable {
+        run("gsettings", &["set", GNOME_PROXY_SCHEMA, "mode", "none"])?;
+        return Ok(());
+    }
+
+    let port = port.to_string();
+    for scheme in ["http", "https", "socks"] {
+        let schema = format!("{}.{}", GNOME_PROXY_SCHEMA, scheme);
+        run("gsettings", &["set", &schema, "host", PROXY_HOST])?;
+        run("gsettings", &["set", &schema, "port", &port])?;
+    }
+
+    let ignore_hosts = format!(
+        "[{}]",
+        NO_PROXY
+            .split(',')
+            .map(|h| format!("'{}'", h))
+            .collect::<Vec<_>>()
+            .join(", ")
+    );
+    run(
+        "gsettings",
+        &["set", GNOME_PROXY_SCHEMA, "ignore-hosts", &ignore_hosts],
+    )?;
+    run("gsettings", &["set", GNOME_PROXY_SCHEMA, "mode", "manual"])?;
+
+    Ok(())
+}
+
+fn gnome_is_enabled() -> bool {
+    run("gsettings", &["get", GNOME_PROXY_SCHEMA, "mode"])
+        .map(|mode| mode.trim_matches('\'') == "manual")
+        .unwrap_or(false)
+}
+
+// ==================== KDE ====================
+
+fn kioslaverc_path() -> Option<PathBuf> {
+    config_home().map(|dir| dir.join("kioslaverc"))
+}
+
+fn kde_set_proxy(enable: bool, port: u16) -> Result<(), Box<dyn std::error::Error>> {
+    let path = kioslaverc_path().ok_or("无法确定 KDE 配置目录")?;
+    let content = fs::read_to_string(&path).unwrap_or_default();
+
+    // ProxyType: 0 = 不使用代理, 1 = 手动配置
+    let mut values = vec![("ProxyType", if enable { "1" } else { "0" }.to_string())];
+    if enable {
+        values.push(("httpProxy", format!("http://{} {}", PROXY_HOST, port)));
+        values.push(("httpsProxy", format!("http://{} {}", PROXY_HOST, port)));
+        values.push(("socksProxy", format!("socks://{} {}", PROXY_HOST, port)));
+        values.push(("NoProxyFor", NO_PROXY.to_string()));
+    }
+
+    let updated = update_ini_group(&content, KDE_PROXY_GROUP, &values);
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    fs::write(&path, updated)?;
+
+    // 通知 KIO 重新读取代理配置，失败不影响设置结果
+    let _ = Command::new("dbus-send")
+        .args([
+            "--type=signal",
+            "/KIO/Scheduler",
+            "org.kde.KIO.Scheduler.reparseSlaveConfiguration",
+            "string:",
+        ])
+        .output();
+
+    Ok(())
+}
+
+fn kde_is_enabled() -> bool {
+    let Some(content) = kioslaverc_path().and_then(|p| fs::read_to_string(p).ok()) else {
+        return false;
+    };
+
+    let mut in_group = false;
+    for line in content.lines() {
+        let line = line.trim();
+        if line.starts_with('[') {
+            in_group = line == KDE_PROXY_GROUP;
+        } else if in_group && let Some(value) = line.strip_prefix("ProxyType=") {
+            return value.trim() == "1";
+        }
+    }
+    false
+}
+
+/// 在 INI 文本中更新指定分组的键值，保留其余内容不变
+fn update_ini_group(content: &str, group: &str, values: &[(&str, String)]) -> String {
+    let mut lines: Vec<String> = Vec::new();
+    let mut pending: Vec<&(&str, String)> = values.iter().collect();
+    let mut in_group = false;
+    let mut found_group = false;
+
+    // 补充的键放在分组末尾的空行之前
+    let flush = |lines: &mut Vec<String>, pending: &mut Vec<&(&str, String)>| {
+        let at = lines
+            .iter()
+            .rposition(|l| !l.trim().is_empty())
+            .map_or(0, |i| i + 1);
+        lines.splice(
+            at..at,
+            pending
+                .drain(..)
+                .map(|(key, value)| format!("{}={}", key, value)),
+        );
+    };
+
+    for line in content.lines() {
+        let trimmed = line.trim();
+        if trimmed.starts_with('[') {
+            if in_group {
+                flush(&mut lines, &mut pending);
+            }
+            in_group = trimmed == group;
+            found_group |= in_group;
+            lines.push(line.to_string());
+            continue;
+        }
+
+        if in_group {
+            let key = trimmed.split('=').next().unwrap_or("").trim();
+            if let Some(pos) = pending.iter().position(|(k, _)| *k == key) {
+                let (k, v) = pending.remove(pos);
+                lines.push(format!("{}={}", k, v));
+                continue;
+            }
+        }
+        lines.push(line.to_string());
+    }
+
+    if in_group {
+        flush(&mut lines, &mut pending);
+    } else if !found_group {
+        if lines.last().is_some_and(|l| !l.trim().is_empty()) {
+            lines.push(String::new());
+        }
+        lines.push(group.to_string());
+        flush(&mut lines, &mut pending);
+    }
+
+    let mut result = lines.join("\n");
+    result.push('\n');
+    result
+}
+
+// ==================== 环境变量文件 ====================
+
+/// systemd 用户环境变量文件，下次登录后对所有会话生效
+fn env_file_path() -> Option<PathBuf> {
+    config_home().map(|dir| dir.join("environment.d").join(ENV_FILE_NAME))
+}
+
+fn env_file_set_proxy(enable: bool, port: u16) -> Result<(), Box<dyn std::error::Error>> {
+    let path = env_file_path().ok_or("无法确定用户配置目录")?;
+
+    if !enable {
+        if path.exists() {
+            fs::remove_file(&path)?;
+        }
+        return Ok(());
+    }
+
+    let http_proxy = format!("http://{}:{}", PROXY_HOST, port);
+    let socks_proxy = format!("socks5://{}:{}", PROXY_HOST, port);
+    let mut content = String::from("# 由 NetProxy 生成，关闭系统代理时自动删除\n");
+    for (key, value) in [
+        ("http_proxy", &http_proxy),
+        ("https_proxy", &http_proxy),
+        ("all_proxy", &socks_proxy),
+    ] {
+        content.push_str(&format!("{}={}\n", key, value));
+        content.push_str(&format!("{}={}\n", key.to_uppercase(), value));
+    }
+    content.push_str(&format!("no_proxy={}\nNO_PROXY={}\n", NO_PROXY, NO_PROXY));
+
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    fs::write(&path, content)?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn values(port: u16) -> Vec<(&'static str, String)> {
+        vec![
+            ("ProxyType", "1".to_string()),
+            ("httpProxy", format!("http://127.0.0.1 {}", port)),
+        ]
+    }
+
+    #[test]
+    fn adds_group_to_empty_file() {
+        assert_eq!(
+            update_ini_group("", KDE_PROXY_GROUP, &values(7890)),
+            "[Proxy Settings]\nProxyType=1\nhttpProxy=http://127.0.0.1 7890\n"
+        );
+    }
+
+    #[test]
+    fn appends_missing_group() {
+        let content = "[Cache]\nCacheSize=5120\n";
+        assert_eq!(
+            update_ini_group(content, KDE_PROXY_GROUP, &values(7890)),
+            "[Cache]\nCacheSize=5120\n\n[Proxy Settings]\nProxyType=1\nhttpProxy=http://127.0.0.1 7890\n"
+        );
+    }
+
+    #[test]
+    fn updates_existing_group_in_place() {
+        let content = "\
+[Proxy Settings]
+# 手动配置
+ProxyType=0
+NoProxyFor=localhost
+
+[Cache]
+ProxyType=0
+";
+        assert_eq!(
+            update_ini_group(content, KDE_PROXY_GROUP, &values(1080)),
+            "\
+[Proxy Settings]
+# 手动配置
+ProxyType=1
+NoProxyFor=localhost
+httpProxy=http://127.0.0.1 1080
+
+[Cache]
+ProxyType=0
+"
+        );
+    }
+
+    #[test]
+    fn updates_group_at_end_of_file() {
+        let content =
+            "[Cache]\nCacheSize=5120\n\n[Proxy Settings]\nhttpProxy=http://127.0.0.1 80\n\n";
+        assert_eq!(
+            update_ini_group(content, KDE_PROXY_GROUP, &values(7890)),
+            "[Cache]\nCacheSize=5120\n\n[Proxy Settings]\nhttpProxy=http://127.0.0.1 7890\nProxyType=1\n\n"
+        );
+    }
+
+    #[test]
+    fn detects_desktop() {
+        assert_eq!(detect_desktop("KDE", || true), Desktop::Kde);
+        assert_eq!(detect_desktop("plasma", || false), Desktop::Kde);
+        assert_eq!(detect_desktop("ubuntu:GNOME", || true), Desktop::Gnome);
+        assert_eq!(detect_desktop("X-Cinnamon", || true), Desktop::Gnome);
+        // 没有 gsettings 时退回环境变量文件
+        assert_eq!(detect_desktop("GNOME", || false), Desktop::Other);
+        assert_eq!(detect_desktop("XFCE", || true), Desktop::Other);
+        assert_eq!(detect_desktop("", || true), Desktop::Other);
+    }
+}
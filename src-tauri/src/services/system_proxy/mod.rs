@@ -0,0 +1,13 @@
+// 系统代理设置：对外统一提供 set_proxy / is_enabled，按平台选择实现
+// - Windows: 写注册表 Internet Settings
+// - Linux: 按桌面环境驱动 GNOME (gsettings)、KDE (kioslaverc) 或环境变量文件
+
+#[cfg(target_os = "linux")]
+mod linux;
+#[cfg(windows)]
+mod windows;
+
+#[cfg(target_os = "linux")]
+pub use linux::{is_enabled, set_proxy};
+#[cfg(windows)]
+pub use windows::{is_enabled, set_proxy};
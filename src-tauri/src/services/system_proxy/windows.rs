@@ -1,5 +1,5 @@
-use winreg::enums::HKEY_CURRENT_USER;
 use winreg::RegKey;
+use winreg::enums::HKEY_CURRENT_USER;
 
 const INTERNET_SETTINGS: &str = r"Software\Microsoft\Windows\CurrentVersion\Internet Settings";
 
@@ -31,25 +31,22 @@ pub fn is_enabled() -> bool {
 }
 
 fn notify_proxy_change() {
-    #[cfg(windows)]
-    {
-        use windows::Win32::Networking::WinInet::{
-            InternetSetOptionW, INTERNET_OPTION_REFRESH, INTERNET_OPTION_SETTINGS_CHANGED,
-        };
-        unsafe {
-            // 通知 IE/系统 代理设置已更改
-            let _ = InternetSetOptionW(
-                None,
-                INTERNET_OPTION_SETTINGS_CHANGED,
-                Some(std::ptr::null() as *const std::ffi::c_void),
-                0,
-            );
-            let _ = InternetSetOptionW(
-                None,
-                INTERNET_OPTION_REFRESH,
-                Some(std::ptr::null() as *const std::ffi::c_void),
-                0,
-            );
-        }
+    use windows::Win32::Networking::WinInet::{
+        INTERNET_OPTION_REFRESH, INTERNET_OPTION_SETTINGS_CHANGED, InternetSetOptionW,
+    };
+    unsafe {
+        // 通知 IE/系统 代理设置已更改
+        let _ = InternetSetOptionW(
+            None,
+            INTERNET_OPTION_SETTINGS_CHANGED,
+            Some(std::ptr::null() as *const std::ffi::c_void),
+            0,
+        );
+        let _ = InternetSetOptionW(
+            None,
+            INTERNET_OPTION_REFRESH,
+            Some(std::ptr::null() as *const std::ffi::c_void),
+            0,
+        );
     }
 }
@@ -0,0 +1,270 @@
+// Xray 核心进程管理：启动、停止，并由监控线程检测异常退出、按退避时间自动重启
+pub mod output;
+pub mod settings;
+pub mod validate;
+
+use crate::models::{CoreExit, ProxyStatus};
+use crate::services::api::stats;
+use crate::services::{inbounds, ports, system_proxy};
+use crate::utils::{paths, process, time};
+use once_cell::sync::OnceCell;
+use std::process::{Child, Command, ExitStatus, Stdio};
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicU32, AtomicU64, Ordering};
+use std::thread;
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Emitter};
+
+/// 核心异常退出事件
+pub const EXIT_EVENT: &str = "xray-exited";
+/// 端口冲突后自动改用其他端口的事件
+pub const PORTS_EVENT: &str = "ports-reassigned";
+
+static XRAY_PROCESS: Mutex<Option<Child>> = Mutex::new(None);
+static CURRENT_NODE: Mutex<Option<String>> = Mutex::new(None);
+static LAST_EXIT: Mutex<Option<CoreExit>> = Mutex::new(None);
+// 每次启动或停止递增，使旧的监控线程失效
+static GENERATION: AtomicU64 = AtomicU64::new(0);
+static RESTART_ATTEMPTS: AtomicU32 = AtomicU32::new(0);
+static APP: OnceCell<AppHandle> = OnceCell::new();
+
+// 异常退出时附带的输出行数
+const TAIL_LINES: usize = 50;
+const POLL_INTERVAL: Duration = Duration::from_millis(500);
+// 运行超过该时长后视为稳定，重置重启计数
+const STABLE_AFTER: Duration = Duration::from_secs(60);
+const MAX_BACKOFF: Duration = Duration::from_secs(30);
+
+/// 保存 AppHandle 用于发送事件，在 setup 中调用
+pub fn init(app: AppHandle) {
+    let _ = APP.set(app);
+}
+
+pub fn start(node_file: &str) -> Result<(), Box<dyn std::error::Error>> {
+    preflight(node_file)?;
+    RESTART_ATTEMPTS.store(0, Ordering::Relaxed);
+    *LAST_EXIT.lock().unwrap() = None;
+    launch(node_file)
+}
+
+/// 启动前检查，手动启动与自动重启共用：处理端口冲突、开启流量统计并校验配置，
+/// 失败时保留正在运行的实例
+fn preflight(node_file: &str) -> Result<(), Box<dyn std::error::Error>> {
+    // 端口被其他程序占用时 xray 会立即退出
+    let conflicts: Vec<_> = ports::check(current_pid())
+        .into_iter()
+        .filter(|c| !c.available)
+        .collect();
+    if !conflicts.is_empty() && !settings::load().auto_select_ports {
+        return Err(ports::describe(&conflicts).into());
+    }
+    // 没有冲突时同时清除上次自动改用的端口
+    let changes = ports::reassign(&conflicts, current_pid())?;
+    if !changes.is_empty()
+        && let Some(app) = APP.get()
+    {
+        let _ = app.emit(PORTS_EVENT, changes);
+    }
+
+    // 流量统计依赖 policy 中的 stats 开关
+    stats::enable_policy_stats()?;
+
+    let result = validate::validate(Some(node_file))?;
+    if !result.valid {
+        return Err(validate::summary(&result).into());
+    }
+    Ok(())
+}
+
+fn launch(node_file: &str) -> Result<(), Box<dyn std::error::Error>> {
+    // 先停止已有进程
+    stop()?;
+
+    let xray_path = paths::get_xray_path();
+    let confdir = paths::get_confdir();
+    let state_dir = paths::get_state_dir();
+    let node_path = paths::get_outbounds_dir().join(node_file);
+
+    // 确保 logs 目录存在
+    let logs_dir = paths::get_logs_dir();
+    if !logs_dir.exists() {
+        std::fs::create_dir_all(&logs_dir)?;
+    }
+
+    let mut child = process::configure_background(
+        Command::new(&xray_path)
+            .arg("-confdir")
+            .arg(&confdir)
+            .arg("-c")
+            .arg(&node_path)
+            .current_dir(&state_dir) // 设置工作目录，日志相对路径基于此
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped()),
+    )
+    .spawn()?;
+
+    let first_seq = output::next_seq();
+    output::capture(child.stdout.take(), "stdout");
+    output::capture(child.stderr.take(), "stderr");
+
+    let generation = GENERATION.fetch_add(1, Ordering::SeqCst) + 1;
+    *XRAY_PROCESS.lock().unwrap() = Some(child);
+    *CURRENT_NODE.lock().unwrap() = Some(node_file.to_string());
+    supervise(generation, node_file.to_string(), first_seq);
+
+    // 启动成功，设置系统代理
+    system_proxy::set_proxy(true, inbounds::proxy_port())?;
+
+    Ok(())
+}
+
+pub fn stop() -> Result<(), Box<dyn std::error::Error>> {
+    GENERATION.fetch_add(1, Ordering::SeqCst);
+    if let Some(mut child) = XRAY_PROCESS.lock().unwrap().take() {
+        process::terminate(&mut child);
+    }
+    *CURRENT_NODE.lock().unwrap() = None;
+
+    // 关闭系统代理
+    system_proxy::set_proxy(false, inbounds::proxy_port())?;
+
+    Ok(())
+}
+
+/// 监控线程：进程退出后记录原因、关闭系统代理、通知前端，并按设置自动重启
+fn supervise(generation: u64, node_file: String, first_seq: u64) {
+    thread::spawn(move || {
+        let started = Instant::now();
+        let status = loop {
+            thread::sleep(POLL_INTERVAL);
+
+            let mut process = XRAY_PROCESS.lock().unwrap();
+            // 已被手动停止或重新启动
+            if GENERATION.load(Ordering::SeqCst) != generation {
+                return;
+            }
+            match process.as_mut().map(Child::try_wait) {
+                Some(Ok(None)) => continue,
+                Some(Ok(Some(status))) => {
+                    process.take();
+                    break Some(status);
+                }
+                Some(Err(_)) => {
+                    process.take();
+                    break None;
+                }
+                None => return,
+            }
+        };
+        *CURRENT_NODE.lock().unwrap() = None;
+
+        // 等待读取线程收完最后的输出
+        thread::sleep(Duration::from_millis(200));
+
+        if started.elapsed() >= STABLE_AFTER {
+            RESTART_ATTEMPTS.store(0, Ordering::Relaxed);
+        }
+        let settings = settings::load();
+        let attempt = RESTART_ATTEMPTS.fetch_add(1, Ordering::Relaxed) + 1;
+        let restart_in =
+            (settings.auto_restart && attempt <= settings.max_restarts).then(|| backoff(attempt));
+
+        let exit = CoreExit {
+            node_file: node_file.clone(),
+            code: status.and_then(|s| s.code()),
+            message: describe(status),
+            at: time::now_secs(),
+            output_tail: output::lines_since(first_seq, TAIL_LINES)
+                .into_iter()
+                .filter(|l| l.stream != output::APP_STREAM)
+                .map(|l| l.line)
+                .collect(),
+            restart_in_ms: restart_in.map(|d| d.as_millis() as u64),
+        };
+        *LAST_EXIT.lock().unwrap() = Some(exit.clone());
+
+        // 避免浏览器继续指向已失效的端口
+        if let Err(e) = system_proxy::set_proxy(false, inbounds::proxy_port()) {
+            output::log(format!("关闭系统代理失败: {}", e));
+        }
+        if let Some(app) = APP.get() {
+            let _ = app.emit(EXIT_EVENT, exit);
+        }
+
+        if let Some(delay) = restart_in {
+            thread::sleep(delay);
+            // 等待期间用户未手动启动或停止
+            if GENERATION.load(Ordering::SeqCst) == generation
+                && let Err(e) = preflight(&node_file).and_then(|_| launch(&node_file))
+            {
+                output::log(format!("自动重启 xray 失败: {}", e));
+            }
+        }
+    });
+}
+
+/// 第 n 次重启的等待时间：1s、2s、4s… 最长 30s
+fn backoff(attempt: u32) -> Duration {
+    Duration::from_secs(1u64 << attempt.saturating_sub(1).min(5)).min(MAX_BACKOFF)
+}
+
+fn describe(status: Option<ExitStatus>) -> String {
+    match status {
+        Some(status) => format!("xray 异常退出 ({})", status),
+        None => "无法获取 xray 进程状态".to_string(),
+    }
+}
+
+/// 正在运行的 xray 进程 PID
+pub fn current_pid() -> Option<u32> {
+    XRAY_PROCESS.lock().unwrap().as_ref().map(Child::id)
+}
+
+/// 正在运行的节点文件
+pub fn current_node() -> Option<String> {
+    CURRENT_NODE.lock().unwrap().clone()
+}
+
+/// 节点文件被重命名时同步记录，不重启进程
+pub fn rename_current_node(node_file: &str) {
+    let mut current = CURRENT_NODE.lock().unwrap();
+    if current.is_some() {
+        *current = Some(node_file.to_string());
+    }
+}
+
+pub fn get_status() -> ProxyStatus {
+    // 不持有进程锁调用外部命令，避免阻塞监控线程与启动停止
+    let running = XRAY_PROCESS.lock().unwrap().is_some();
+    let current_node = CURRENT_NODE.lock().unwrap().clone();
+    let last_exit = LAST_EXIT.lock().unwrap().clone();
+
+    // 获取系统代理真实状态
+    let system_proxy_enabled = system_proxy::is_enabled();
+
+    ProxyStatus {
+        running,
+        system_proxy: system_proxy_enabled,
+        current_node,
+        port: inbounds::proxy_port(),
+        error: last_exit
+            .as_ref()
+            .filter(|_| !running)
+            .map(|e| e.message.clone()),
+        last_exit,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn backoff_doubles_up_to_limit() {
+        let delays: Vec<u64> = (1..=8).map(|n| backoff(n).as_secs()).collect();
+        assert_eq!(delays, [1, 2, 4, 8, 16, 30, 30, 30]);
+        assert_eq!(backoff(0), Duration::from_secs(1));
+        assert_eq!(backoff(u32::MAX), MAX_BACKOFF);
+    }
+}
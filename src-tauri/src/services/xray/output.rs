@@ -0,0 +1,148 @@
+// 核心输出：xray 的 stdout/stderr 以及应用后台任务的消息写入内存环形缓冲
+// 与滚动日志文件 logs/core.log，并推送给通过 Channel 订阅的前端日志视图
+use crate::models::CoreLogLine;
+use crate::utils::{paths, time};
+use std::collections::VecDeque;
+use std::fs::{self, File, OpenOptions};
+use std::io::{BufRead, BufReader, Read, Write};
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::thread;
+use tauri::ipc::Channel;
+
+/// 内存中保留的行数
+const BUFFER_LINES: usize = 2000;
+/// 单个日志文件的大小上限
+const MAX_LOG_SIZE: u64 = 5 * 1024 * 1024;
+/// 保留的历史日志文件数 (core.log.1 ~ core.log.3)
+const MAX_LOG_FILES: usize = 3;
+const LOG_FILE: &str = "core.log";
+/// 应用自身消息使用的 stream 名称
+pub const APP_STREAM: &str = "app";
+
+static BUFFER: Mutex<VecDeque<CoreLogLine>> = Mutex::new(VecDeque::new());
+static NEXT_SEQ: AtomicU64 = AtomicU64::new(1);
+static LOG_WRITER: Mutex<Option<LogWriter>> = Mutex::new(None);
+static SUBSCRIBERS: Mutex<Vec<Channel<CoreLogLine>>> = Mutex::new(Vec::new());
+
+struct LogWriter {
+    file: File,
+    size: u64,
+}
+
+/// 在后台线程中逐行读取子进程输出
+pub fn capture<R: Read + Send + 'static>(reader: Option<R>, stream: &'static str) {
+    let Some(reader) = reader else {
+        return;
+    };
+
+    thread::spawn(move || {
+        for line in BufReader::new(reader).lines().map_while(Result::ok) {
+            push(stream, line);
+        }
+    });
+}
+
+/// 记录应用后台任务的消息（保存失败、自动切换失败等），与核心输出一起显示
+pub fn log(message: impl Into<String>) {
+    push(APP_STREAM, message.into());
+}
+
+fn push(stream: &str, line: String) {
+    let entry = CoreLogLine {
+        seq: NEXT_SEQ.fetch_add(1, Ordering::Relaxed),
+        timestamp: time::now_secs(),
+        stream: stream.to_string(),
+        line,
+    };
+
+    {
+        let mut buffer = BUFFER.lock().unwrap();
+        if buffer.len() >= BUFFER_LINES {
+            buffer.pop_front();
+        }
+        buffer.push_back(entry.clone());
+    }
+
+    write_log(&entry);
+
+    // 发送失败说明前端已关闭该 Channel，移除订阅
+    SUBSCRIBERS
+        .lock()
+        .unwrap()
+        .retain(|channel| channel.send(entry.clone()).is_ok());
+}
+
+fn write_log(entry: &CoreLogLine) {
+    let mut writer = LOG_WRITER.lock().unwrap();
+    if writer.as_ref().is_some_and(|w| w.size >= MAX_LOG_SIZE) {
+        *writer = None;
+        rotate();
+    }
+    if writer.is_none() {
+        *writer = open_log();
+    }
+
+    if let Some(w) = writer.as_mut() {
+        let text = format!("[{}] [{}] {}\n", entry.timestamp, entry.stream, entry.line);
+        if w.file.write_all(text.as_bytes()).is_ok() {
+            w.size += text.len() as u64;
+        }
+    }
+}
+
+fn open_log() -> Option<LogWriter> {
+    let logs_dir = paths::get_logs_dir();
+    fs::create_dir_all(&logs_dir).ok()?;
+    let file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(logs_dir.join(LOG_FILE))
+        .ok()?;
+    let size = file.metadata().map(|m| m.len()).unwrap_or(0);
+    Some(LogWriter { file, size })
+}
+
+/// core.log -> core.log.1 -> core.log.2 ...，超出数量的最旧文件被覆盖
+fn rotate() {
+    let logs_dir = paths::get_logs_dir();
+    for index in (1..MAX_LOG_FILES).rev() {
+        let from = logs_dir.join(format!("{}.{}", LOG_FILE, index));
+        if from.exists() {
+            let _ = fs::rename(&from, logs_dir.join(format!("{}.{}", LOG_FILE, index + 1)));
+        }
+    }
+    let _ = fs::rename(
+        logs_dir.join(LOG_FILE),
+        logs_dir.join(format!("{}.1", LOG_FILE)),
+    );
+}
+
+/// 下一行输出的序号，用于标记一次运行的起点
+pub fn next_seq() -> u64 {
+    NEXT_SEQ.load(Ordering::Relaxed)
+}
+
+/// 序号不小于 since 的最近 limit 行
+pub fn lines_since(since: u64, limit: usize) -> Vec<CoreLogLine> {
+    let buffer = BUFFER.lock().unwrap();
+    let lines: Vec<&CoreLogLine> = buffer.iter().filter(|l| l.seq >= since).collect();
+    lines[lines.len().saturating_sub(limit)..]
+        .iter()
+        .map(|l| (*l).clone())
+        .collect()
+}
+
+/// 订阅实时输出，返回缓冲区中已有的内容
+pub fn subscribe(channel: Channel<CoreLogLine>) -> Vec<CoreLogLine> {
+    // 先注册再读取缓冲，避免两者之间的输出丢失（前端按 seq 去重）
+    SUBSCRIBERS.lock().unwrap().push(channel);
+    lines_since(0, BUFFER_LINES)
+}
+
+pub fn unsubscribe(channel_id: u32) {
+    SUBSCRIBERS
+        .lock()
+        .unwrap()
+        .retain(|channel| channel.id() != channel_id);
+}
@@ -0,0 +1,13 @@
+// 核心进程设置：保存在配置目录的 core.json
+use crate::models::CoreSettings;
+use crate::utils::settings;
+
+const FILE_NAME: &str = "core.json";
+
+pub fn load() -> CoreSettings {
+    settings::load(FILE_NAME)
+}
+
+pub fn save(value: &CoreSettings) -> Result<(), Box<dyn std::error::Error>> {
+    settings::save(FILE_NAME, value)
+}
@@ -0,0 +1,208 @@
+// 配置预检：用 xray run -test 校验 confdir 与节点文件，切换节点前避免加载损坏的配置
+use crate::models::{ValidationIssue, ValidationResult};
+use crate::utils::{paths, process};
+use std::process::{Command, Stdio};
+
+/// 校验 confdir 及可选的节点文件（相对于 outbounds 目录）
+pub fn validate(node_file: Option<&str>) -> Result<ValidationResult, Box<dyn std::error::Error>> {
+    let mut command = Command::new(paths::get_xray_path());
+    command
+        .arg("run")
+        .arg("-test")
+        .arg("-confdir")
+        .arg(paths::get_confdir());
+    if let Some(node_file) = node_file {
+        command
+            .arg("-c")
+            .arg(paths::get_outbounds_dir().join(node_file));
+    }
+
+    let output = process::configure_background(
+        command
+            .current_dir(paths::get_state_dir())
+            .stdin(Stdio::null()),
+    )
+    .output()
+    .map_err(|e| format!("无法运行 xray: {}", e))?;
+
+    let mut text = String::from_utf8_lossy(&output.stdout).into_owned();
+    text.push_str(&String::from_utf8_lossy(&output.stderr));
+
+    let valid = output.status.success();
+    let errors = if valid {
+        Vec::new()
+    } else {
+        parse_errors(&text)
+    };
+
+    Ok(ValidationResult {
+        valid,
+        errors,
+        output: text.trim().to_string(),
+    })
+}
+
+/// 校验结果的简短说明，用于拒绝切换时的错误信息
+pub fn summary(result: &ValidationResult) -> String {
+    let reasons: Vec<String> = result
+        .errors
+        .iter()
+        .map(|e| match &e.file {
+            Some(file) => format!("{}: {}", file, e.message),
+            None => e.message.clone(),
+        })
+        .collect();
+
+    if reasons.is_empty() {
+        format!("配置校验失败: {}", result.output)
+    } else {
+        format!("配置校验失败: {}", reasons.join("; "))
+    }
+}
+
+/// 解析形如 `Failed to start: main: failed to load config files: [...] > infra/conf: ...` 的输出
+fn parse_errors(output: &str) -> Vec<ValidationIssue> {
+    let issues: Vec<ValidationIssue> = output
+        .lines()
+        .filter_map(|line| {
+            let line = line.trim();
+            line.strip_prefix("Failed to start:")
+                .or_else(|| line.strip_prefix("Failed to load config:"))
+                .map(str::trim)
+        })
+        .map(issue)
+        .collect();
+
+    if !issues.is_empty() {
+        return issues;
+    }
+
+    // 无法识别格式时退回最后一行非空输出
+    output
+        .lines()
+        .map(str::trim)
+        .rfind(|l| !l.is_empty())
+        .map(|line| vec![issue(line)])
+        .unwrap_or_default()
+}
+
+fn issue(text: &str) -> ValidationIssue {
+    let chain: Vec<String> = text
+        .split(" > ")
+        .map(str::trim)
+        .filter(|s| !s.is_empty())
+        .map(str::to_string)
+        .collect();
+
+    // 第一段通常列出全部已加载的文件，不能据此判断出错的文件
+    let file = chain.iter().skip(1).rev().find_map(|part| find_file(part));
+
+    ValidationIssue {
+        message: chain.last().cloned().unwrap_or_else(|| text.to_string()),
+        file,
+        chain,
+    }
+}
+
+/// 输出中提到的第一个 json 文件名
+fn find_file(text: &str) -> Option<String> {
+    text.split(|c: char| c.is_whitespace() || matches!(c, '[' | ']' | '"' | '\'' | ','))
+        .map(|token| token.trim_end_matches([':', '.']))
+        .find(|token| token.ends_with(".json"))
+        .map(|token| {
+            std::path::Path::new(token)
+                .file_name()
+                .map(|name| name.to_string_lossy().to_string())
+                .unwrap_or_else(|| token.to_string())
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BANNER: &str = "Xray 1.8.24 (Xray, Penetrates Everything.) 3b1b1d6 (go1.22.5 linux/amd64)
+A unified platform for anti-censorship.
+2024/05/01 12:00:00 [Info] infra/conf/serial: Reading config: &{Name:/etc/xray/confdir/01_inbounds.json Format:json}
+2024/05/01 12:00:00 [Info] infra/conf/serial: Reading config: &{Name:/etc/xray/confdir/05_api.json Format:json}
+2024/05/01 12:00:00 [Info] infra/conf/serial: Reading config: &{Name:/etc/xray/outbounds/sub/hk-01.json Format:json}
+";
+
+    #[test]
+    fn parses_build_failure_chain() {
+        let output = format!(
+            "{}Failed to start: main: failed to load config files: [/etc/xray/confdir/01_inbounds.json /etc/xray/confdir/05_api.json /etc/xray/outbounds/sub/hk-01.json] > infra/conf: failed to build outbound config with tag proxy > infra/conf: failed to build outbound handler for protocol vless > infra/conf: VLESS users: invalid user > common/uuid: invalid UUID: abc\n",
+            BANNER
+        );
+        let issues = parse_errors(&output);
+        assert_eq!(issues.len(), 1);
+        let issue = &issues[0];
+        assert_eq!(issue.chain.len(), 5);
+        assert_eq!(
+            issue.chain[1],
+            "infra/conf: failed to build outbound config with tag proxy"
+        );
+        assert_eq!(issue.message, "common/uuid: invalid UUID: abc");
+        // 第一段列出的文件不作为出错文件
+        assert_eq!(issue.file, None);
+    }
+
+    #[test]
+    fn finds_file_in_decode_failure() {
+        let output = format!(
+            "{}Failed to start: main: failed to load config files: [/etc/xray/confdir/01_inbounds.json /etc/xray/outbounds/sub/hk-01.json] > infra/conf/serial: failed to read config: &{{Name:/etc/xray/outbounds/sub/hk-01.json Format:json}} > infra/conf/serial: failed to parse json config > infra/conf/json: invalid character '}}' looking for beginning of object key string in line 12 char 5\n",
+            BANNER
+        );
+        let issues = parse_errors(&output);
+        assert_eq!(issues.len(), 1);
+        assert_eq!(issues[0].file.as_deref(), Some("hk-01.json"));
+        assert_eq!(
+            issues[0].message,
+            "infra/conf/json: invalid character '}' looking for beginning of object key string in line 12 char 5"
+        );
+    }
+
+    #[test]
+    fn parses_load_config_prefix() {
+        let issues = parse_errors(
+            "Failed to load config: main: failed to read config files > infra/conf/serial: failed to read config file \"/etc/xray/confdir/03_routing.json\": open: no such file or directory",
+        );
+        assert_eq!(issues.len(), 1);
+        assert_eq!(issues[0].file.as_deref(), Some("03_routing.json"));
+    }
+
+    #[test]
+    fn falls_back_to_last_line() {
+        let issues = parse_errors(
+            "panic: runtime error: invalid memory address\n\ngoroutine 1 [running]:\n  main.main()\n\n",
+        );
+        assert_eq!(issues.len(), 1);
+        assert_eq!(issues[0].message, "main.main()");
+        assert_eq!(issues[0].chain, vec!["main.main()".to_string()]);
+        assert_eq!(issues[0].file, None);
+
+        assert!(parse_errors("  \n").is_empty());
+    }
+
+    #[test]
+    fn finds_json_file_names() {
+        assert_eq!(
+            find_file("failed to read config: &{Name:/etc/xray/outbounds/a.json Format:json}")
+                .as_deref(),
+            Some("a.json")
+        );
+        assert_eq!(
+            find_file("invalid config in 'node.json': bad port").as_deref(),
+            Some("node.json")
+        );
+        assert_eq!(
+            find_file("[01_inbounds.json, 02_outbounds.json]").as_deref(),
+            Some("01_inbounds.json")
+        );
+        assert_eq!(
+            find_file("loaded 05_api.json.").as_deref(),
+            Some("05_api.json")
+        );
+        assert_eq!(find_file("invalid UUID"), None);
+    }
+}
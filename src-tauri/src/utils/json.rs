@@ -0,0 +1,101 @@
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+use std::error::Error;
+use std::fs::{self, File};
+use std::io::{ErrorKind, Write};
+use std::path::{Path, PathBuf};
+
+/// 读取 JSON 文件；文件不存在时返回默认值，读取或解析失败时返回错误
+pub fn read_json<T: DeserializeOwned + Default>(path: &Path) -> Result<T, Box<dyn Error>> {
+    match fs::read_to_string(path) {
+        Ok(content) => serde_json::from_str(&content)
+            .map_err(|e| format!("{} 格式错误: {}", path.display(), e).into()),
+        Err(e) if e.kind() == ErrorKind::NotFound => Ok(T::default()),
+        Err(e) => Err(format!("无法读取 {}: {}", path.display(), e).into()),
+    }
+}
+
+/// 读取 JSON 文件，失败时使用默认值
+///
+/// 无法解析的文件改名为 *.corrupt 保留，避免之后保存的默认值覆盖原有内容。
+pub fn load_json<T: DeserializeOwned + Default>(path: &Path) -> T {
+    let Ok(content) = fs::read_to_string(path) else {
+        return T::default();
+    };
+    serde_json::from_str(&content).unwrap_or_else(|_| {
+        let _ = fs::rename(path, with_suffix(path, "corrupt"));
+        T::default()
+    })
+}
+
+/// 写入格式化的 JSON 文件，父目录不存在时自动创建
+///
+/// 先写入同目录的临时文件再改名替换，写入中途失败或退出不会留下半个文件。
+pub fn save_json<T: Serialize + ?Sized>(path: &Path, value: &T) -> Result<(), Box<dyn Error>> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let content = serde_json::to_string_pretty(value)?;
+    let temp = with_suffix(path, "tmp");
+    let result = File::create(&temp)
+        .and_then(|mut file| {
+            file.write_all(content.as_bytes())?;
+            file.sync_all()
+        })
+        .and_then(|_| fs::rename(&temp, path));
+    if result.is_err() {
+        let _ = fs::remove_file(&temp);
+    }
+    Ok(result?)
+}
+
+/// 同目录下追加后缀的文件名，如 groups.json.tmp
+fn with_suffix(path: &Path, suffix: &str) -> PathBuf {
+    let mut name = path.file_name().unwrap_or_default().to_os_string();
+    name.push(".");
+    name.push(suffix);
+    path.with_file_name(name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::BTreeMap;
+
+    type Map = BTreeMap<String, u32>;
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir =
+            std::env::temp_dir().join(format!("netproxy-json-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn saves_atomically_and_reads_back() {
+        let path = temp_dir("save").join("nested").join("store.json");
+        let value = Map::from([("a".to_string(), 1)]);
+
+        assert_eq!(read_json::<Map>(&path).unwrap(), Map::new());
+        save_json(&path, &value).unwrap();
+        assert_eq!(read_json::<Map>(&path).unwrap(), value);
+        assert!(!with_suffix(&path, "tmp").exists());
+    }
+
+    #[test]
+    fn keeps_corrupt_files() {
+        let path = temp_dir("corrupt").join("store.json");
+        fs::write(&path, "{\"a\": 1").unwrap();
+
+        assert!(read_json::<Map>(&path).is_err());
+        assert!(path.exists());
+
+        assert_eq!(load_json::<Map>(&path), Map::new());
+        assert!(!path.exists());
+        assert_eq!(
+            fs::read_to_string(with_suffix(&path, "corrupt")).unwrap(),
+            "{\"a\": 1"
+        );
+    }
+}
@@ -1 +1,5 @@
+pub mod json;
 pub mod paths;
+pub mod process;
+pub mod settings;
+pub mod time;
@@ -1,40 +1,203 @@
+use once_cell::sync::{Lazy, OnceCell};
 use std::env;
-use std::path::PathBuf;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
 
-/// 获取 NetProxy 数据目录
-pub fn get_data_dir() -> PathBuf {
+/// 覆盖数据目录的环境变量
+const DATA_DIR_ENV: &str = "NETPROXY_DATA_DIR";
+/// 覆盖数据目录的命令行参数，支持 `--data-dir <路径>` 与 `--data-dir=<路径>`
+const DATA_DIR_ARG: &str = "--data-dir";
+
+const APP_DIR_NAME: &str = if cfg!(windows) {
+    "NetProxy"
+} else {
+    "netproxy"
+};
+/// 首次初始化完成的标记文件
+const SEEDED_MARKER: &str = ".seeded";
+
+// 打包资源目录（由 Tauri setup 阶段注入）
+static RESOURCE_DIR: OnceCell<PathBuf> = OnceCell::new();
+
+// 目录布局，首次访问时解析
+static LAYOUT: Lazy<Layout> = Lazy::new(Layout::resolve);
+
+struct Layout {
+    config: PathBuf,
+    data: PathBuf,
+    state: PathBuf,
+}
+
+impl Layout {
+    fn resolve() -> Self {
+        // 指定了数据目录时使用便携布局：所有内容都放在同一目录下
+        if let Some(root) = override_dir() {
+            return Self {
+                config: root.join("config"),
+                data: root.clone(),
+                state: root,
+            };
+        }
+
+        // Linux: XDG config/data/state；Windows: Roaming/Local AppData
+        let fallback = exe_dir().join("NetProxy");
+        let data = dirs::data_local_dir()
+            .map(|d| d.join(APP_DIR_NAME))
+            .unwrap_or_else(|| fallback.clone());
+        let config = dirs::config_dir()
+            .map(|d| d.join(APP_DIR_NAME))
+            .unwrap_or_else(|| fallback.join("config"));
+        let state = dirs::state_dir()
+            .map(|d| d.join(APP_DIR_NAME))
+            .unwrap_or_else(|| data.clone());
+
+        Self {
+            config,
+            data,
+            state,
+        }
+    }
+}
+
+/// 从命令行参数或环境变量读取数据目录覆盖
+fn override_dir() -> Option<PathBuf> {
+    let mut args = env::args().skip(1);
+    while let Some(arg) = args.next() {
+        if arg == DATA_DIR_ARG {
+            if let Some(value) = args.next() {
+                return Some(PathBuf::from(value));
+            }
+        } else if let Some(value) = arg
+            .strip_prefix(DATA_DIR_ARG)
+            .and_then(|v| v.strip_prefix('='))
+        {
+            return Some(PathBuf::from(value));
+        }
+    }
+
+    env::var_os(DATA_DIR_ENV)
+        .filter(|v| !v.is_empty())
+        .map(PathBuf::from)
+}
+
+fn exe_dir() -> PathBuf {
+    env::current_exe()
+        .ok()
+        .and_then(|p| p.parent().map(Path::to_path_buf))
+        .unwrap_or_default()
+}
+
+/// 注入 Tauri 的资源目录，需在首次访问路径前调用
+pub fn init(resource_dir: Option<PathBuf>) {
+    if let Some(dir) = resource_dir {
+        let _ = RESOURCE_DIR.set(dir.join("NetProxy"));
+    }
+}
+
+/// 获取打包的 NetProxy 资源目录（只读）
+pub fn get_resource_dir() -> PathBuf {
     if cfg!(debug_assertions) {
-        // 开发环境：使用 src-tauri/resources 目录
-        PathBuf::from(r"D:\workspaces\CodeProjects\windows\NetProxy\src-tauri\resources\NetProxy")
-    } else {
-        // 生产环境：使用 exe 同级的 NetProxy 目录
-        env::current_exe()
-            .unwrap_or_default()
-            .parent()
-            .unwrap_or(&PathBuf::new())
-            .join("NetProxy")
+        // 开发环境：直接使用 src-tauri/resources 目录
+        return PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+            .join("resources")
+            .join("NetProxy");
     }
+
+    RESOURCE_DIR
+        .get()
+        .cloned()
+        .unwrap_or_else(|| exe_dir().join("NetProxy"))
 }
 
-/// 获取 xray.exe 路径
-pub fn get_xray_path() -> PathBuf {
-    get_data_dir().join("bin").join("xray.exe")
+/// 获取配置目录（xray 配置、节点文件）
+pub fn get_config_dir() -> PathBuf {
+    LAYOUT.config.clone()
+}
+
+/// 获取 NetProxy 数据目录（用户自行放置的 bin 等）
+pub fn get_data_dir() -> PathBuf {
+    LAYOUT.data.clone()
+}
+
+/// 获取状态目录（日志、运行状态）
+pub fn get_state_dir() -> PathBuf {
+    LAYOUT.state.clone()
+}
+
+/// 获取日志目录，xray 以状态目录为工作目录，00_log.json 中的相对路径落在这里
+pub fn get_logs_dir() -> PathBuf {
+    get_state_dir().join("logs")
 }
 
-/// 获取 proxylink.exe 路径
-pub fn get_proxylink_path() -> PathBuf {
-    get_data_dir()
-        .join("tools")
-        .join("proxylink")
-        .join("proxylink.exe")
+/// 获取 xray 可执行文件路径（Windows 下为 xray.exe）
+pub fn get_xray_path() -> PathBuf {
+    let name = executable_name("xray");
+    // 都找不到时交给 PATH 查找
+    locate(&Path::new("bin").join(&name)).unwrap_or_else(|| PathBuf::from(name))
 }
 
 /// 获取 confdir 目录
 pub fn get_confdir() -> PathBuf {
-    get_data_dir().join("config").join("xray").join("confdir")
+    get_config_dir().join("xray").join("confdir")
 }
 
 /// 获取 outbounds 目录
 pub fn get_outbounds_dir() -> PathBuf {
-    get_data_dir().join("config").join("xray").join("outbounds")
+    get_config_dir().join("xray").join("outbounds")
+}
+
+/// 按平台补全可执行文件后缀
+fn executable_name(name: &str) -> String {
+    format!("{}{}", name, env::consts::EXE_SUFFIX)
+}
+
+/// 优先使用数据目录中的文件，其次使用打包资源
+fn locate(relative: &Path) -> Option<PathBuf> {
+    [get_data_dir(), get_resource_dir()]
+        .into_iter()
+        .map(|dir| dir.join(relative))
+        .find(|p| p.exists())
+}
+
+/// 用打包资源初始化配置目录，已存在的文件不会被覆盖
+/// - confdir: 每次启动补齐缺失的文件（xray 运行所必需）
+/// - outbounds: 仅首次运行时写入默认节点，之后尊重用户的删除
+pub fn seed_from_resources() -> io::Result<()> {
+    let source = get_resource_dir().join("config").join("xray");
+    let config_dir = get_config_dir();
+    let marker = config_dir.join(SEEDED_MARKER);
+
+    fs::create_dir_all(get_logs_dir())?;
+    if !source.exists() {
+        return Ok(());
+    }
+
+    copy_missing(&source.join("confdir"), &get_confdir())?;
+    if !marker.exists() {
+        copy_missing(&source.join("outbounds"), &get_outbounds_dir())?;
+        fs::write(&marker, "")?;
+    }
+
+    Ok(())
+}
+
+/// 递归复制目录，跳过目标中已存在的文件
+fn copy_missing(src: &Path, dst: &Path) -> io::Result<()> {
+    if !src.exists() {
+        return Ok(());
+    }
+    fs::create_dir_all(dst)?;
+
+    for entry in fs::read_dir(src)?.flatten() {
+        let path = entry.path();
+        let target = dst.join(entry.file_name());
+        if path.is_dir() {
+            copy_missing(&path, &target)?;
+        } else if !target.exists() {
+            fs::copy(&path, &target)?;
+        }
+    }
+
+    Ok(())
 }
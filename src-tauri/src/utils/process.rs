@@ -0,0 +1,49 @@
+use std::process::{Child, Command};
+
+#[cfg(windows)]
+const CREATE_NO_WINDOW: u32 = 0x08000000;
+
+/// 配置后台子进程的平台相关参数
+/// - Windows: 无窗口启动
+/// - Unix: 放入独立进程组，便于退出时整组回收
+pub fn configure_background(command: &mut Command) -> &mut Command {
+    #[cfg(windows)]
+    {
+        use std::os::windows::process::CommandExt;
+        command.creation_flags(CREATE_NO_WINDOW);
+    }
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::process::CommandExt;
+        command.process_group(0);
+    }
+
+    command
+}
+
+/// 结束子进程（Unix 下连同其进程组）并等待回收
+pub fn terminate(child: &mut Child) {
+    #[cfg(unix)]
+    {
+        // 进程组 ID 与子进程 PID 相同，先发送 SIGTERM 让其正常退出
+        let pgid = child.id() as libc::pid_t;
+        unsafe {
+            libc::killpg(pgid, libc::SIGTERM);
+        }
+
+        for _ in 0..20 {
+            if let Ok(Some(_)) = child.try_wait() {
+                return;
+            }
+            std::thread::sleep(std::time::Duration::from_millis(50));
+        }
+
+        unsafe {
+            libc::killpg(pgid, libc::SIGKILL);
+        }
+    }
+
+    let _ = child.kill();
+    let _ = child.wait();
+}
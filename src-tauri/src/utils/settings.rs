@@ -0,0 +1,13 @@
+// 配置目录中按文件名保存的 JSON 设置
+use super::{json, paths};
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+
+/// 读取设置，文件缺失或损坏时使用默认值
+pub fn load<T: DeserializeOwned + Default>(file_name: &str) -> T {
+    json::load_json(&paths::get_config_dir().join(file_name))
+}
+
+pub fn save<T: Serialize>(file_name: &str, settings: &T) -> Result<(), Box<dyn std::error::Error>> {
+    json::save_json(&paths::get_config_dir().join(file_name), settings)
+}
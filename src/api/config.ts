@@ -1,4 +1,4 @@
-import type { DnsConfig, RoutingConfig } from '../types';
+import type { DnsConfig, RoutingConfig, CoreLogLine } from '../types';
 
 // 检查是否在 Tauri 环境中
 const isTauri = () => {
@@ -60,3 +60,23 @@ export async function getXrayLog(logType: 'access' | 'error'): Promise<string> {
     }
     return '';
 }
+
+// 订阅 xray 实时输出，返回已缓冲的内容和取消订阅函数
+export async function subscribeCoreLog(
+    onLine: (line: CoreLogLine) => void
+): Promise<{ backlog: CoreLogLine[]; unsubscribe: () => Promise<void> }> {
+    const invoke = await getInvoke();
+    if (!invoke) {
+        return { backlog: [], unsubscribe: async () => {} };
+    }
+    const { Channel } = await import('@tauri-apps/api/core');
+    const channel = new Channel<CoreLogLine>();
+    channel.onmessage = onLine;
+    const backlog: CoreLogLine[] = await invoke('subscribe_core_log', { channel });
+    return {
+        backlog,
+        unsubscribe: async () => {
+            await invoke('unsubscribe_core_log', { channelId: channel.id });
+        },
+    };
+}
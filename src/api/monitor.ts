@@ -1,4 +1,19 @@
-import type { TrafficStats, IpInfo } from '../types';
+import type {
+    TrafficStats,
+    TrafficSample,
+    TrafficBucket,
+    TrafficGranularity,
+    TrafficCapSettings,
+    TrafficCapStatus,
+    NetworkInterface,
+    InterfaceSettings,
+    IpInfo,
+    IpCheckSettings,
+    DailyUsage,
+    UsagePeriod,
+    UsageReport,
+    Connection
+} from '../types';
 
 const isTauri = () => {
     return typeof window !== 'undefined' && '__TAURI_INTERNALS__' in window;
@@ -19,20 +34,153 @@ export async function getTrafficStats(): Promise<TrafficStats> {
     }
     // 浏览器开发环境的模拟数据
     return {
-        upload_speed: 0,
-        download_speed: 0,
-        upload_total: 0,
-        download_total: 0
+        uploadSpeed: 0,
+        downloadSpeed: 0,
+        uploadTotal: 0,
+        downloadTotal: 0,
+        source: 'system',
+        inbounds: [],
+        outbounds: [],
+        interfaces: []
     };
 }
 
-export async function getIpInfo(): Promise<IpInfo> {
+export async function getTrafficHistory(): Promise<TrafficSample[]> {
     const invoke = await getInvoke();
     if (invoke) {
-        return await invoke('get_ip_info');
+        return await invoke('get_traffic_history');
+    }
+    return [];
+}
+
+export async function listNetworkInterfaces(): Promise<NetworkInterface[]> {
+    const invoke = await getInvoke();
+    if (invoke) {
+        return await invoke('list_network_interfaces');
+    }
+    return [];
+}
+
+export async function getInterfaceSettings(): Promise<InterfaceSettings> {
+    const invoke = await getInvoke();
+    if (invoke) {
+        return await invoke('get_interface_settings');
+    }
+    return { included: [], excluded: [] };
+}
+
+export async function saveInterfaceSettings(settings: InterfaceSettings): Promise<void> {
+    const invoke = await getInvoke();
+    if (invoke) {
+        await invoke('save_interface_settings', { settings });
+    }
+}
+
+export async function getTrafficRollups(granularity: TrafficGranularity, count: number): Promise<TrafficBucket[]> {
+    const invoke = await getInvoke();
+    if (invoke) {
+        return await invoke('get_traffic_rollups', { granularity, count });
+    }
+    return [];
+}
+
+export async function getTrafficCapSettings(): Promise<TrafficCapSettings | null> {
+    const invoke = await getInvoke();
+    if (invoke) {
+        return await invoke('get_traffic_cap_settings');
+    }
+    return null;
+}
+
+export async function saveTrafficCapSettings(settings: TrafficCapSettings): Promise<void> {
+    const invoke = await getInvoke();
+    if (invoke) {
+        await invoke('save_traffic_cap_settings', { settings });
+    }
+}
+
+export async function getTrafficCapStatus(): Promise<TrafficCapStatus | null> {
+    const invoke = await getInvoke();
+    if (invoke) {
+        return await invoke('get_traffic_cap_status');
+    }
+    return null;
+}
+
+// 订阅后台每秒推送的 traffic 事件，返回取消订阅的函数
+export async function onTraffic(callback: (stats: TrafficStats) => void): Promise<() => void> {
+    if (!isTauri()) {
+        return () => {};
+    }
+    const { listen } = await import('@tauri-apps/api/event');
+    return await listen<TrafficStats>('traffic', (event) => callback(event.payload));
+}
+
+export async function getIpInfo(refresh = false): Promise<IpInfo> {
+    const invoke = await getInvoke();
+    if (invoke) {
+        return await invoke('get_ip_info', { refresh });
     }
     return {
         external_ip: '127.0.0.1 (Dev)',
-        internal_ip: '192.168.1.x (Dev)'
+        internal_ip: '192.168.1.x (Dev)',
+        direct: null,
+        proxy: null,
+        direct_error: null,
+        proxy_error: null,
+        node: null
     };
 }
+
+export async function getIpCheckSettings(): Promise<IpCheckSettings | null> {
+    const invoke = await getInvoke();
+    if (invoke) {
+        return await invoke('get_ip_check_settings');
+    }
+    return null;
+}
+
+export async function saveIpCheckSettings(settings: IpCheckSettings): Promise<void> {
+    const invoke = await getInvoke();
+    if (invoke) {
+        await invoke('save_ip_check_settings', { settings });
+    }
+}
+
+export async function getTrafficUsage(period: UsagePeriod): Promise<UsageReport | null> {
+    const invoke = await getInvoke();
+    if (invoke) {
+        return await invoke('get_traffic_usage', { period });
+    }
+    return null;
+}
+
+export async function getDailyTrafficUsage(days: number): Promise<DailyUsage[]> {
+    const invoke = await getInvoke();
+    if (invoke) {
+        return await invoke('get_daily_traffic_usage', { days });
+    }
+    return [];
+}
+
+export async function clearTrafficUsage(): Promise<void> {
+    const invoke = await getInvoke();
+    if (invoke) {
+        await invoke('clear_traffic_usage');
+    }
+}
+
+export async function getConnections(): Promise<Connection[]> {
+    const invoke = await getInvoke();
+    if (invoke) {
+        return await invoke('get_connections');
+    }
+    return [];
+}
+
+export async function clearConnections(): Promise<void> {
+    const invoke = await getInvoke();
+    if (invoke) {
+        await invoke('clear_connections');
+    }
+}
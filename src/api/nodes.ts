@@ -1,4 +1,4 @@
-import type { NodeInfo } from '../types';
+import type { NodeInfo, LatencyBatchSummary, LatencyProgress } from '../types';
 
 // 检查是否在 Tauri 环境中
 const isTauri = () => {
@@ -45,10 +45,50 @@ export async function deleteNode(filePath: string): Promise<void> {
     }
 }
 
-export async function pingNode(address: string, port: number): Promise<number> {
+export async function pingNode(address: string, port: number, nodeFile?: string): Promise<number> {
     const invoke = await getInvoke();
     if (invoke) {
-        return await invoke('ping_node', { address, port });
+        return await invoke('ping_node', { address, port, nodeFile });
     }
     return -1;
 }
+
+// 批量真实延迟测试，新批次会取消尚未结束的旧批次
+export async function testNodesLatency(options: {
+    nodeFiles?: string[];
+    subscription?: string;
+    concurrency?: number;
+    timeoutMs?: number;
+}): Promise<LatencyBatchSummary | null> {
+    const invoke = await getInvoke();
+    if (invoke) {
+        return await invoke('test_nodes_latency', options);
+    }
+    return null;
+}
+
+export async function cancelLatencyTest(): Promise<boolean> {
+    const invoke = await getInvoke();
+    if (invoke) {
+        return await invoke('cancel_latency_test');
+    }
+    return false;
+}
+
+// 监听单个节点测试完成事件，返回取消监听的函数
+export async function onLatencyProgress(callback: (progress: LatencyProgress) => void): Promise<() => void> {
+    if (!isTauri()) {
+        return () => {};
+    }
+    const { listen } = await import('@tauri-apps/api/event');
+    return await listen<LatencyProgress>('latency-progress', (event) => callback(event.payload));
+}
+
+// 监听批量测试结束事件，返回取消监听的函数
+export async function onLatencyFinished(callback: (summary: LatencyBatchSummary) => void): Promise<() => void> {
+    if (!isTauri()) {
+        return () => {};
+    }
+    const { listen } = await import('@tauri-apps/api/event');
+    return await listen<LatencyBatchSummary>('latency-finished', (event) => callback(event.payload));
+}
@@ -1,4 +1,4 @@
-import type { ProxyStatus } from '../types';
+import type { PortCheck, ProxyStatus } from '../types';
 
 // 检查是否在 Tauri 环境中
 const isTauri = () => {
@@ -63,3 +63,11 @@ export async function getSelectedNode(): Promise<string | null> {
     }
     return null;
 }
+
+export async function checkPorts(): Promise<PortCheck[]> {
+    const invoke = await getInvoke();
+    if (invoke) {
+        return await invoke('check_ports');
+    }
+    return [];
+}
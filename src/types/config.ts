@@ -22,6 +22,7 @@ export interface RoutingRule {
     type: string;
     inboundTag?: string[];
     outboundTag: string;
+    balancerTag?: string;
     domain?: string[];
     ip?: string[];
     port?: string;
@@ -1,11 +1,156 @@
+export interface TrafficCounter {
+    tag: string;
+    uplink: number;
+    downlink: number;
+    uploadSpeed: number;
+    downloadSpeed: number;
+}
+
 export interface TrafficStats {
-    upload_speed: number;
-    download_speed: number;
-    upload_total: number;
-    download_total: number;
+    uploadSpeed: number;
+    downloadSpeed: number;
+    uploadTotal: number;
+    downloadTotal: number;
+    source: 'xray' | 'system';
+    inbounds: TrafficCounter[];
+    outbounds: TrafficCounter[];
+    interfaces: TrafficCounter[];
+}
+
+export interface TrafficSample {
+    timestamp: number;
+    uploadSpeed: number;
+    downloadSpeed: number;
+    uploadTotal: number;
+    downloadTotal: number;
+    source: 'xray' | 'system';
+}
+
+export type IpProvider = 'ip-api' | 'ip-info' | 'ip-api-co' | 'ip-sb' | 'ipify';
+
+export interface IpLookup {
+    ip: string;
+    country: string | null;
+    countryCode: string | null;
+    region: string | null;
+    city: string | null;
+    asn: string | null;
+    isp: string | null;
+    provider: IpProvider | null;
 }
 
 export interface IpInfo {
     external_ip: string | null;
     internal_ip: string | null;
+    direct: IpLookup | null;
+    proxy: IpLookup | null;
+    direct_error: string | null;
+    proxy_error: string | null;
+    node: string | null;
+}
+
+export interface IpCheckSettings {
+    providers: IpProvider[];
+    timeoutMs: number;
+}
+
+export interface ByteCount {
+    uplink: number;
+    downlink: number;
+}
+
+export interface DailyUsage {
+    date: string;
+    system: ByteCount;
+    proxy: ByteCount;
+    direct: ByteCount;
+    block: ByteCount;
+    other: ByteCount;
+    nodes: Record<string, ByteCount>;
+    subscriptions: Record<string, ByteCount>;
+}
+
+export type UsagePeriod = 'day' | 'week' | 'month';
+
+export interface UsageEntry {
+    name: string;
+    uplink: number;
+    downlink: number;
+}
+
+export interface UsageReport {
+    period: UsagePeriod;
+    from: string;
+    to: string;
+    proxy: ByteCount;
+    direct: ByteCount;
+    block: ByteCount;
+    other: ByteCount;
+    nodes: UsageEntry[];
+    subscriptions: UsageEntry[];
+}
+
+// certain 为 false 时规则含 geosite/geoip 等条件，仅为推测
+export interface MatchedRule {
+    label: string;
+    certain: boolean;
+}
+
+export interface Connection {
+    id: number;
+    network: string;
+    source: string;
+    domain: string | null;
+    destinationIp: string | null;
+    destinationPort: number | null;
+    inboundTag: string;
+    outboundTag: string;
+    protocol: string | null;
+    rule: MatchedRule | null;
+    rejected: boolean;
+    start: number;
+    origin: 'routing' | 'accessLog';
+}
+
+export type TrafficGranularity = 'hour' | 'day' | 'month';
+
+export interface TrafficBucket {
+    start: string;
+    system: ByteCount;
+    proxy: ByteCount;
+}
+
+export type CapSource = 'system' | 'proxy';
+
+export interface TrafficCapSettings {
+    enabled: boolean;
+    capBytes: number;
+    warnPercent: number;
+    resetDay: number;
+    source: CapSource;
+}
+
+export interface TrafficCapStatus {
+    periodStart: string;
+    used: number;
+    capBytes: number;
+    percent: number;
+    source: CapSource;
+    exceeded: boolean;
+}
+
+export type InterfaceKind = 'loopback' | 'ethernet' | 'wireless' | 'tun' | 'virtual' | 'other';
+
+export interface NetworkInterface {
+    name: string;
+    kind: InterfaceKind;
+    macAddress: string;
+    included: boolean;
+    transmitted: number;
+    received: number;
+}
+
+export interface InterfaceSettings {
+    included: string[];
+    excluded: string[];
 }
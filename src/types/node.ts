@@ -16,3 +16,46 @@ export interface OutboundConfig {
     settings?: Record<string, unknown>;
     streamSettings?: Record<string, unknown>;
 }
+
+export type LatencyErrorKind =
+    | 'invalidNode'
+    | 'coreFailed'
+    | 'timeout'
+    | 'proxyFailed'
+    | 'badStatus'
+    | 'network';
+
+export interface LatencyError {
+    kind: LatencyErrorKind;
+    message: string;
+}
+
+// 单个节点的真实延迟测试结果
+export interface LatencyResult {
+    nodeFile: string;
+    success: boolean;
+    // 首次请求到收到响应头的总耗时
+    delayMs: number | null;
+    // 估算的建立代理连接耗时（首次请求减去复用连接的请求）
+    estimatedHandshakeMs: number | null;
+    // 复用已建立的连接再次请求的耗时
+    warmMs: number | null;
+    error: LatencyError | null;
+}
+
+// 批量测试中单个节点完成时的事件
+export interface LatencyProgress {
+    batchId: number;
+    completed: number;
+    total: number;
+    result: LatencyResult;
+}
+
+// 批量测试结束时的汇总
+export interface LatencyBatchSummary {
+    batchId: number;
+    total: number;
+    completed: number;
+    cancelled: boolean;
+    results: LatencyResult[];
+}
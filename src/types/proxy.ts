@@ -3,6 +3,39 @@ export interface ProxyStatus {
     systemProxy: boolean;
     currentNode: string | null;
     port: number;
+    lastExit?: CoreExit | null;
+    error?: string | null;
+}
+
+export interface CoreExit {
+    nodeFile: string;
+    code: number | null;
+    message: string;
+    at: number;
+    outputTail: string[];
+    restartInMs: number | null;
+}
+
+// xray 输出或应用消息的一行，app 为应用自身的消息
+export interface CoreLogLine {
+    seq: number;
+    timestamp: number;
+    stream: 'stdout' | 'stderr' | 'app';
+    line: string;
+}
+
+export interface PortOwner {
+    pid: number;
+    name: string;
+    exe: string | null;
+}
+
+export interface PortCheck {
+    tag: string;
+    listen: string;
+    port: number;
+    available: boolean;
+    owner: PortOwner | null;
 }
 
 export interface ProxyConfig {